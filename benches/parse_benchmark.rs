@@ -0,0 +1,83 @@
+//! Throughput of turning a 500x500 all wall image into a level, stage by
+//! stage. Every benchmark counts pixels as elements, so criterion reports
+//! and plots megapixels per second as `Melem/s`.
+//!
+//! The merge phases aren't functions of their own, they are timed from the
+//! [`ScanProgress`] reports at the end of the horizontal and vertical pass.
+
+use std::{
+    hint::black_box,
+    io::Cursor,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use maze_lvl_maker::{scan_grid, scan_grid_with_progress, Grid, ScanOptions, ScanPhase};
+
+const SIZE: u32 = 500;
+
+/// Image where every pixel is a wall.
+fn all_walls() -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_pixel(SIZE, SIZE, Rgba([0, 0, 0, 255])))
+}
+
+fn encode_png(img: &DynamicImage) -> Vec<u8> {
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .expect("encode png");
+    png
+}
+
+/// Time of one scan spent in the horizontal pass, the vertical pass and
+/// everything after them.
+///
+/// The horizontal pass includes finding the start, end and checkpoints, the
+/// rest includes filtering and pruning covered walls, sorting and the dynamic
+/// wall pass, which finds nothing in an all wall image.
+fn timed_scan(grid: &Grid) -> [Duration; 3] {
+    let start = Instant::now();
+    let (mut horizontal, mut vertical) = (start, start);
+    let lvl = scan_grid_with_progress(grid, ScanOptions::default(), |progress| {
+        if progress.done == progress.total {
+            match progress.phase {
+                ScanPhase::Horizontal => horizontal = Instant::now(),
+                ScanPhase::Vertical => vertical = Instant::now(),
+            }
+        }
+    });
+    let end = Instant::now();
+    black_box(lvl);
+
+    [horizontal - start, vertical - horizontal, end - vertical]
+}
+
+fn parse(c: &mut Criterion) {
+    let img = all_walls();
+    let png = encode_png(&img);
+    let grid = Grid::from_image(&img);
+    let lvl = scan_grid(&grid, ScanOptions::default());
+
+    let mut group = c.benchmark_group("parse_500x500");
+    group.throughput(Throughput::Elements(u64::from(SIZE * SIZE)));
+
+    group.bench_function("decode_png", |b| {
+        b.iter(|| image::load_from_memory(black_box(&png)).expect("decode png"));
+    });
+    for (phase, name) in ["horizontal_scan", "vertical_scan", "filter_dedup"]
+        .into_iter()
+        .enumerate()
+    {
+        group.bench_function(name, |b| {
+            b.iter_custom(|iters| (0..iters).map(|_| timed_scan(&grid)[phase]).sum());
+        });
+    }
+    group.bench_function("serialize_json", |b| {
+        b.iter(|| serde_json::to_string(black_box(&lvl)).expect("serialize level"));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
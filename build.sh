@@ -0,0 +1,21 @@
+#!/usr/bin/env sh
+# Build the shared libraries, which `cargo build` leaves out so the `core`
+# feature also builds without `std`:
+#   ./build.sh [wasm]  the browser package in `pkg/`, which exports `parse_image_bytes`
+#   ./build.sh ffi     the C library of `maze_lvl_maker.h` in `target/release/`
+set -eu
+
+cd "$(dirname "$0")"
+case "${1:-wasm}" in
+wasm)
+    cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+    wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/maze_lvl_maker.wasm
+    ;;
+ffi)
+    cargo rustc --lib --release --features ffi --crate-type cdylib
+    ;;
+*)
+    echo "usage: $0 [wasm|ffi]" >&2
+    exit 1
+    ;;
+esac
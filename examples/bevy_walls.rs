@@ -0,0 +1,73 @@
+//! Loads `assets/example.lvl.json` and spawns a sprite for every wall square,
+//! the start in green, the end in red and checkpoints in blue.
+//!
+//! The level asset only needs the `bevy` feature, the window and sprites
+//! need more of Bevy:
+//!
+//! ```text
+//! cargo run --example bevy_walls --features \
+//!     bevy,bevy/2d_api,bevy/bevy_sprite_render,bevy/bevy_winit,bevy/x11
+//! ```
+
+use bevy::prelude::*;
+use maze_lvl_maker::{Lvl, LvlPlugin, Point};
+
+/// Pixels per square.
+const SQUARE: f32 = 16.0;
+
+#[derive(Resource)]
+struct Level(Handle<Lvl>);
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, LvlPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, spawn_level)
+        .run();
+}
+
+fn setup(mut commands: Commands, assets: Res<AssetServer>) {
+    commands.spawn(Camera2d);
+    commands.insert_resource(Level(assets.load("example.lvl.json")));
+}
+
+fn spawn_level(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<Lvl>>,
+    level: Res<Level>,
+    levels: Res<Assets<Lvl>>,
+) {
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(&level.0) {
+            continue;
+        }
+        let Some(lvl) = levels.get(&level.0) else {
+            continue;
+        };
+
+        // Centered on the camera, with y up
+        let position = |point: Point| {
+            Vec3::new(
+                (point.x as f32 - lvl.width as f32 / 2.0) * SQUARE,
+                (lvl.height as f32 / 2.0 - point.y as f32) * SQUARE,
+                0.0,
+            )
+        };
+        let mut square = |point: Point, color: Color| {
+            commands.spawn((
+                Sprite::from_color(color, Vec2::splat(SQUARE)),
+                Transform::from_translation(position(point)),
+            ));
+        };
+        for wall in &lvl.walls {
+            for point in wall.squares() {
+                square(point, Color::WHITE);
+            }
+        }
+        for checkpoint in &lvl.checkpoints {
+            square(*checkpoint, Color::srgb(0.0, 0.0, 1.0));
+        }
+        square(lvl.start, Color::srgb(0.0, 1.0, 0.0));
+        square(lvl.end, Color::srgb(1.0, 0.0, 0.0));
+    }
+}
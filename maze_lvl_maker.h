@@ -0,0 +1,56 @@
+#ifndef MAZE_LVL_MAKER_H
+#define MAZE_LVL_MAKER_H
+
+/* Generated by cbindgen from src/ffi.rs, don't edit by hand. */
+
+#include <stddef.h>
+
+// Success.
+#define MAZE_LVL_OK 0
+
+// A pointer argument is null.
+#define MAZE_LVL_ERR_NULL -1
+
+// The path isn't valid UTF-8.
+#define MAZE_LVL_ERR_PATH -2
+
+// The image can't be read or decoded.
+#define MAZE_LVL_ERR_IMAGE -3
+
+// The buffer can't hold the json and its terminating null, `out_len` is
+// set to the size needed.
+#define MAZE_LVL_ERR_BUFFER_TOO_SMALL -4
+
+// The parser panicked.
+#define MAZE_LVL_ERR_PANIC -5
+
+
+
+// Scan the image at `path` with the default options and write the level as
+// null terminated json into `out_json`.
+//
+// `*out_len` is the size of `out_json` in bytes when called and the length
+// of the json without the null afterwards. When the buffer is too small
+// nothing is written, [`MAZE_LVL_ERR_BUFFER_TOO_SMALL`] is returned and
+// `*out_len` is the size to retry with.
+//
+// # Safety
+//
+// `path` must be a null terminated string, `out_len` must be valid for
+// reads and writes and `out_json` must be valid for writes of `*out_len`
+// bytes.
+int maze_lvl_parse_image(const char *path, char *out_json, size_t *out_len);
+
+// Message of the last error on this thread, null if there was none. Free
+// it with [`maze_lvl_free_string`].
+char *maze_lvl_last_error(void);
+
+// Free a string returned by this library, null is ignored.
+//
+// # Safety
+//
+// `string` must be null or come from [`maze_lvl_last_error`] and not be
+// freed already.
+void maze_lvl_free_string(char *string);
+
+#endif  /* MAZE_LVL_MAKER_H */
@@ -0,0 +1,646 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashMap},
+    fmt::Display,
+};
+
+use serde::Serialize;
+
+use crate::{Grid, Lvl, Point, SquareType, Wall};
+
+/// How the walls meet at a [`Junction`].
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JunctionKind {
+    /// One horizontal and one vertical neighbor
+    Corner,
+    /// Three neighbors
+    T,
+    /// Four neighbors
+    Cross,
+}
+
+/// Wall square where walls running in different directions meet.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Junction {
+    pub x: u32,
+    pub y: u32,
+    pub kind: JunctionKind,
+}
+
+/// Run of squares along one edge of the level where players can walk off
+/// the map. `start` and `end` share a row or a column.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorderGap {
+    pub start: Point,
+    pub end: Point,
+}
+
+impl Display for BorderGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (start, end) = (self.start, self.end);
+        if start.y == end.y && start.x != end.x {
+            write!(f, "border open at y={}, x={}..={}", start.y, start.x, end.x)
+        } else if start == end {
+            write!(f, "border open at x={}, y={}", start.x, start.y)
+        } else {
+            write!(f, "border open at x={}, y={}..={}", start.x, start.y, end.y)
+        }
+    }
+}
+
+/// Rectangular open area, both corners are inside the room.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Room {
+    pub top_left: Point,
+    pub bottom_right: Point,
+    pub area: u32,
+}
+
+/// Two walls that share at least one square, see
+/// [`Lvl::find_overlapping_walls`].
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallOverlap {
+    /// Wall that comes first in the level
+    pub wall_a: Wall,
+    pub wall_b: Wall,
+    /// First square of `wall_b` that `wall_a` covers too
+    pub overlap_point: Point,
+}
+
+impl Lvl {
+    /// Runs of border squares that are open to the outside: not a wall and
+    /// without a wall right next to them on the inside.
+    ///
+    /// Transparent pixels scan as walls, so levels surrounded by void
+    /// aren't reported.
+    pub fn border_gaps(&self) -> Vec<BorderGap> {
+        let grid = Grid::from_lvl(self);
+        let (width, height) = (i64::from(grid.width()), i64::from(grid.height()));
+        let is_wall = |(x, y): (i64, i64)| {
+            (0..width).contains(&x)
+                && (0..height).contains(&y)
+                && grid.get(x as u32, y as u32) == SquareType::Wall
+        };
+
+        // Each edge as its squares and the step inwards, the columns leave
+        // out the corners that the rows already cover. Edges that wrap lead
+        // to the opposite one instead of outside.
+        let rows = !self.wrap.wraps_y();
+        let columns = if rows { 1..height - 1 } else { 0..height };
+        let edges = [
+            ((0..width).map(|x| (x, 0)).collect::<Vec<_>>(), (0, 1)),
+            ((0..width).map(|x| (x, height - 1)).collect(), (0, -1)),
+            (columns.clone().map(|y| (0, y)).collect(), (1, 0)),
+            (columns.map(|y| (width - 1, y)).collect(), (-1, 0)),
+        ];
+        let wrapped = [!rows, !rows, self.wrap.wraps_x(), self.wrap.wraps_x()];
+
+        let mut gaps = Vec::new();
+        for ((squares, (dx, dy)), _) in edges
+            .into_iter()
+            .zip(wrapped)
+            .filter(|(_, wrapped)| !wrapped)
+        {
+            let mut run: Option<(Point, Point)> = None;
+            for (x, y) in squares {
+                let open = !is_wall((x, y)) && !is_wall((x + dx, y + dy));
+                let point = Point::new(x as u32, y as u32);
+                match (open, run) {
+                    (true, Some((start, _))) => run = Some((start, point)),
+                    (true, None) => run = Some((point, point)),
+                    (false, Some((start, end))) => {
+                        gaps.push(BorderGap { start, end });
+                        run = None;
+                    }
+                    (false, None) => (),
+                }
+            }
+            if let Some((start, end)) = run {
+                gaps.push(BorderGap { start, end });
+            }
+        }
+
+        gaps
+    }
+
+    /// Close the outer border with walls along every edge that doesn't wrap.
+    ///
+    /// Each edge gets one wall across its empty squares, split around the
+    /// walls, dynamic walls, start, end and checkpoints already on it so
+    /// those stay as they are. The columns leave out the corners that the
+    /// rows cover. The walls are appended after the scanned ones.
+    pub fn add_border_walls(&mut self) {
+        let grid = Grid::from_lvl(self);
+        let (width, height) = (self.width, self.height);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let rows = !self.wrap.wraps_y();
+        let columns = if rows { 1..height - 1 } else { 0..height };
+        let mut edges: Vec<Vec<Point>> = Vec::new();
+        if rows {
+            edges.push((0..width).map(|x| Point::new(x, 0)).collect());
+            if height > 1 {
+                edges.push((0..width).map(|x| Point::new(x, height - 1)).collect());
+            }
+        }
+        if !self.wrap.wraps_x() {
+            edges.push(columns.clone().map(|y| Point::new(0, y)).collect());
+            if width > 1 {
+                edges.push(columns.map(|y| Point::new(width - 1, y)).collect());
+            }
+        }
+
+        for squares in edges {
+            let runs = squares.split(|point| grid.get(point.x, point.y) != SquareType::Empty);
+            for run in runs {
+                if let (Some(first), Some(last)) = (run.first(), run.last()) {
+                    self.walls.push(Wall::between(*first, *last));
+                }
+            }
+        }
+    }
+
+    /// Index in `walls` of the wall covering each `(x, y)` square, to trace a
+    /// square of the source image back to the wall it was scanned into.
+    /// Squares that more than one wall covers map to the first of them.
+    /// The squares are pixels of the image unless it was scaled, cropped
+    /// or transformed.
+    pub fn source_map(&self) -> HashMap<(u32, u32), usize> {
+        let mut map = HashMap::new();
+        for (index, wall) in self.walls.iter().enumerate() {
+            for piece in wall.split_at_seam(self.width, self.height) {
+                for square in piece.squares() {
+                    map.entry((square.x, square.y)).or_insert(index);
+                }
+            }
+        }
+        map
+    }
+
+    /// Squares covered by each wall, indexed like `walls`, to highlight all
+    /// squares of a wall picked in an editor. Walls across the seam of a
+    /// wrapping level cover the squares on both sides of it. Unlike in
+    /// [`Lvl::source_map`], a square of more than one wall is listed for
+    /// each of them.
+    pub fn inverse_source_map(&self) -> Vec<Vec<(u32, u32)>> {
+        self.walls
+            .iter()
+            .map(|wall| {
+                wall.split_at_seam(self.width, self.height)
+                    .into_iter()
+                    .flat_map(Wall::squares)
+                    .map(|square| (square.x, square.y))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Length in steps of the shortest path from start to end, `None` if
+    /// the end can't be reached.
+    pub fn shortest_path_length(&self) -> Option<u32> {
+        Grid::from_lvl(self).distance(self.start, self.end)
+    }
+
+    /// Squares of a shortest route from the start through the checkpoints,
+    /// in the order they're listed, to the end. Every square is one step
+    /// from the one before it, across the seam in levels that wrap, and
+    /// dynamic walls are walked through like [`Lvl::is_solvable`] does.
+    /// `None` when a part of the route can't be walked.
+    pub fn solution(&self) -> Option<Vec<Point>> {
+        let grid = Grid::from_lvl(self);
+        let stops: Vec<Point> = [self.start]
+            .into_iter()
+            .chain(self.checkpoints.iter().copied())
+            .chain([self.end])
+            .collect();
+
+        let mut path = vec![self.start];
+        for leg in stops.windows(2) {
+            path.extend(grid.shortest_path(leg[0], leg[1])?.into_iter().skip(1));
+        }
+        Some(path)
+    }
+
+    /// Fewest steps any path from start to end could take if there were no
+    /// walls, a lower bound of [`Lvl::shortest_path_length`] that needs no
+    /// search. Across wrapping edges the shorter way around counts. A
+    /// shortest path of the same length runs without detours.
+    ///
+    /// Start and end are always set, `None` only when the distance doesn't
+    /// fit in a `u32`.
+    ///
+    /// ```
+    /// use maze_lvl_maker::{LvlBuilder, Point};
+    ///
+    /// let lvl = LvlBuilder::new(8, 8)
+    ///     .start(Point::new(1, 1))
+    ///     .end(Point::new(4, 6))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(lvl.manhattan_path_length(), Some(8));
+    /// assert_eq!(lvl.shortest_path_length(), Some(8));
+    /// ```
+    pub fn manhattan_path_length(&self) -> Option<u32> {
+        // Same narrow grids as `Grid::moves`, they reach the far edge anyway
+        let around = |wraps: bool, size: u32, distance: u32| {
+            if wraps && size > 2 {
+                distance.min(size - distance)
+            } else {
+                distance
+            }
+        };
+        let dx = around(
+            self.wrap.wraps_x(),
+            self.width,
+            self.start.x.abs_diff(self.end.x),
+        );
+        let dy = around(
+            self.wrap.wraps_y(),
+            self.height,
+            self.start.y.abs_diff(self.end.y),
+        );
+        dx.checked_add(dy)
+    }
+
+    /// Start, end and the checkpoints in that order, the nodes of
+    /// [`Lvl::checkpoint_graph`].
+    pub fn checkpoint_graph_nodes(&self) -> Vec<Point> {
+        [self.start, self.end]
+            .into_iter()
+            .chain(self.checkpoints.iter().copied())
+            .collect()
+    }
+
+    /// Shortest path lengths between start, end and every checkpoint.
+    ///
+    /// Node 0 is the start, node 1 the end and node `2 + i` checkpoint `i`.
+    /// Every node lists `(to_node, shortest_path_length)` for the other
+    /// nodes it can reach. Runs one breadth first search per node.
+    pub fn checkpoint_graph(&self) -> Vec<Vec<(usize, u32)>> {
+        let grid = Grid::from_lvl(self);
+        let nodes = self.checkpoint_graph_nodes();
+
+        nodes
+            .iter()
+            .enumerate()
+            .map(|(from, node)| {
+                let distances = grid.distances_from(*node);
+                nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|(to, _)| *to != from)
+                    .filter_map(|(to, point)| {
+                        distances[grid.index(point.x, point.y)].map(|distance| (to, distance))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Regions of connected passable squares, largest first.
+    pub fn connected_components(&self) -> Vec<Vec<Point>> {
+        Grid::from_lvl(self).components()
+    }
+
+    /// Number of squares in the largest region of connected passable
+    /// squares, the whole area for a level without walls.
+    pub fn largest_open_area(&self) -> u64 {
+        self.connected_components()
+            .first()
+            .map_or(0, |component| component.len() as u64)
+    }
+
+    /// A level can only be solved when start, end and every required
+    /// checkpoint share a region. Optional checkpoints may be unreachable.
+    pub fn is_solvable(&self) -> bool {
+        let grid = Grid::from_lvl(self);
+        let mut component_of = vec![None; grid.square_count()];
+        for (label, component) in grid.components().iter().enumerate() {
+            for point in component {
+                component_of[grid.index(point.x, point.y)] = Some(label);
+            }
+        }
+        let component = |point: Point| {
+            (point.x < grid.width() && point.y < grid.height())
+                .then(|| component_of[grid.index(point.x, point.y)])
+                .flatten()
+        };
+
+        component(self.start).is_some_and(|start| {
+            component(self.end) == Some(start)
+                && self
+                    .checkpoints
+                    .iter()
+                    .all(|checkpoint| component(*checkpoint) == Some(start))
+        })
+    }
+
+    /// Whether every route to the end goes through a dynamic wall, which
+    /// makes solving it depend on timing that isn't checked here.
+    pub fn needs_dynamic_walls(&self) -> bool {
+        if self.dynamic_walls.is_empty() || !self.is_solvable() {
+            return false;
+        }
+
+        let mut solid = self.clone();
+        solid.walls.extend(
+            solid
+                .dynamic_walls
+                .drain(..)
+                .map(|dynamic_wall| dynamic_wall.wall),
+        );
+        !solid.is_solvable()
+    }
+
+    /// Wall squares where a horizontal and a vertical run of walls meet,
+    /// from the squares themselves so the mesh doesn't matter.
+    pub fn junctions(&self) -> Vec<Junction> {
+        let grid = Grid::from_lvl(self);
+        let is_wall = |point: Point| grid.get(point.x, point.y) == SquareType::Wall;
+
+        let mut junctions = Vec::new();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let point = Point::new(x, y);
+                if !is_wall(point) {
+                    continue;
+                }
+
+                let walls: Vec<Point> = grid.neighbors(point).filter(|n| is_wall(*n)).collect();
+                let kind = match walls[..] {
+                    [a, b] if a.x != b.x && a.y != b.y => JunctionKind::Corner,
+                    [_, _, _] => JunctionKind::T,
+                    [_, _, _, _] => JunctionKind::Cross,
+                    _ => continue,
+                };
+                junctions.push(Junction { x, y, kind });
+            }
+        }
+
+        junctions
+    }
+
+    /// Every pair of solid walls that share a square, in the order of the
+    /// walls.
+    ///
+    /// Runs merged by default meet at corners and junctions, only
+    /// `no_overlap` and `optimize_walls` scans leave no overlaps.
+    pub fn find_overlapping_walls(&self) -> Vec<WallOverlap> {
+        // Only the squares the walls cover, a large level with few walls
+        // doesn't need an entry per square
+        let mut covering: HashMap<Point, Vec<usize>> = HashMap::new();
+        let mut overlaps = BTreeMap::new();
+        for (index, wall) in self.walls.iter().enumerate() {
+            let squares = wall
+                .split_at_seam(self.width, self.height)
+                .into_iter()
+                .flat_map(Wall::squares)
+                .filter(|point| point.x < self.width && point.y < self.height);
+            for point in squares {
+                let walls = covering.entry(point).or_default();
+                for other in walls.iter() {
+                    overlaps.entry((*other, index)).or_insert(point);
+                }
+                walls.push(index);
+            }
+        }
+
+        overlaps
+            .into_iter()
+            .map(|((a, b), overlap_point)| WallOverlap {
+                wall_a: self.walls[a],
+                wall_b: self.walls[b],
+                overlap_point,
+            })
+            .collect()
+    }
+
+    /// Empty squares with exactly one passable neighbor.
+    pub fn dead_ends(&self) -> Vec<Point> {
+        let grid = Grid::from_lvl(self);
+
+        let mut dead_ends = Vec::new();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.get(x, y) != SquareType::Empty {
+                    continue;
+                }
+
+                // Stop looking once a second way out is found
+                let point = Point::new(x, y);
+                let exits = grid
+                    .moves(point)
+                    .filter(|neighbor| grid.is_passable(*neighbor))
+                    .take(2)
+                    .count();
+                if exits == 1 {
+                    dead_ends.push(point);
+                }
+            }
+        }
+
+        dead_ends
+    }
+
+    /// Heuristic difficulty in `[0.0, 1.0]`, higher is harder.
+    ///
+    /// `score = 0.4 * P + 0.25 * D + 0.15 * C + 0.2 * W` where
+    /// - `P`: shortest path length divided by the number of passable squares,
+    ///   1 when the level can't be solved
+    /// - `D`: `dead_ends / (dead_ends + 10)`
+    /// - `C`: `checkpoints / (checkpoints + 5)`, only counting required ones
+    /// - `W`: wall squares divided by the level area
+    ///
+    /// Every term is in `[0.0, 1.0]` and the weights add up to 1.
+    pub fn difficulty_score(&self) -> f64 {
+        let grid = Grid::from_lvl(self);
+        let area = f64::from(self.width) * f64::from(self.height);
+
+        let mut wall_squares = 0u32;
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.get(x, y) == SquareType::Wall {
+                    wall_squares += 1;
+                }
+            }
+        }
+        let passable = area - f64::from(wall_squares);
+
+        let path = match grid.distance(self.start, self.end) {
+            Some(length) if passable > 0.0 => (f64::from(length) / passable).min(1.0),
+            _ => 1.0,
+        };
+        let dead_ends = self.dead_ends().len() as f64;
+        let dead_ends = dead_ends / (dead_ends + 10.0);
+        let checkpoints = self.checkpoints.len() as f64;
+        let checkpoints = checkpoints / (checkpoints + 5.0);
+        let walls = if area > 0.0 {
+            f64::from(wall_squares) / area
+        } else {
+            0.0
+        };
+
+        (0.4 * path + 0.25 * dead_ends + 0.15 * checkpoints + 0.2 * walls).clamp(0.0, 1.0)
+    }
+
+    /// Open rectangular areas of at least `min_area` squares, largest first.
+    ///
+    /// Takes the largest rectangle of passable squares and repeats on the
+    /// squares that are left until the rooms get too small, so rooms never
+    /// overlap. The maximal rectangles are found in one O(width * height)
+    /// pass. A candidate that overlaps a room taken since is split into the
+    /// parts beside the room, which hold every rectangle it held that the
+    /// room doesn't, instead of searching the whole level again.
+    pub fn rooms(&self, min_area: u32) -> Vec<Room> {
+        let grid = Grid::from_lvl(self);
+        let open: Vec<bool> = (0..grid.height())
+            .flat_map(|y| (0..grid.width()).map(move |x| Point::new(x, y)))
+            .map(|point| grid.is_passable(point))
+            .collect();
+
+        // Largest first, ties in the order they were found. The last field
+        // is how many of the rooms the candidate was checked against.
+        let mut found = 0_u64;
+        let mut candidates = BinaryHeap::new();
+        let mut push = |candidates: &mut BinaryHeap<_>, room: Room, checked: usize| {
+            if room.area >= min_area {
+                candidates.push((room.area, Reverse(found), Corners::from(room), checked));
+                found += 1;
+            }
+        };
+        for room in maximal_rectangles(&open, self.width, self.height) {
+            push(&mut candidates, room, 0);
+        }
+
+        let mut rooms: Vec<Room> = Vec::new();
+        while let Some((_, _, corners, checked)) = candidates.pop() {
+            let candidate = Room::from(corners);
+            let Some(overlap) = rooms[checked..]
+                .iter()
+                .position(|room| room.overlaps(candidate))
+                .map(|index| checked + index)
+            else {
+                rooms.push(candidate);
+                continue;
+            };
+
+            let room = rooms[overlap];
+            let (top_left, bottom_right) = (candidate.top_left, candidate.bottom_right);
+            let mut parts = Vec::new();
+            if room.top_left.x > top_left.x {
+                parts.push((top_left, Point::new(room.top_left.x - 1, bottom_right.y)));
+            }
+            if room.bottom_right.x < bottom_right.x {
+                parts.push((
+                    Point::new(room.bottom_right.x + 1, top_left.y),
+                    bottom_right,
+                ));
+            }
+            if room.top_left.y > top_left.y {
+                parts.push((top_left, Point::new(bottom_right.x, room.top_left.y - 1)));
+            }
+            if room.bottom_right.y < bottom_right.y {
+                parts.push((
+                    Point::new(top_left.x, room.bottom_right.y + 1),
+                    bottom_right,
+                ));
+            }
+            for (top_left, bottom_right) in parts {
+                push(
+                    &mut candidates,
+                    Room::new(top_left, bottom_right),
+                    overlap + 1,
+                );
+            }
+        }
+
+        rooms
+    }
+}
+
+/// Corners of a [`Room`] in an order the candidates can be compared by.
+type Corners = [u32; 4];
+
+impl From<Room> for Corners {
+    fn from(room: Room) -> Self {
+        [
+            room.top_left.x,
+            room.top_left.y,
+            room.bottom_right.x,
+            room.bottom_right.y,
+        ]
+    }
+}
+
+impl From<Corners> for Room {
+    fn from([left, top, right, bottom]: Corners) -> Self {
+        Self::new(Point::new(left, top), Point::new(right, bottom))
+    }
+}
+
+impl Room {
+    fn new(top_left: Point, bottom_right: Point) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+            area: (bottom_right.x - top_left.x + 1) * (bottom_right.y - top_left.y + 1),
+        }
+    }
+
+    const fn overlaps(self, other: Self) -> bool {
+        self.top_left.x <= other.bottom_right.x
+            && other.top_left.x <= self.bottom_right.x
+            && self.top_left.y <= other.bottom_right.y
+            && other.top_left.y <= self.bottom_right.y
+    }
+}
+
+/// Every rectangle of open squares that can't grow in any direction, and
+/// some that can grow down, with a histogram row by row: `heights` holds
+/// the number of open squares above and including the current row.
+fn maximal_rectangles(open: &[bool], width: u32, height: u32) -> Vec<Room> {
+    let mut heights = vec![0u32; width as usize];
+    let mut rectangles = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let column = x as usize;
+            heights[column] = if open[y as usize * width as usize + column] {
+                heights[column] + 1
+            } else {
+                0
+            };
+        }
+
+        // (first column, height) of the bars that can still grow to the right
+        let mut stack: Vec<(u32, u32)> = Vec::new();
+        for x in 0..=width {
+            let bar = if x < width { heights[x as usize] } else { 0 };
+            let mut first = x;
+            while let Some(&(start, top)) = stack.last() {
+                if top < bar {
+                    break;
+                }
+                stack.pop();
+
+                // An equal bar continues the rectangle, it ends at the lower
+                // one after it
+                if top > bar {
+                    rectangles.push(Room::new(
+                        Point::new(start, y + 1 - top),
+                        Point::new(x - 1, y),
+                    ));
+                }
+                first = start;
+            }
+            if bar > 0 {
+                stack.push((first, bar));
+            }
+        }
+    }
+
+    rectangles
+}
@@ -0,0 +1,95 @@
+//! Read levels sketched as text, one character per square.
+//!
+//! `#` is a wall, `S` the start, `E` the end, `C` a checkpoint and `.` or a
+//! space is empty. Lines shorter than the longest one are padded with empty
+//! squares.
+//!
+//! [`Grid::to_ascii`] draws squares back the same way, with `c` for optional
+//! checkpoints and `%` for dynamic walls, which levels read from text don't
+//! have.
+
+use std::{error::Error, fmt::Display};
+
+use crate::{Grid, SquareType};
+
+#[derive(Debug)]
+pub enum AsciiError {
+    /// Character that isn't a square, with its 1-based position
+    UnknownCharacter {
+        character: char,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl Display for AsciiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCharacter {
+                character,
+                line,
+                column,
+            } => write!(f, "unknown square {character:?} at {line}:{column}"),
+        }
+    }
+}
+
+impl Error for AsciiError {}
+
+/// Classify every character of an ASCII level.
+pub fn parse_ascii(text: &str) -> Result<Grid, AsciiError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut grid = Grid::new(to_u32(width), to_u32(lines.len()));
+    for (y, line) in lines.iter().enumerate() {
+        for (x, character) in line.chars().enumerate() {
+            let square = match character {
+                '#' => SquareType::Wall,
+                'S' => SquareType::Start,
+                'E' => SquareType::End,
+                'C' => SquareType::Checkpoint,
+                '.' | ' ' => SquareType::Empty,
+                _ => {
+                    return Err(AsciiError::UnknownCharacter {
+                        character,
+                        line: y + 1,
+                        column: x + 1,
+                    })
+                }
+            };
+            grid.put(to_u32(x), to_u32(y), square);
+        }
+    }
+
+    Ok(grid)
+}
+
+impl Grid {
+    /// Draw every square as a character, one line per row, the inverse of
+    /// [`parse_ascii`] for the squares it reads.
+    pub fn to_ascii(&self) -> String {
+        let mut text = String::with_capacity((self.width() as usize + 1) * self.height() as usize);
+        for y in 0..self.height() {
+            text.extend((0..self.width()).map(|x| match self.get(x, y) {
+                SquareType::Wall => '#',
+                SquareType::DynamicWall(_) => '%',
+                SquareType::Start => 'S',
+                SquareType::End => 'E',
+                SquareType::Checkpoint => 'C',
+                SquareType::OptionalCheckpoint => 'c',
+                SquareType::Empty => '.',
+            }));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).expect("ASCII level larger than u32::MAX squares")
+}
@@ -0,0 +1,83 @@
+//! Levels as Bevy assets.
+//!
+//! [`LvlPlugin`] registers [`Lvl`] as an asset, loaded by [`LvlLoader`] from
+//! the json the command line tool writes when the file ends in `.lvl.json`.
+//! The level types derive `Reflect`, so they show up in inspectors and can
+//! be queried by type path.
+
+use std::{error::Error, fmt::Display};
+
+use bevy::{
+    app::{App, Plugin},
+    asset::{io::Reader, AssetApp, AssetLoader, LoadContext},
+    reflect::TypePath,
+};
+
+use crate::Lvl;
+
+/// Registers the level asset, its loader and the reflected level types.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LvlPlugin;
+
+impl Plugin for LvlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Lvl>()
+            .register_asset_loader(LvlLoader)
+            .register_type::<Lvl>();
+    }
+}
+
+/// Reads `.lvl.json` files into [`Lvl`] assets.
+#[derive(Debug, Clone, Copy, Default, TypePath)]
+pub struct LvlLoader;
+
+/// Level file that can't be loaded.
+#[derive(Debug)]
+pub enum LvlLoaderError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for LvlLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "can't read the level: {error}"),
+            Self::Json(error) => write!(f, "invalid level json: {error}"),
+        }
+    }
+}
+
+impl Error for LvlLoaderError {}
+
+impl From<std::io::Error> for LvlLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for LvlLoaderError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+impl AssetLoader for LvlLoader {
+    type Asset = Lvl;
+    type Settings = ();
+    type Error = LvlLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Lvl, LvlLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lvl.json"]
+    }
+}
@@ -0,0 +1,298 @@
+//! Packed binary levels for loaders without a json parser.
+//!
+//! Every number is little endian and every record has a fixed size, so a
+//! loader can read the file with plain struct reads. The layout of version
+//! [`BIN_VERSION`]:
+//!
+//! ```text
+//! offset  size  header
+//!      0     4  magic "MZLV"
+//!      4     2  version u16, loaders reject versions they don't know
+//!      6     1  wrap u8, 0 none, 1 x, 2 y, 3 both
+//!      7     1  reserved, 0
+//!      8     4  width u32
+//!     12     4  height u32
+//!     16     8  start x, y u32
+//!     24     8  end x, y u32
+//! ```
+//!
+//! Six lists follow the 32 byte header, in this order. Each is a u32 count
+//! followed by that many records:
+//!
+//! ```text
+//! list                  record size  record
+//! walls                          16  start x, y, end x, y u32
+//! dynamic walls                  20  start x, y, end x, y u32, period u8,
+//!                                    3 reserved bytes of 0
+//! checkpoints                     8  x, y u32
+//! optional checkpoints            8  x, y u32
+//! rects                          16  x, y, width, height u32
+//! edge walls                     12  x, y u32, side u8 (0 top, 1 right,
+//!                                    2 bottom, 3 left), 3 reserved bytes of 0
+//! ```
+//!
+//! Single square walls repeat their start as the end. The orientation isn't
+//! stored, it follows from the coordinates. Wall paths and polygons have no
+//! fixed size and aren't written. Anything that changes this layout bumps
+//! the version.
+
+use alloc::vec::Vec;
+use core::{error::Error, fmt::Display};
+
+use crate::{DynamicWall, EdgeWall, Lvl, Point, Rect, Side, Wall, Wrap, FORMAT_VERSION};
+
+/// First bytes of every binary level.
+pub const BIN_MAGIC: [u8; 4] = *b"MZLV";
+
+/// Version of the layout written by [`Lvl::to_bin`].
+pub const BIN_VERSION: u16 = 1;
+
+/// Bytes that aren't a binary level [`Lvl::from_bin`] can read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinError {
+    /// Doesn't start with [`BIN_MAGIC`]
+    Magic,
+    /// Written in a layout version this reader doesn't know
+    Version(u16),
+    /// Ends in the middle of the header or a list
+    Truncated,
+    /// Bytes left after the last list
+    TrailingBytes(usize),
+    /// Wrap or side byte that isn't one of the listed values
+    InvalidTag { offset: usize, value: u8 },
+}
+
+impl Display for BinError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Magic => write!(f, "not a binary level, the magic bytes are missing"),
+            Self::Version(version) => write!(
+                f,
+                "binary level version {version} isn't supported, only {BIN_VERSION} is"
+            ),
+            Self::Truncated => write!(f, "binary level ends early"),
+            Self::TrailingBytes(count) => {
+                write!(f, "binary level has {count} bytes after its last list")
+            }
+            Self::InvalidTag { offset, value } => {
+                write!(
+                    f,
+                    "invalid value {value} at byte {offset} of the binary level"
+                )
+            }
+        }
+    }
+}
+
+impl Error for BinError {}
+
+impl Lvl {
+    /// Pack the level into the layout of the [module docs](self). Wall
+    /// paths and polygons are left out.
+    pub fn to_bin(&self) -> Vec<u8> {
+        let mut writer = Writer(Vec::new());
+        writer.0.extend_from_slice(&BIN_MAGIC);
+        writer.0.extend_from_slice(&BIN_VERSION.to_le_bytes());
+        writer.0.extend_from_slice(&[wrap_tag(self.wrap), 0]);
+        writer.u32(self.width);
+        writer.u32(self.height);
+        writer.point(self.start);
+        writer.point(self.end);
+
+        writer.list(&self.walls, |writer, wall| writer.wall(*wall));
+        writer.list(&self.dynamic_walls, |writer, dynamic_wall| {
+            writer.wall(dynamic_wall.wall);
+            writer.0.extend_from_slice(&[dynamic_wall.period, 0, 0, 0]);
+        });
+        writer.list(&self.checkpoints, |writer, point| writer.point(*point));
+        writer.list(&self.optional_checkpoints, |writer, point| {
+            writer.point(*point);
+        });
+        writer.list(&self.rects, |writer, rect| {
+            writer.u32(rect.x);
+            writer.u32(rect.y);
+            writer.u32(rect.width);
+            writer.u32(rect.height);
+        });
+        writer.list(&self.edge_walls, |writer, edge_wall| {
+            writer.u32(edge_wall.x);
+            writer.u32(edge_wall.y);
+            writer
+                .0
+                .extend_from_slice(&[side_tag(edge_wall.side), 0, 0, 0]);
+        });
+
+        writer.0
+    }
+
+    /// Read a level written by [`Lvl::to_bin`].
+    pub fn from_bin(bytes: &[u8]) -> Result<Self, BinError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != BIN_MAGIC {
+            return Err(BinError::Magic);
+        }
+        let version = u16::from_le_bytes([reader.u8()?, reader.u8()?]);
+        if version != BIN_VERSION {
+            return Err(BinError::Version(version));
+        }
+        let wrap = match reader.u8()? {
+            0 => Wrap::None,
+            1 => Wrap::X,
+            2 => Wrap::Y,
+            3 => Wrap::Both,
+            value => return Err(reader.invalid(value)),
+        };
+        reader.take(1)?;
+
+        let mut lvl = Self {
+            format_version: FORMAT_VERSION,
+            width: reader.u32()?,
+            height: reader.u32()?,
+            wrap,
+            walls: Vec::new(),
+            dynamic_walls: Vec::new(),
+            rects: Vec::new(),
+            wall_paths: Vec::new(),
+            collision: Vec::new(),
+            edge_walls: Vec::new(),
+            start: reader.point()?,
+            end: reader.point()?,
+            checkpoints: Vec::new(),
+            optional_checkpoints: Vec::new(),
+        };
+        lvl.walls = reader.list(Reader::wall)?;
+        lvl.dynamic_walls = reader.list(|reader| {
+            let wall = reader.wall()?;
+            let period = reader.u8()?;
+            reader.take(3)?;
+            Ok(DynamicWall { wall, period })
+        })?;
+        lvl.checkpoints = reader.list(Reader::point)?;
+        lvl.optional_checkpoints = reader.list(Reader::point)?;
+        lvl.rects = reader.list(|reader| {
+            Ok(Rect {
+                x: reader.u32()?,
+                y: reader.u32()?,
+                width: reader.u32()?,
+                height: reader.u32()?,
+            })
+        })?;
+        lvl.edge_walls = reader.list(|reader| {
+            let (x, y) = (reader.u32()?, reader.u32()?);
+            let side = match reader.u8()? {
+                0 => Side::Top,
+                1 => Side::Right,
+                2 => Side::Bottom,
+                3 => Side::Left,
+                value => return Err(reader.invalid(value)),
+            };
+            reader.take(3)?;
+            Ok(EdgeWall { x, y, side })
+        })?;
+
+        match bytes.len() - reader.offset {
+            0 => Ok(lvl),
+            count => Err(BinError::TrailingBytes(count)),
+        }
+    }
+}
+
+const fn wrap_tag(wrap: Wrap) -> u8 {
+    match wrap {
+        Wrap::None => 0,
+        Wrap::X => 1,
+        Wrap::Y => 2,
+        Wrap::Both => 3,
+    }
+}
+
+const fn side_tag(side: Side) -> u8 {
+    match side {
+        Side::Top => 0,
+        Side::Right => 1,
+        Side::Bottom => 2,
+        Side::Left => 3,
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn point(&mut self, point: Point) {
+        self.u32(point.x);
+        self.u32(point.y);
+    }
+
+    fn wall(&mut self, wall: Wall) {
+        self.point(wall.start);
+        self.point(wall.end.unwrap_or(wall.start));
+    }
+
+    fn list<T>(&mut self, items: &[T], mut record: impl FnMut(&mut Self, &T)) {
+        let count = u32::try_from(items.len()).expect("more than u32::MAX records");
+        self.u32(count);
+        for item in items {
+            record(self, item);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], BinError> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + count)
+            .ok_or(BinError::Truncated)?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, BinError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, BinError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn point(&mut self) -> Result<Point, BinError> {
+        Ok(Point::new(self.u32()?, self.u32()?))
+    }
+
+    fn wall(&mut self) -> Result<Wall, BinError> {
+        Ok(Wall::between(self.point()?, self.point()?))
+    }
+
+    /// Error for the tag byte that was just read.
+    const fn invalid(&self, value: u8) -> BinError {
+        BinError::InvalidTag {
+            offset: self.offset - 1,
+            value,
+        }
+    }
+
+    fn list<T>(
+        &mut self,
+        mut record: impl FnMut(&mut Self) -> Result<T, BinError>,
+    ) -> Result<Vec<T>, BinError> {
+        let count = self.u32()?;
+        // A count larger than the bytes left can't be right, don't allocate
+        // for it
+        let mut items = Vec::with_capacity((count as usize).min(self.bytes.len() - self.offset));
+        for _ in 0..count {
+            items.push(record(self)?);
+        }
+        Ok(items)
+    }
+}
@@ -0,0 +1,100 @@
+//! Walls as a packed bitset, the smallest form the level is written in.
+//!
+//! Every square is one bit in reading order, row after row without padding
+//! in between. The bits are packed into bytes with the first square in the
+//! most significant bit, the last byte is padded with zero bits, and the
+//! bytes are base64 encoded with the standard alphabet and `=` padding. A set
+//! bit is a wall, every other square, dynamic walls included, is clear.
+
+use std::{error::Error, fmt::Display};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+
+use crate::{Grid, SquareType};
+
+/// Where the first square of each byte goes, written next to the bitset so
+/// readers don't have to guess.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BitOrder {
+    /// First square in the most significant bit, `0x80`
+    #[default]
+    MsbFirst,
+}
+
+#[derive(Debug)]
+pub enum OccupancyError {
+    Base64(base64::DecodeError),
+    /// The bitset isn't `ceil(width * height / 8)` bytes long
+    Length {
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl Display for OccupancyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Base64(error) => write!(f, "occupancy isn't valid base64: {error}"),
+            Self::Length { expected, actual } => write!(
+                f,
+                "occupancy has {actual} bytes, {expected} are needed for the level"
+            ),
+        }
+    }
+}
+
+impl Error for OccupancyError {}
+
+impl From<base64::DecodeError> for OccupancyError {
+    fn from(value: base64::DecodeError) -> Self {
+        Self::Base64(value)
+    }
+}
+
+/// Bytes needed for a bit per square.
+fn byte_count(width: u32, height: u32) -> usize {
+    (width as usize * height as usize).div_ceil(8)
+}
+
+impl Grid {
+    /// The walls as a base64 bitset, see the [module docs](self).
+    pub fn to_occupancy(&self) -> String {
+        let mut bytes = vec![0u8; byte_count(self.width(), self.height())];
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self.get(x, y) == SquareType::Wall {
+                    let bit = y as usize * self.width() as usize + x as usize;
+                    bytes[bit / 8] |= 0x80 >> (bit % 8);
+                }
+            }
+        }
+        STANDARD.encode(bytes)
+    }
+}
+
+/// Read a bitset written by [`Grid::to_occupancy`] back into a grid of walls
+/// and empty squares, the only two kinds it tells apart. Padding bits are
+/// ignored.
+pub fn parse_occupancy(text: &str, width: u32, height: u32) -> Result<Grid, OccupancyError> {
+    let bytes = STANDARD.decode(text.trim())?;
+    let expected = byte_count(width, height);
+    if bytes.len() != expected {
+        return Err(OccupancyError::Length {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    let mut grid = Grid::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let bit = y as usize * width as usize + x as usize;
+            if bytes[bit / 8] & (0x80 >> (bit % 8)) != 0 {
+                grid.put(x, y, SquareType::Wall);
+            }
+        }
+    }
+    Ok(grid)
+}
@@ -0,0 +1,215 @@
+//! Put levels together in code instead of scanning them.
+
+use alloc::{format, string::String, vec::Vec};
+use core::{error::Error, fmt::Display};
+
+use crate::{DynamicWall, Lvl, Point, Wall, Wrap, FORMAT_VERSION};
+
+/// Level that can't be built or doesn't have exactly one start and end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    MissingStart,
+    MissingEnd,
+    /// Every start square, in reading order
+    MultipleStarts(Vec<Point>),
+    /// Every end square, in reading order
+    MultipleEnds(Vec<Point>),
+    /// Point or wall square outside of the level
+    OutOfBounds(Point),
+    /// Square of a level's field outside of it, like `walls[3]`
+    FieldOutOfBounds {
+        field: String,
+        point: Point,
+    },
+    /// Square used by more than one of the start, end and checkpoints
+    SharedSquare(Point),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let list = |points: &[Point]| {
+            points
+                .iter()
+                .map(|point| format!("({}, {})", point.x, point.y))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Self::MissingStart => write!(f, "level has no start"),
+            Self::MissingEnd => write!(f, "level has no end"),
+            Self::MultipleStarts(points) => write!(f, "level has starts at {}", list(points)),
+            Self::MultipleEnds(points) => write!(f, "level has ends at {}", list(points)),
+            Self::OutOfBounds(point) => {
+                write!(f, "({}, {}) is outside of the level", point.x, point.y)
+            }
+            Self::FieldOutOfBounds { field, point } => write!(
+                f,
+                "{field} at ({}, {}) is outside of the level",
+                point.x, point.y
+            ),
+            Self::SharedSquare(point) => {
+                write!(f, "({}, {}) is used more than once", point.x, point.y)
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Level that's valid but likely not what a game expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationWarning {
+    /// The start isn't on a square of the outer edge
+    StartNotOnBoundary(Point),
+    /// The end isn't on a square of the outer edge
+    EndNotOnBoundary(Point),
+}
+
+impl Display for ValidationWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::StartNotOnBoundary(point) => write!(
+                f,
+                "the start at ({}, {}) isn't on the edge of the level",
+                point.x, point.y
+            ),
+            Self::EndNotOnBoundary(point) => write!(
+                f,
+                "the end at ({}, {}) isn't on the edge of the level",
+                point.x, point.y
+            ),
+        }
+    }
+}
+
+/// Builder for a [`Lvl`], checked when it's built.
+///
+/// ```
+/// use maze_lvl_maker::{LvlBuilder, Point};
+///
+/// let lvl = LvlBuilder::new(5, 3)
+///     .start(Point::new(1, 1))
+///     .end(Point::new(3, 1))
+///     .wall(Point::new(0, 0), Point::new(4, 0))
+///     .build()
+///     .unwrap();
+/// assert_eq!(lvl.walls.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LvlBuilder {
+    width: u32,
+    height: u32,
+    walls: Vec<Wall>,
+    dynamic_walls: Vec<DynamicWall>,
+    start: Option<Point>,
+    end: Option<Point>,
+    checkpoints: Vec<Point>,
+    optional_checkpoints: Vec<Point>,
+}
+
+impl LvlBuilder {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub const fn start(mut self, start: Point) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    #[must_use]
+    pub const fn end(mut self, end: Point) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    #[must_use]
+    pub fn checkpoint(mut self, checkpoint: Point) -> Self {
+        self.checkpoints.push(checkpoint);
+        self
+    }
+
+    #[must_use]
+    pub fn optional_checkpoint(mut self, checkpoint: Point) -> Self {
+        self.optional_checkpoints.push(checkpoint);
+        self
+    }
+
+    /// Wall covering the squares from `start` to `end`, which share a row, a
+    /// column or a diagonal, in either order. Built levels don't wrap, so
+    /// the ends are put in reading order with [`Wall::normalize`].
+    #[must_use]
+    pub fn wall(mut self, start: Point, end: Point) -> Self {
+        self.walls.push(Wall::between(start, end).normalize());
+        self
+    }
+
+    #[must_use]
+    pub fn dynamic_wall(mut self, start: Point, end: Point, period: u8) -> Self {
+        self.dynamic_walls.push(DynamicWall {
+            wall: Wall::between(start, end).normalize(),
+            period,
+        });
+        self
+    }
+
+    /// Check that the start and end are set, everything is inside the level
+    /// and no two points share a square.
+    pub fn build(self) -> Result<Lvl, ValidationError> {
+        let start = self.start.ok_or(ValidationError::MissingStart)?;
+        let end = self.end.ok_or(ValidationError::MissingEnd)?;
+
+        let points: Vec<Point> = [start, end]
+            .into_iter()
+            .chain(self.checkpoints.iter().copied())
+            .chain(self.optional_checkpoints.iter().copied())
+            .collect();
+        let wall_squares = self
+            .walls
+            .iter()
+            .chain(
+                self.dynamic_walls
+                    .iter()
+                    .map(|dynamic_wall| &dynamic_wall.wall),
+            )
+            .flat_map(|wall| wall.squares());
+        if let Some(point) = points
+            .iter()
+            .copied()
+            .chain(wall_squares)
+            .find(|point| point.x >= self.width || point.y >= self.height)
+        {
+            return Err(ValidationError::OutOfBounds(point));
+        }
+        if let Some((_, point)) = points
+            .iter()
+            .enumerate()
+            .find(|(index, point)| points[..*index].contains(point))
+        {
+            return Err(ValidationError::SharedSquare(*point));
+        }
+
+        Ok(Lvl {
+            format_version: FORMAT_VERSION,
+            width: self.width,
+            height: self.height,
+            wrap: Wrap::None,
+            walls: self.walls,
+            dynamic_walls: self.dynamic_walls,
+            rects: Vec::new(),
+            wall_paths: Vec::new(),
+            collision: Vec::new(),
+            edge_walls: Vec::new(),
+            start,
+            end,
+            checkpoints: self.checkpoints,
+            optional_checkpoints: self.optional_checkpoints,
+        })
+    }
+}
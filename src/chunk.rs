@@ -0,0 +1,162 @@
+//! Square chunks of a level for games that stream big maps in pieces.
+//!
+//! Chunk `(cx, cy)` covers the squares `cx * size..(cx + 1) * size` and
+//! `cy * size..(cy + 1) * size`, the last row and column of chunks stop at
+//! the edge of the level. Every square belongs to exactly one chunk, so a
+//! wall running along the last column of a chunk stays in it and a wall
+//! crossing into the next one is split there. Edge walls belong to the
+//! chunk of their cell. Coordinates stay those of the whole level.
+
+use serde::Serialize;
+
+use crate::{lvl::clip_path, DynamicWall, EdgeWall, Lvl, Point, Rect, Wall};
+
+/// Walls of one chunk, see [`Lvl::chunks`].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// Column of the chunk
+    pub cx: u32,
+    /// Row of the chunk
+    pub cy: u32,
+    pub walls: Vec<Wall>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dynamic_walls: Vec<DynamicWall>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rects: Vec<Rect>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wall_paths: Vec<Vec<Point>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub edge_walls: Vec<EdgeWall>,
+}
+
+impl Chunk {
+    const fn new(cx: u32, cy: u32) -> Self {
+        Self {
+            cx,
+            cy,
+            walls: Vec::new(),
+            dynamic_walls: Vec::new(),
+            rects: Vec::new(),
+            wall_paths: Vec::new(),
+            edge_walls: Vec::new(),
+        }
+    }
+}
+
+/// Row-major chunks of `size` squares and lookups into them.
+struct Chunks {
+    size: u32,
+    columns: u32,
+    rows: u32,
+    chunks: Vec<Chunk>,
+}
+
+impl Chunks {
+    fn rect(&self, cx: u32, cy: u32) -> Rect {
+        Rect {
+            x: cx * self.size,
+            y: cy * self.size,
+            width: self.size,
+            height: self.size,
+        }
+    }
+
+    /// Chunks overlapping the box from `min` to `max`, inside of the level.
+    fn touched(&self, (min, max): (Point, Point)) -> impl Iterator<Item = (u32, u32)> {
+        let size = self.size;
+        let columns = min.x / size..=(max.x / size).min(self.columns.saturating_sub(1));
+        (min.y / size..=(max.y / size).min(self.rows.saturating_sub(1)))
+            .flat_map(move |cy| columns.clone().map(move |cx| (cx, cy)))
+    }
+
+    fn get_mut(&mut self, cx: u32, cy: u32) -> &mut Chunk {
+        &mut self.chunks[(cy * self.columns + cx) as usize]
+    }
+}
+
+impl Lvl {
+    /// The walls split into chunks of `size` by `size` squares, row by row.
+    ///
+    /// Walls, dynamic walls, rectangles and wall paths are clipped at the
+    /// chunk boundaries, wrapping walls at the seam first. Chunks without
+    /// walls are kept so a chunk is always at `cy * columns + cx`. Collision
+    /// polygons aren't chunked.
+    ///
+    /// # Panics
+    ///
+    /// When `size` is 0.
+    pub fn chunks(&self, size: u32) -> Vec<Chunk> {
+        assert!(size > 0, "chunks need at least one square");
+        let (columns, rows) = (self.width.div_ceil(size), self.height.div_ceil(size));
+        let mut chunks = Chunks {
+            size,
+            columns,
+            rows,
+            chunks: (0..rows)
+                .flat_map(|cy| (0..columns).map(move |cx| Chunk::new(cx, cy)))
+                .collect(),
+        };
+
+        for wall in self
+            .walls
+            .iter()
+            .flat_map(|wall| wall.split_at_seam(self.width, self.height))
+        {
+            for (cx, cy) in chunks.touched(wall.bounding_box()) {
+                if let Some(piece) = wall.clip(chunks.rect(cx, cy)) {
+                    chunks.get_mut(cx, cy).walls.push(piece);
+                }
+            }
+        }
+        for dynamic_wall in &self.dynamic_walls {
+            for wall in dynamic_wall.wall.split_at_seam(self.width, self.height) {
+                for (cx, cy) in chunks.touched(wall.bounding_box()) {
+                    if let Some(piece) = wall.clip(chunks.rect(cx, cy)) {
+                        chunks.get_mut(cx, cy).dynamic_walls.push(DynamicWall {
+                            wall: piece,
+                            period: dynamic_wall.period,
+                        });
+                    }
+                }
+            }
+        }
+        for rect in self
+            .rects
+            .iter()
+            .filter(|rect| rect.width > 0 && rect.height > 0)
+        {
+            let corners = (
+                Point::new(rect.x, rect.y),
+                Point::new(rect.x + rect.width - 1, rect.y + rect.height - 1),
+            );
+            for (cx, cy) in chunks.touched(corners) {
+                if let Some(piece) = rect.intersection(chunks.rect(cx, cy)) {
+                    chunks.get_mut(cx, cy).rects.push(piece);
+                }
+            }
+        }
+        for path in self.wall_paths.iter().filter(|path| !path.is_empty()) {
+            let corners = path.iter().fold(
+                (Point::new(u32::MAX, u32::MAX), Point::new(0, 0)),
+                |(min, max), point| {
+                    (
+                        Point::new(min.x.min(point.x), min.y.min(point.y)),
+                        Point::new(max.x.max(point.x), max.y.max(point.y)),
+                    )
+                },
+            );
+            for (cx, cy) in chunks.touched(corners) {
+                let pieces = clip_path(path, chunks.rect(cx, cy));
+                chunks.get_mut(cx, cy).wall_paths.extend(pieces);
+            }
+        }
+        for edge_wall in &self.edge_walls {
+            let (cx, cy) = (edge_wall.x / size, edge_wall.y / size);
+            if cx < columns && cy < rows {
+                chunks.get_mut(cx, cy).edge_walls.push(*edge_wall);
+            }
+        }
+
+        chunks.chunks
+    }
+}
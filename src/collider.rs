@@ -0,0 +1,111 @@
+//! Axis aligned bounding boxes for physics engines.
+//!
+//! Square `(x, y)` covers `x..x + 1` and `y..y + 1` in cell units, so a
+//! single square has its center at `(x + 0.5, y + 0.5)` and half extents of
+//! `0.5`.
+
+use serde::Serialize;
+
+use crate::{Lvl, Rect, Wall};
+
+/// Decimal places kept in every value, so outputs don't differ in the last
+/// digits of float noise.
+const PRECISION: f64 = 10_000.0;
+
+/// Box by its center and half its size in each direction.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Collider {
+    pub cx: f64,
+    pub cy: f64,
+    pub hx: f64,
+    pub hy: f64,
+}
+
+impl Collider {
+    /// Box over `width` by `height` squares from `(x, y)`, shrunk by
+    /// `padding` on every side but never below zero.
+    fn new(x: u32, y: u32, width: u32, height: u32, padding: f64) -> Self {
+        let round = |value: f64| (value * PRECISION).round() / PRECISION;
+        let half = |size: u32| round((f64::from(size) / 2.0 - padding).max(0.0));
+        Self {
+            cx: round(f64::from(x) + f64::from(width) / 2.0),
+            cy: round(f64::from(y) + f64::from(height) / 2.0),
+            hx: half(width),
+            hy: half(height),
+        }
+    }
+}
+
+impl Wall {
+    /// Collider over the [`bounding_box`](Wall::bounding_box), shrunk by
+    /// `padding` on every side.
+    ///
+    /// ```
+    /// use maze_lvl_maker::{Collider, Point, Wall};
+    ///
+    /// let wall = Wall {
+    ///     start: Point::new(1, 2),
+    ///     end: Some(Point::new(4, 2)),
+    /// };
+    /// let collider = Collider {
+    ///     cx: 3.0,
+    ///     cy: 2.5,
+    ///     hx: 1.95,
+    ///     hy: 0.45,
+    /// };
+    /// assert_eq!(wall.collider(0.05), collider);
+    /// ```
+    pub fn collider(self, padding: f64) -> Collider {
+        let (top_left, bottom_right) = self.bounding_box();
+        Collider::new(
+            top_left.x,
+            top_left.y,
+            bottom_right.x - top_left.x + 1,
+            bottom_right.y - top_left.y + 1,
+            padding,
+        )
+    }
+}
+
+impl Rect {
+    /// Collider over the rectangle, shrunk by `padding` on every side.
+    pub fn collider(self, padding: f64) -> Collider {
+        Collider::new(self.x, self.y, self.width, self.height, padding)
+    }
+}
+
+impl Lvl {
+    /// Colliders for the `rects` if the level was meshed into rectangles,
+    /// otherwise for the `walls`.
+    ///
+    /// Diagonal walls get a box per square since their bounding box would
+    /// block the open squares next to them, and walls that
+    /// [wrap](Wall::wraps) get one on each side of the seam.
+    pub fn colliders(&self, padding: f64) -> Vec<Collider> {
+        if !self.rects.is_empty() {
+            return self
+                .rects
+                .iter()
+                .map(|rect| rect.collider(padding))
+                .collect();
+        }
+
+        self.walls
+            .iter()
+            .flat_map(|wall| wall.split_at_seam(self.width, self.height))
+            .flat_map(|wall| -> Vec<Wall> {
+                if wall.is_diagonal() {
+                    wall.squares()
+                        .map(|square| Wall {
+                            start: square,
+                            end: None,
+                        })
+                        .collect()
+                } else {
+                    vec![wall]
+                }
+            })
+            .map(|wall| wall.collider(padding))
+            .collect()
+    }
+}
@@ -0,0 +1,170 @@
+//! Boundaries of the wall areas as collision polygons.
+//!
+//! Polygons are in square corner coordinates: the square at `(x, y)` spans
+//! the corners `(x, y)` to `(x + 1, y + 1)`. Seen on screen, with y pointing
+//! down, outer boundaries run counter-clockwise and holes run clockwise, so
+//! the wall is always on the left. Only corners are listed and the first
+//! corner isn't repeated at the end.
+//!
+//! Wall squares only belong to the same polygon when they share a side,
+//! squares that only touch diagonally end up in separate polygons that
+//! share a corner.
+
+use std::collections::VecDeque;
+
+use crate::{Grid, Point, Polygon, SquareType, Vertex};
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    from: Point,
+    to: Point,
+    /// Wall area of the square on the left of the edge
+    area: usize,
+}
+
+impl Edge {
+    fn direction(self) -> (i64, i64) {
+        (
+            i64::from(self.to.x) - i64::from(self.from.x),
+            i64::from(self.to.y) - i64::from(self.from.y),
+        )
+    }
+}
+
+/// Trace the outer boundary and the holes of every wall area.
+///
+/// Polygons are in the order their top left square is found in reading
+/// order.
+pub fn contours(grid: &Grid) -> Vec<Polygon> {
+    let (areas, area_count) = label_areas(grid);
+    let is_wall = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && x < i64::from(grid.width())
+            && y < i64::from(grid.height())
+            && grid.get(x as u32, y as u32) == SquareType::Wall
+    };
+
+    // Every side between a wall square and anything else, pointing so the
+    // wall is on its left
+    let mut edges = Vec::new();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let Some(area) = areas[grid.index(x, y)] else {
+                continue;
+            };
+            let (sx, sy) = (i64::from(x), i64::from(y));
+            let corner = |dx, dy| Point::new(x + dx, y + dy);
+            let mut side = |open: bool, from: Point, to: Point| {
+                if open {
+                    edges.push(Edge { from, to, area });
+                }
+            };
+
+            side(!is_wall(sx, sy - 1), corner(1, 0), corner(0, 0));
+            side(!is_wall(sx - 1, sy), corner(0, 0), corner(0, 1));
+            side(!is_wall(sx, sy + 1), corner(0, 1), corner(1, 1));
+            side(!is_wall(sx + 1, sy), corner(1, 1), corner(1, 0));
+        }
+    }
+
+    // Outgoing edges per corner
+    let corner_index = |point: Point| (point.y * (grid.width() + 1) + point.x) as usize;
+    let mut outgoing = vec![Vec::new(); ((grid.width() + 1) * (grid.height() + 1)) as usize];
+    for (index, edge) in edges.iter().enumerate() {
+        outgoing[corner_index(edge.from)].push(index);
+    }
+
+    // Prefer turning left, then going straight, then turning right. Keeping
+    // the wall close on the left keeps diagonal neighbors apart.
+    let next = |edge: Edge| {
+        let (dx, dy) = edge.direction();
+        [(dy, -dx), (dx, dy), (-dy, dx)]
+            .into_iter()
+            .find_map(|direction| {
+                outgoing[corner_index(edge.to)]
+                    .iter()
+                    .copied()
+                    .find(|candidate| edges[*candidate].direction() == direction)
+            })
+            .expect("boundaries are closed")
+    };
+
+    let mut polygons: Vec<Polygon> = (0..area_count)
+        .map(|_| Polygon {
+            outer: Vec::new(),
+            holes: Vec::new(),
+        })
+        .collect();
+    let mut visited = vec![false; edges.len()];
+    for start in 0..edges.len() {
+        if visited[start] {
+            continue;
+        }
+
+        let mut ring = Vec::new();
+        let mut current = start;
+        loop {
+            visited[current] = true;
+            let following = next(edges[current]);
+            if edges[current].direction() != edges[following].direction() {
+                ring.push(edges[current].to);
+            }
+
+            current = following;
+            if current == start {
+                break;
+            }
+        }
+
+        let ring: Vec<Vertex> = ring.into_iter().map(Vertex::from).collect();
+        let polygon = &mut polygons[edges[start].area];
+        if signed_area(&ring) < 0.0 {
+            polygon.outer = ring;
+        } else {
+            polygon.holes.push(ring);
+        }
+    }
+
+    polygons
+}
+
+/// Twice the signed area, negative for rings that run counter-clockwise on
+/// screen.
+pub(crate) fn signed_area(ring: &[Vertex]) -> f64 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum()
+}
+
+/// Number every wall area, squares sharing a side share an area.
+pub(crate) fn label_areas(grid: &Grid) -> (Vec<Option<usize>>, usize) {
+    let mut areas = vec![None; grid.square_count()];
+    let mut count = 0;
+
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            if grid.get(x, y) != SquareType::Wall || areas[grid.index(x, y)].is_some() {
+                continue;
+            }
+
+            areas[grid.index(x, y)] = Some(count);
+            let mut queue = VecDeque::from([Point::new(x, y)]);
+            while let Some(point) = queue.pop_front() {
+                for neighbor in grid.neighbors(point) {
+                    let index = grid.index(neighbor.x, neighbor.y);
+                    if grid.get(neighbor.x, neighbor.y) == SquareType::Wall
+                        && areas[index].is_none()
+                    {
+                        areas[index] = Some(count);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            count += 1;
+        }
+    }
+
+    (areas, count)
+}
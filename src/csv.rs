@@ -0,0 +1,85 @@
+//! Read levels from spreadsheets exported as CSV, one field per square.
+//!
+//! Fields are square type names like `Wall` or `Start`, matched without
+//! case, and empty fields are empty squares. The first row sets the width
+//! and shorter rows are padded with empty squares.
+
+use std::{error::Error, fmt::Display};
+
+use crate::{Grid, SquareType};
+
+#[derive(Debug)]
+pub enum CsvError {
+    Csv(csv::Error),
+    /// Field that isn't a square type name, with its 1-based position
+    UnknownSquare {
+        field: String,
+        row: usize,
+        column: usize,
+    },
+    /// Row with more fields than the first row
+    RowTooLong {
+        row: usize,
+        width: usize,
+    },
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv(error) => write!(f, "invalid csv: {error}"),
+            Self::UnknownSquare { field, row, column } => {
+                write!(f, "unknown square {field:?} at row {row}, column {column}")
+            }
+            Self::RowTooLong { row, width } => {
+                write!(f, "row {row} has more than {width} squares")
+            }
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+impl From<csv::Error> for CsvError {
+    fn from(value: csv::Error) -> Self {
+        Self::Csv(value)
+    }
+}
+
+/// Classify every field of a CSV level.
+pub fn parse_csv(text: &str) -> Result<Grid, CsvError> {
+    let rows = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .into_records()
+        .collect::<Result<Vec<_>, _>>()?;
+    let width = rows.first().map_or(0, csv::StringRecord::len);
+
+    let mut grid = Grid::new(to_u32(width), to_u32(rows.len()));
+    for (y, row) in rows.iter().enumerate() {
+        if row.len() > width {
+            return Err(CsvError::RowTooLong { row: y + 1, width });
+        }
+
+        for (x, field) in row.iter().enumerate() {
+            let field = field.trim();
+            let square = if field.is_empty() {
+                SquareType::Empty
+            } else {
+                field.parse().map_err(|_| CsvError::UnknownSquare {
+                    field: field.to_owned(),
+                    row: y + 1,
+                    column: x + 1,
+                })?
+            };
+            grid.put(to_u32(x), to_u32(y), square);
+        }
+    }
+
+    Ok(grid)
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).expect("CSV level larger than u32::MAX squares")
+}
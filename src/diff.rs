@@ -0,0 +1,142 @@
+//! What changed between two versions of a level.
+
+use std::{collections::HashMap, hash::Hash};
+
+use serde::Serialize;
+
+use crate::{DynamicWall, Lvl, Point, Wall, Wrap};
+
+/// Value before and after a change.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn of(from: T, to: T) -> Option<Self> {
+        (from != to).then_some(Self { from, to })
+    }
+}
+
+/// Width and height of a level.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Changes from one version of a level to the next, see [`Lvl::diff`].
+///
+/// Only what changed is serialized, identical levels are `{}`.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LvlDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resized: Option<Change<Size>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<Change<Wrap>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_walls: Vec<Wall>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_walls: Vec<Wall>,
+    /// Walls that kept their start but changed their end
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modified_walls: Vec<Change<Wall>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_dynamic_walls: Vec<DynamicWall>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_dynamic_walls: Vec<DynamicWall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moved_start: Option<Change<Point>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moved_end: Option<Change<Point>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_checkpoints: Vec<Point>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_checkpoints: Vec<Point>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_optional_checkpoints: Vec<Point>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_optional_checkpoints: Vec<Point>,
+}
+
+impl LvlDiff {
+    /// Whether the two levels are the same.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Lvl {
+    /// What changed from `self` to `other`, comparing the walls, dynamic
+    /// walls, points, size and wrap. The other meshes follow from the walls
+    /// and aren't compared.
+    ///
+    /// Walls are compared as sets, so reordering them isn't a change. A
+    /// removed and an added wall with the same start are one modified wall.
+    pub fn diff(&self, other: &Self) -> LvlDiff {
+        let mut removed_walls = missing(&self.walls, &other.walls);
+        let mut added_walls = missing(&other.walls, &self.walls);
+        let mut modified_walls = Vec::new();
+        removed_walls.retain(|removed| {
+            let Some(index) = added_walls
+                .iter()
+                .position(|added| added.start == removed.start)
+            else {
+                return true;
+            };
+            modified_walls.push(Change {
+                from: *removed,
+                to: added_walls.remove(index),
+            });
+            false
+        });
+
+        let size = |lvl: &Self| Size {
+            width: lvl.width,
+            height: lvl.height,
+        };
+        LvlDiff {
+            resized: Change::of(size(self), size(other)),
+            wrap: Change::of(self.wrap, other.wrap),
+            added_walls,
+            removed_walls,
+            modified_walls,
+            added_dynamic_walls: missing(&other.dynamic_walls, &self.dynamic_walls),
+            removed_dynamic_walls: missing(&self.dynamic_walls, &other.dynamic_walls),
+            moved_start: Change::of(self.start, other.start),
+            moved_end: Change::of(self.end, other.end),
+            added_checkpoints: missing(&other.checkpoints, &self.checkpoints),
+            removed_checkpoints: missing(&self.checkpoints, &other.checkpoints),
+            added_optional_checkpoints: missing(
+                &other.optional_checkpoints,
+                &self.optional_checkpoints,
+            ),
+            removed_optional_checkpoints: missing(
+                &self.optional_checkpoints,
+                &other.optional_checkpoints,
+            ),
+        }
+    }
+}
+
+/// Items of `items` that aren't in `other`, in order. Duplicates count, two
+/// equal items need two in `other`.
+fn missing<T: Copy + Eq + Hash>(items: &[T], other: &[T]) -> Vec<T> {
+    let mut unmatched: HashMap<T, usize> = HashMap::new();
+    for item in other {
+        *unmatched.entry(*item).or_default() += 1;
+    }
+
+    items
+        .iter()
+        .filter(|item| match unmatched.get_mut(*item) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .copied()
+        .collect()
+}
@@ -0,0 +1,160 @@
+//! Mazes whose walls are thin lines between cells.
+//!
+//! The image is `2 * width + 1` by `2 * height + 1` squares: odd rows and
+//! columns are the cells, the even ones the wall lines between them and the
+//! squares where even rows and columns cross are the corners. A cell's
+//! right side is closed when the square to its right is a wall, its bottom
+//! side when the square below it is one.
+
+#[cfg(feature = "std")]
+use std::{error::Error, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::{Grid, Lvl, Point, SquareType, Wrap, FORMAT_VERSION};
+
+/// Side of a cell an [`EdgeWall`] closes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Wall on one side of the cell at `(x, y)`.
+///
+/// Walls between two cells are only listed once, as the right or bottom
+/// side of the cell to the left or above. The top of the first row and the
+/// left of the first column are the only `top` and `left` sides.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+pub struct EdgeWall {
+    /// Column of the cell
+    pub x: u32,
+    /// Row of the cell
+    pub y: u32,
+    /// Side of the cell that's closed
+    pub side: Side,
+}
+
+/// Image that doesn't follow the edge convention.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdgeModelError {
+    /// Width or height is even or less than 3
+    Size { width: u32, height: u32 },
+    /// Square of the outer border that isn't a wall
+    OpenBorder(Point),
+    /// Start, end or checkpoint on a wall line instead of a cell
+    PointOnWallLine(Point),
+}
+
+#[cfg(feature = "std")]
+impl Display for EdgeModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Size { width, height } => write!(
+                f,
+                "a {width}x{height} image can't hold edge walls, it needs an odd size of \
+                 2 * cells + 1 in both directions"
+            ),
+            Self::OpenBorder(point) => write!(
+                f,
+                "edge walls need a closed border but ({}, {}) isn't a wall",
+                point.x, point.y
+            ),
+            Self::PointOnWallLine(point) => write!(
+                f,
+                "({}, {}) is on a wall line, points have to be on cells at odd coordinates",
+                point.x, point.y
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for EdgeModelError {}
+
+#[cfg(feature = "std")]
+/// Build a level of `(grid.width() - 1) / 2` by `(grid.height() - 1) / 2`
+/// cells with its walls in `edge_walls`.
+pub fn scan_edges(grid: &Grid) -> Result<Lvl, EdgeModelError> {
+    let (width, height) = (grid.width(), grid.height());
+    if width < 3 || height < 3 || width % 2 == 0 || height % 2 == 0 {
+        return Err(EdgeModelError::Size { width, height });
+    }
+
+    let is_wall = |x, y| grid.get(x, y) == SquareType::Wall;
+    let border = (0..width)
+        .flat_map(|x| [Point::new(x, 0), Point::new(x, height - 1)])
+        .chain((0..height).flat_map(|y| [Point::new(0, y), Point::new(width - 1, y)]));
+    for point in border {
+        if !is_wall(point.x, point.y) {
+            return Err(EdgeModelError::OpenBorder(point));
+        }
+    }
+
+    let mut lvl = Lvl {
+        format_version: FORMAT_VERSION,
+        width: width / 2,
+        height: height / 2,
+        wrap: Wrap::None,
+        walls: Vec::new(),
+        dynamic_walls: Vec::new(),
+        rects: Vec::new(),
+        wall_paths: Vec::new(),
+        collision: Vec::new(),
+        edge_walls: Vec::new(),
+        start: Point::new(0, 0),
+        end: Point::new(0, 0),
+        checkpoints: Vec::new(),
+        optional_checkpoints: Vec::new(),
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let square = grid.get(x, y);
+            if !square.is_special() {
+                continue;
+            }
+            if x % 2 == 0 || y % 2 == 0 {
+                return Err(EdgeModelError::PointOnWallLine(Point::new(x, y)));
+            }
+
+            let cell = Point::new(x / 2, y / 2);
+            match square {
+                SquareType::Start => lvl.start = cell,
+                SquareType::End => lvl.end = cell,
+                SquareType::Checkpoint => lvl.checkpoints.push(cell),
+                SquareType::OptionalCheckpoint => lvl.optional_checkpoints.push(cell),
+                SquareType::Wall | SquareType::DynamicWall(_) | SquareType::Empty => (),
+            }
+        }
+    }
+
+    for y in 0..lvl.height {
+        for x in 0..lvl.width {
+            let (center_x, center_y) = (2 * x + 1, 2 * y + 1);
+            let sides = [
+                (y == 0, Side::Top),
+                (x == 0, Side::Left),
+                (is_wall(center_x + 1, center_y), Side::Right),
+                (is_wall(center_x, center_y + 1), Side::Bottom),
+            ];
+            lvl.edge_walls.extend(
+                sides
+                    .into_iter()
+                    .filter(|(closed, _)| *closed)
+                    .map(|(_, side)| EdgeWall { x, y, side }),
+            );
+        }
+    }
+
+    Ok(lvl)
+}
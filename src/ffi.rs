@@ -0,0 +1,116 @@
+//! C interface, declared in `maze_lvl_maker.h`.
+//!
+//! Every function returns [`MAZE_LVL_OK`] or one of the negative error
+//! codes. The message of the last error on the calling thread is available
+//! from [`maze_lvl_last_error`].
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{scan_image, ScanOptions};
+
+/// Success.
+pub const MAZE_LVL_OK: c_int = 0;
+/// A pointer argument is null.
+pub const MAZE_LVL_ERR_NULL: c_int = -1;
+/// The path isn't valid UTF-8.
+pub const MAZE_LVL_ERR_PATH: c_int = -2;
+/// The image can't be read or decoded.
+pub const MAZE_LVL_ERR_IMAGE: c_int = -3;
+/// The buffer can't hold the json and its terminating null, `out_len` is
+/// set to the size needed.
+pub const MAZE_LVL_ERR_BUFFER_TOO_SMALL: c_int = -4;
+/// The parser panicked.
+pub const MAZE_LVL_ERR_PANIC: c_int = -5;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(code: c_int, message: impl ToString) -> c_int {
+    let message = CString::new(message.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    code
+}
+
+/// Scan the image at `path` with the default options and write the level as
+/// null terminated json into `out_json`.
+///
+/// `*out_len` is the size of `out_json` in bytes when called and the length
+/// of the json without the null afterwards. When the buffer is too small
+/// nothing is written, [`MAZE_LVL_ERR_BUFFER_TOO_SMALL`] is returned and
+/// `*out_len` is the size to retry with.
+///
+/// # Safety
+///
+/// `path` must be a null terminated string, `out_len` must be valid for
+/// reads and writes and `out_json` must be valid for writes of `*out_len`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn maze_lvl_parse_image(
+    path: *const c_char,
+    out_json: *mut c_char,
+    out_len: *mut usize,
+) -> c_int {
+    if path.is_null() || out_len.is_null() {
+        return fail(MAZE_LVL_ERR_NULL, "path and out_len can't be null");
+    }
+    let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+        return fail(MAZE_LVL_ERR_PATH, "path isn't valid UTF-8");
+    };
+
+    let scanned = panic::catch_unwind(AssertUnwindSafe(|| {
+        let image = image::open(path).map_err(|error| error.to_string())?;
+        serde_json::to_string(&scan_image(&image, ScanOptions::default()))
+            .map_err(|error| error.to_string())
+    }));
+    let json = match scanned {
+        Ok(Ok(json)) => json,
+        Ok(Err(error)) => return fail(MAZE_LVL_ERR_IMAGE, format!("{path}: {error}")),
+        Err(_) => return fail(MAZE_LVL_ERR_PANIC, format!("{path}: parser panicked")),
+    };
+
+    let needed = json.len() + 1;
+    if out_json.is_null() || unsafe { *out_len } < needed {
+        unsafe { *out_len = needed };
+        return fail(
+            MAZE_LVL_ERR_BUFFER_TOO_SMALL,
+            format!("the json needs {needed} bytes"),
+        );
+    }
+
+    unsafe {
+        ptr::copy_nonoverlapping(json.as_ptr().cast::<c_char>(), out_json, json.len());
+        *out_json.add(json.len()) = 0;
+        *out_len = json.len();
+    }
+    MAZE_LVL_OK
+}
+
+/// Message of the last error on this thread, null if there was none. Free
+/// it with [`maze_lvl_free_string`].
+#[no_mangle]
+pub extern "C" fn maze_lvl_last_error() -> *mut c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .clone()
+            .map_or(ptr::null_mut(), CString::into_raw)
+    })
+}
+
+/// Free a string returned by this library, null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or come from [`maze_lvl_last_error`] and not be
+/// freed already.
+#[no_mangle]
+pub unsafe extern "C" fn maze_lvl_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
@@ -0,0 +1,103 @@
+//! Levels as Godot 4 text resources, loadable with `load()` without an
+//! importer or a script.
+//!
+//! Everything is resource metadata, read with `get_meta`. The types are
+//! listed in a comment at the top of the file. Walls, rectangles and wall
+//! paths all become `Rect2`s of the squares they cover. Walls across the
+//! seam of a wrapping level are split in two and diagonal walls into their
+//! squares. Edge walls and polygons aren't written.
+
+use crate::{lvl::path_walls, Lvl, Point, Rect, Wrap};
+
+const HEADER: &str = "\
+[gd_resource type=\"Resource\" format=3]
+
+; Level written by maze-lvl-maker, every field is metadata:
+; width, height: int, in squares
+; wrap: String, \"none\", \"x\", \"y\" or \"both\"
+; start, end: Vector2i
+; checkpoints, optional_checkpoints: PackedVector2Array
+; walls: Array[Rect2] of the squares each wall covers
+; dynamic_walls: Array[Dictionary] of {\"rect\": Rect2, \"period\": int}
+
+[resource]
+";
+
+impl Lvl {
+    /// The level as a `.tres` resource, see the module documentation.
+    pub fn to_godot(&self) -> String {
+        let wrap = match self.wrap {
+            Wrap::None => "none",
+            Wrap::X => "x",
+            Wrap::Y => "y",
+            Wrap::Both => "both",
+        };
+        let walls: Vec<String> = self
+            .walls
+            .iter()
+            .copied()
+            .chain(self.wall_paths.iter().flat_map(|path| path_walls(path)))
+            .flat_map(|wall| self.wall_rects(wall))
+            .chain(self.rects.iter().copied())
+            .map(rect2)
+            .collect();
+        let dynamic_walls: Vec<String> = self
+            .dynamic_walls
+            .iter()
+            .flat_map(|dynamic_wall| {
+                self.wall_rects(dynamic_wall.wall).into_iter().map(|rect| {
+                    format!(
+                        "{{\"rect\": {}, \"period\": {}}}",
+                        rect2(rect),
+                        dynamic_wall.period
+                    )
+                })
+            })
+            .collect();
+
+        let mut tres = HEADER.to_string();
+        for line in [
+            format!("metadata/width = {}", self.width),
+            format!("metadata/height = {}", self.height),
+            format!("metadata/wrap = \"{wrap}\""),
+            format!("metadata/start = {}", vector2i(self.start)),
+            format!("metadata/end = {}", vector2i(self.end)),
+            format!(
+                "metadata/checkpoints = {}",
+                packed_vector2_array(&self.checkpoints)
+            ),
+            format!(
+                "metadata/optional_checkpoints = {}",
+                packed_vector2_array(&self.optional_checkpoints)
+            ),
+            format!("metadata/walls = Array[Rect2]([{}])", walls.join(", ")),
+            format!(
+                "metadata/dynamic_walls = Array[Dictionary]([{}])",
+                dynamic_walls.join(", ")
+            ),
+        ] {
+            tres.push_str(&line);
+            tres.push('\n');
+        }
+        tres
+    }
+}
+
+fn rect2(rect: Rect) -> String {
+    format!(
+        "Rect2({}, {}, {}, {})",
+        rect.x, rect.y, rect.width, rect.height
+    )
+}
+
+fn vector2i(point: Point) -> String {
+    format!("Vector2i({}, {})", point.x, point.y)
+}
+
+fn packed_vector2_array(points: &[Point]) -> String {
+    let coordinates: Vec<String> = points
+        .iter()
+        .flat_map(|point| [point.x.to_string(), point.y.to_string()])
+        .collect();
+    format!("PackedVector2Array({})", coordinates.join(", "))
+}
@@ -0,0 +1,176 @@
+//! The passable squares reduced to a graph for navigation.
+//!
+//! Nodes are the start, the end, checkpoints, dead ends and junctions.
+//! Corridors between them become edges whose length is the number of
+//! squares strictly between the two nodes, so neighboring junctions are
+//! joined by edges of length 0. A corridor that comes back to the node it
+//! left is an edge from that node to itself. Loops without any node get
+//! one on their first square in reading order.
+//!
+//! Every passable square is either a node or inside exactly one edge, so
+//! the node count plus the edge lengths is the number of passable squares.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{Grid, Point, SquareType};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Start,
+    End,
+    Checkpoint,
+    OptionalCheckpoint,
+    /// Single way in
+    DeadEnd,
+    /// Three or more ways in
+    Junction,
+    /// Square picked to anchor a loop without other nodes
+    Loop,
+    /// No way in at all
+    Isolated,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphNode {
+    pub id: usize,
+    pub x: u32,
+    pub y: u32,
+    pub kind: NodeKind,
+}
+
+/// Corridor between the nodes `a` and `b`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub a: usize,
+    pub b: usize,
+    /// Squares between the two nodes
+    pub length: u32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct CorridorGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl CorridorGraph {
+    /// Build the graph over the passable squares of `grid`.
+    pub fn from_grid(grid: &Grid) -> Self {
+        let mut builder = Builder {
+            grid,
+            graph: Self::default(),
+            node_at: vec![None; grid.square_count()],
+            covered: vec![false; grid.square_count()],
+            walked: HashSet::new(),
+        };
+
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let point = Point::new(x, y);
+                if !grid.is_passable(point) {
+                    continue;
+                }
+
+                let kind = match (grid.get(x, y), builder.exits(point).len()) {
+                    (SquareType::Start, _) => NodeKind::Start,
+                    (SquareType::End, _) => NodeKind::End,
+                    (SquareType::Checkpoint, _) => NodeKind::Checkpoint,
+                    (SquareType::OptionalCheckpoint, _) => NodeKind::OptionalCheckpoint,
+                    (_, 0) => NodeKind::Isolated,
+                    (_, 1) => NodeKind::DeadEnd,
+                    (_, 2) => continue,
+                    _ => NodeKind::Junction,
+                };
+                builder.add_node(point, kind);
+            }
+        }
+
+        for index in 0..builder.graph.nodes.len() {
+            let node = builder.graph.nodes[index];
+            builder.walk(Point::new(node.x, node.y));
+        }
+
+        // What's left are loops of corridor squares without any node
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let point = Point::new(x, y);
+                let index = grid.index(x, y);
+                if grid.is_passable(point)
+                    && builder.node_at[index].is_none()
+                    && !builder.covered[index]
+                {
+                    builder.add_node(point, NodeKind::Loop);
+                    builder.walk(point);
+                }
+            }
+        }
+
+        builder.graph
+    }
+}
+
+struct Builder<'a> {
+    grid: &'a Grid,
+    graph: CorridorGraph,
+    node_at: Vec<Option<usize>>,
+    /// Corridor squares that are inside an edge
+    covered: Vec<bool>,
+    /// Steps out of a node that were already walked, from either end of
+    /// their corridor
+    walked: HashSet<(Point, Point)>,
+}
+
+impl Builder<'_> {
+    fn exits(&self, point: Point) -> Vec<Point> {
+        self.grid
+            .moves(point)
+            .filter(|neighbor| self.grid.is_passable(*neighbor))
+            .collect()
+    }
+
+    fn node(&self, point: Point) -> Option<usize> {
+        self.node_at[self.grid.index(point.x, point.y)]
+    }
+
+    fn add_node(&mut self, point: Point, kind: NodeKind) {
+        let id = self.graph.nodes.len();
+        self.graph.nodes.push(GraphNode {
+            id,
+            x: point.x,
+            y: point.y,
+            kind,
+        });
+        self.node_at[self.grid.index(point.x, point.y)] = Some(id);
+    }
+
+    /// Follow every corridor leaving the node at `from` to the next node.
+    fn walk(&mut self, from: Point) {
+        let a = self.node(from).expect("walks start at nodes");
+        for first in self.exits(from) {
+            if self.walked.contains(&(from, first)) {
+                continue;
+            }
+
+            let (mut previous, mut current, mut length) = (from, first, 0);
+            while self.node(current).is_none() {
+                self.covered[self.grid.index(current.x, current.y)] = true;
+                length += 1;
+                let next = self
+                    .exits(current)
+                    .into_iter()
+                    .find(|next| *next != previous)
+                    .expect("corridor squares have two ways out");
+                previous = current;
+                current = next;
+            }
+
+            self.walked.insert((from, first));
+            self.walked.insert((current, previous));
+            let b = self.node(current).expect("walks end at nodes");
+            self.graph.edges.push(GraphEdge { a, b, length });
+        }
+    }
+}
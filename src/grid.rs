@@ -0,0 +1,439 @@
+use std::{collections::VecDeque, error::Error, fmt::Display};
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+use crate::{
+    lvl::path_walls, scan_grid, Lvl, Point, ScanOptions, SquareType, ValidationError, Wall, Wrap,
+};
+
+/// Grids that were expected to have the same size don't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeMismatch {
+    pub expected: (u32, u32),
+    pub found: (u32, u32),
+}
+
+impl Display for SizeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected a {}x{} level but got {}x{}",
+            self.expected.0, self.expected.1, self.found.0, self.found.1
+        )
+    }
+}
+
+impl Error for SizeMismatch {}
+
+/// A pixel whose color isn't in the palette of [`SquareType::from_rgba`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownColor {
+    pub x: u32,
+    pub y: u32,
+    pub rgba: [u8; 4],
+}
+
+impl Display for UnknownColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [r, g, b, a] = self.rgba;
+        write!(
+            f,
+            "the pixel at ({}, {}) is rgba({r}, {g}, {b}, {a}), which isn't in the palette",
+            self.x, self.y
+        )
+    }
+}
+
+impl Error for UnknownColor {}
+
+/// Fully materialized `width` x `height` grid of squares, stored row-major.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: u32,
+    height: u32,
+    wrap: Wrap,
+    cells: Vec<SquareType>,
+}
+
+impl Grid {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            wrap: Wrap::None,
+            // Widened first, the area of a large image doesn't fit in a u32
+            cells: vec![SquareType::Empty; width as usize * height as usize],
+        }
+    }
+
+    /// Classify every pixel of the image.
+    pub fn from_image(img: &DynamicImage) -> Self {
+        let mut grid = Self::new(img.width(), img.height());
+        for (x, y, pixel) in img.pixels() {
+            grid.put(x, y, SquareType::from(pixel.0));
+        }
+
+        grid
+    }
+
+    /// [`Grid::from_image`] that fails on the first pixel, in row-major
+    /// order, that isn't in the palette instead of panicking.
+    pub fn try_from_image(img: &DynamicImage) -> Result<Self, UnknownColor> {
+        let mut grid = Self::new(img.width(), img.height());
+        for (x, y, pixel) in img.pixels() {
+            let square = SquareType::from_rgba(pixel.0).ok_or(UnknownColor {
+                x,
+                y,
+                rgba: pixel.0,
+            })?;
+            grid.put(x, y, square);
+        }
+
+        Ok(grid)
+    }
+
+    /// Draw every square as `cell_size` by `cell_size` pixels of its palette
+    /// color, the inverse of [`Grid::from_image`] at a `cell_size` of 1.
+    pub fn to_image(&self, cell_size: u32) -> RgbaImage {
+        RgbaImage::from_fn(self.width * cell_size, self.height * cell_size, |x, y| {
+            Rgba(self.get(x / cell_size, y / cell_size).to_rgba())
+        })
+    }
+
+    /// Paint the walls first and the special points on top of them.
+    pub fn from_lvl(lvl: &Lvl) -> Self {
+        let mut grid = Self::new(lvl.width, lvl.height);
+        grid.wrap = lvl.wrap;
+
+        for wall in &lvl.walls {
+            grid.paint(*wall, SquareType::Wall);
+        }
+        for rect in &lvl.rects {
+            for y in rect.y..rect.y + rect.height {
+                for x in rect.x..rect.x + rect.width {
+                    grid.put(x, y, SquareType::Wall);
+                }
+            }
+        }
+        for wall in lvl.wall_paths.iter().flat_map(|path| path_walls(path)) {
+            grid.paint(wall, SquareType::Wall);
+        }
+        for polygon in &lvl.collision {
+            for y in 0..grid.height {
+                for x in 0..grid.width {
+                    if polygon.contains(x, y) {
+                        grid.put(x, y, SquareType::Wall);
+                    }
+                }
+            }
+        }
+        for dynamic_wall in &lvl.dynamic_walls {
+            grid.paint(
+                dynamic_wall.wall,
+                SquareType::DynamicWall(dynamic_wall.period),
+            );
+        }
+        for checkpoint in &lvl.checkpoints {
+            grid.put(checkpoint.x, checkpoint.y, SquareType::Checkpoint);
+        }
+        for checkpoint in &lvl.optional_checkpoints {
+            grid.put(checkpoint.x, checkpoint.y, SquareType::OptionalCheckpoint);
+        }
+        grid.put(lvl.end.x, lvl.end.y, SquareType::End);
+        grid.put(lvl.start.x, lvl.start.y, SquareType::Start);
+
+        grid
+    }
+
+    /// Merge the squares back into walls with the default [`ScanOptions`],
+    /// the inverse of [`Grid::from_lvl`] up to the order and split of the
+    /// walls.
+    pub fn to_lvl(&self) -> Result<Lvl, ValidationError> {
+        let find = |wanted: SquareType| -> Vec<Point> {
+            (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| Point::new(x, y)))
+                .filter(|point| self.get(point.x, point.y) == wanted)
+                .collect()
+        };
+
+        match find(SquareType::Start).as_slice() {
+            [] => return Err(ValidationError::MissingStart),
+            [_] => (),
+            starts => return Err(ValidationError::MultipleStarts(starts.to_vec())),
+        }
+        match find(SquareType::End).as_slice() {
+            [] => return Err(ValidationError::MissingEnd),
+            [_] => (),
+            ends => return Err(ValidationError::MultipleEnds(ends.to_vec())),
+        }
+
+        Ok(scan_grid(self, ScanOptions::default()))
+    }
+
+    /// Copy every square of `layer` that isn't empty on top of this grid.
+    pub fn overlay(&mut self, layer: &Self) -> Result<(), SizeMismatch> {
+        if (self.width, self.height) != (layer.width, layer.height) {
+            return Err(SizeMismatch {
+                expected: (self.width, self.height),
+                found: (layer.width, layer.height),
+            });
+        }
+
+        for (square, layer_square) in self.cells.iter_mut().zip(&layer.cells) {
+            if *layer_square != SquareType::Empty {
+                *square = *layer_square;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub(crate) const fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Number of squares, for buffers with an entry per [`Grid::index`].
+    pub(crate) fn square_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub const fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// Connect the edges of `wrap` for [`Grid::moves`].
+    pub fn set_wrap(&mut self, wrap: Wrap) -> &mut Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> SquareType {
+        self.cells[self.index(x, y)]
+    }
+
+    /// Change the square at `(x, y)`, panics outside of the grid.
+    pub fn set(&mut self, x: u32, y: u32, square: SquareType) -> &mut Self {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is outside of the {}x{} grid",
+            self.width,
+            self.height
+        );
+        let index = self.index(x, y);
+        self.cells[index] = square;
+        self
+    }
+
+    /// Copy with the inside of solid wall areas emptied, only wall squares
+    /// with a non-wall square among their 8 neighbors are kept. Squares on
+    /// the edge of the grid are always kept.
+    pub fn hollow(&self) -> Self {
+        let mut hollow = self.clone();
+        for y in 1..self.height.saturating_sub(1) {
+            for x in 1..self.width.saturating_sub(1) {
+                let solid = (y - 1..=y + 1)
+                    .all(|ny| (x - 1..=x + 1).all(|nx| self.get(nx, ny) == SquareType::Wall));
+                if solid {
+                    hollow.put(x, y, SquareType::Empty);
+                }
+            }
+        }
+
+        hollow
+    }
+
+    /// Tile codes row by row, see [`SquareType::code`].
+    pub fn codes(&self) -> Vec<Vec<u8>> {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| row.iter().map(|square| square.code()).collect())
+            .collect()
+    }
+
+    /// Run lengths per row, alternating between squares that can be walked
+    /// through and solid walls, starting with the walkable ones. A row that
+    /// starts with a wall starts with a zero run, every row sums to `width`.
+    pub fn row_runs(&self) -> Vec<Vec<u32>> {
+        (0..self.height)
+            .map(|y| {
+                let mut runs = vec![0];
+                let mut wall = false;
+                for x in 0..self.width {
+                    if self.is_passable(Point::new(x, y)) == wall {
+                        wall = !wall;
+                        runs.push(0);
+                    }
+                    *runs.last_mut().expect("runs start with a run") += 1;
+                }
+                runs
+            })
+            .collect()
+    }
+
+    pub(crate) fn put(&mut self, x: u32, y: u32, square: SquareType) {
+        if x < self.width && y < self.height {
+            let index = self.index(x, y);
+            self.cells[index] = square;
+        }
+    }
+
+    fn paint(&mut self, wall: Wall, square: SquareType) {
+        for piece in wall.split_at_seam(self.width, self.height) {
+            for point in piece.squares() {
+                self.put(point.x, point.y, square);
+            }
+        }
+    }
+
+    /// Whether the square at `point` is [`SquareType::is_passable`].
+    pub fn is_passable(&self, point: Point) -> bool {
+        self.get(point.x, point.y).is_passable()
+    }
+
+    /// Orthogonal neighbors of `point` that lie inside the grid.
+    pub fn neighbors(&self, point: Point) -> impl Iterator<Item = Point> {
+        let Point { x, y } = point;
+        let (width, height) = (self.width, self.height);
+
+        [
+            (x > 0).then(|| Point::new(x - 1, y)),
+            (x + 1 < width).then(|| Point::new(x + 1, y)),
+            (y > 0).then(|| Point::new(x, y - 1)),
+            (y + 1 < height).then(|| Point::new(x, y + 1)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Squares a player can step to from `point`: the
+    /// [`neighbors`](Grid::neighbors) and the square on the opposite edge
+    /// when the grid [`Wrap`]s there.
+    pub fn moves(&self, point: Point) -> impl Iterator<Item = Point> {
+        let Point { x, y } = point;
+        let (width, height) = (self.width, self.height);
+        // Narrower grids already reach the opposite edge without wrapping
+        let wraps_x = self.wrap.wraps_x() && width > 2;
+        let wraps_y = self.wrap.wraps_y() && height > 2;
+
+        self.neighbors(point).chain(
+            [
+                (wraps_x && x == 0).then(|| Point::new(width - 1, y)),
+                (wraps_x && x == width - 1).then(|| Point::new(0, y)),
+                (wraps_y && y == 0).then(|| Point::new(x, height - 1)),
+                (wraps_y && y == height - 1).then(|| Point::new(x, 0)),
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+
+    /// Breadth first search over the passable squares.
+    ///
+    /// Returns the distance in steps from `from` to every square, row-major,
+    /// `None` for squares that can't be reached.
+    pub fn distances_from(&self, from: Point) -> Vec<Option<u32>> {
+        let mut distances = vec![None; self.cells.len()];
+        if !self.is_passable(from) {
+            return distances;
+        }
+
+        let mut queue = VecDeque::from([from]);
+        distances[self.index(from.x, from.y)] = Some(0);
+        while let Some(point) = queue.pop_front() {
+            let distance = distances[self.index(point.x, point.y)].unwrap_or_default();
+            for neighbor in self.moves(point) {
+                let index = self.index(neighbor.x, neighbor.y);
+                if distances[index].is_none() && self.is_passable(neighbor) {
+                    distances[index] = Some(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Flood fill every region of connected passable squares.
+    ///
+    /// Regions are sorted by size, largest first.
+    pub fn components(&self) -> Vec<Vec<Point>> {
+        let mut visited = vec![false; self.cells.len()];
+        let mut components = Vec::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let start = Point::new(x, y);
+                if visited[self.index(x, y)] || !self.is_passable(start) {
+                    continue;
+                }
+
+                let mut component = Vec::new();
+                let mut queue = VecDeque::from([start]);
+                visited[self.index(x, y)] = true;
+                while let Some(point) = queue.pop_front() {
+                    component.push(point);
+                    for neighbor in self.moves(point) {
+                        let index = self.index(neighbor.x, neighbor.y);
+                        if !visited[index] && self.is_passable(neighbor) {
+                            visited[index] = true;
+                            queue.push_back(neighbor);
+                        }
+                    }
+                }
+                components.push(component);
+            }
+        }
+
+        components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+        components
+    }
+
+    /// Squares of a shortest path from `from` to `to`, both included, each
+    /// one of the [`Grid::moves`] of the square before. `None` when `to`
+    /// can't be reached.
+    pub fn shortest_path(&self, from: Point, to: Point) -> Option<Vec<Point>> {
+        if !self.is_passable(from) || !self.is_passable(to) {
+            return None;
+        }
+
+        // The square every reached square was first reached from
+        let mut previous = vec![None; self.cells.len()];
+        previous[self.index(from.x, from.y)] = Some(from);
+        let mut queue = VecDeque::from([from]);
+        while let Some(point) = queue.pop_front() {
+            if point == to {
+                break;
+            }
+            for neighbor in self.moves(point) {
+                let index = self.index(neighbor.x, neighbor.y);
+                if previous[index].is_none() && self.is_passable(neighbor) {
+                    previous[index] = Some(point);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let mut path = vec![to];
+        let mut point = to;
+        while point != from {
+            point = previous[self.index(point.x, point.y)]?;
+            path.push(point);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Shortest path length in steps between `from` and `to`.
+    pub fn distance(&self, from: Point, to: Point) -> Option<u32> {
+        self.distances_from(from)[self.index(to.x, to.y)]
+    }
+}
@@ -0,0 +1,302 @@
+//! Hexagonal levels drawn as blocks of pixels.
+//!
+//! The only layout is `"hex-offset"`, pointy topped hexagons in odd-r
+//! offset coordinates: cell `(col, row)` is the `cell_size` by `cell_size`
+//! block whose top left pixel is at `(col * cell_size, row * cell_size)`,
+//! with odd rows shifted right by `cell_size / 2` pixels. A cell is
+//! classified by the pixel in the middle of its block, so an image of `cols`
+//! by `rows` cells is `cols * cell_size + cell_size / 2` pixels wide and
+//! `rows * cell_size` high. Cells that don't fit completely are dropped.
+//!
+//! Points are `{x: col, y: row}` in offset coordinates. [`Axial`]
+//! coordinates have straight axes and are used for the math: `q = col -
+//! (row - row % 2) / 2` and `r = row`.
+
+use std::collections::{HashSet, VecDeque};
+
+use serde::Serialize;
+
+use crate::{Grid, Point, SquareType};
+
+/// Hexagon in axial coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Axial {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Axial {
+    /// Offset coordinates of the cell, `None` left of or above the level.
+    pub fn to_offset(self) -> Option<Point> {
+        let col = self.q + (self.r - (self.r & 1)) / 2;
+        Some(Point::new(
+            u32::try_from(col).ok()?,
+            u32::try_from(self.r).ok()?,
+        ))
+    }
+
+    /// Neighbor one step along `axis`, backwards when `forward` is false.
+    #[must_use]
+    pub const fn step(self, axis: HexAxis, forward: bool) -> Self {
+        let (dq, dr) = axis.direction();
+        let sign = if forward { 1 } else { -1 };
+        Self {
+            q: self.q + sign * dq,
+            r: self.r + sign * dr,
+        }
+    }
+
+    /// Number of steps between the cells.
+    pub const fn distance(self, other: Self) -> u32 {
+        let (dq, dr) = (self.q - other.q, self.r - other.r);
+        ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as u32
+    }
+}
+
+impl From<Point> for Axial {
+    fn from(point: Point) -> Self {
+        let (col, row) = (i64::from(point.x), i64::from(point.y));
+        Self {
+            q: col - (row - (row & 1)) / 2,
+            r: row,
+        }
+    }
+}
+
+/// The three directions walls run in, each also covers its opposite.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HexAxis {
+    /// Along a row, to the right
+    E,
+    /// Down to the right
+    Se,
+    /// Down to the left
+    Sw,
+}
+
+impl HexAxis {
+    pub const ALL: [Self; 3] = [Self::E, Self::Se, Self::Sw];
+
+    /// One step forward in axial `(q, r)`.
+    pub const fn direction(self) -> (i64, i64) {
+        match self {
+            Self::E => (1, 0),
+            Self::Se => (0, 1),
+            Self::Sw => (-1, 1),
+        }
+    }
+}
+
+/// Layout tag written as `grid` so readers know how to place the cells.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HexLayout {
+    #[default]
+    #[serde(rename = "hex-offset")]
+    Offset,
+}
+
+/// `length` wall cells from `start` along `axis`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexWall {
+    pub start: Point,
+    pub axis: HexAxis,
+    pub length: u32,
+}
+
+impl HexWall {
+    /// Cells covered by the wall, from `start` on.
+    pub fn cells(self) -> impl Iterator<Item = Point> {
+        let mut axial = Axial::from(self.start);
+        (0..self.length).filter_map(move |_| {
+            let cell = axial.to_offset();
+            axial = axial.step(self.axis, true);
+            cell
+        })
+    }
+}
+
+/// Level of hexagonal cells, the hex counterpart of [`Lvl`](crate::Lvl).
+///
+/// Dynamic walls aren't supported and scan as empty cells.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct HexLvl {
+    pub grid: HexLayout,
+    /// Cells per row
+    pub width: u32,
+    /// Number of rows
+    pub height: u32,
+    pub walls: Vec<HexWall>,
+    pub start: Point,
+    pub end: Point,
+    pub checkpoints: Vec<Point>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub optional_checkpoints: Vec<Point>,
+}
+
+impl HexLvl {
+    /// Cells next to `cell` that are inside the level.
+    pub fn neighbors(&self, cell: Point) -> impl Iterator<Item = Point> {
+        let axial = Axial::from(cell);
+        let (width, height) = (self.width, self.height);
+        HexAxis::ALL
+            .into_iter()
+            .flat_map(move |axis| [axial.step(axis, true), axial.step(axis, false)])
+            .filter_map(Axial::to_offset)
+            .filter(move |point| point.x < width && point.y < height)
+    }
+
+    /// Length in steps of the shortest path from start to end, `None` if
+    /// the end can't be reached.
+    pub fn shortest_path_length(&self) -> Option<u32> {
+        if self.end.x >= self.width || self.end.y >= self.height {
+            return None;
+        }
+        self.distances_from_start()[self.index(self.end)]
+    }
+
+    /// Whether the end and every required checkpoint can be reached from
+    /// the start.
+    pub fn is_solvable(&self) -> bool {
+        let distances = self.distances_from_start();
+        let reachable = |point: &Point| {
+            point.x < self.width && point.y < self.height && distances[self.index(*point)].is_some()
+        };
+        reachable(&self.end) && self.checkpoints.iter().all(reachable)
+    }
+
+    const fn index(&self, cell: Point) -> usize {
+        (cell.y * self.width + cell.x) as usize
+    }
+
+    /// Breadth first search from the start over the cells that aren't
+    /// walls, row-major.
+    fn distances_from_start(&self) -> Vec<Option<u32>> {
+        let mut wall = vec![false; self.width as usize * self.height as usize];
+        for cell in self.walls.iter().flat_map(|wall| wall.cells()) {
+            if cell.x < self.width && cell.y < self.height {
+                wall[self.index(cell)] = true;
+            }
+        }
+
+        let mut distances = vec![None; wall.len()];
+        if self.start.x >= self.width || self.start.y >= self.height {
+            return distances;
+        }
+        distances[self.index(self.start)] = Some(0);
+        let mut queue = VecDeque::from([self.start]);
+        while let Some(cell) = queue.pop_front() {
+            let distance = distances[self.index(cell)].unwrap_or_default();
+            for neighbor in self.neighbors(cell) {
+                let index = self.index(neighbor);
+                if !wall[index] && distances[index].is_none() {
+                    distances[index] = Some(distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+/// Classify the cells of a hex level drawn with `cell_size` pixels per cell
+/// in the layout of the module docs and merge its walls into runs.
+///
+/// Runs along a row are kept down to single cells, runs along the other two
+/// axes need at least two cells. Single cells that are part of a longer run
+/// along another axis are dropped.
+pub fn scan_hex(pixels: &Grid, cell_size: u32) -> HexLvl {
+    let cells = hex_cells(pixels, cell_size);
+    let mut lvl = HexLvl {
+        grid: HexLayout::Offset,
+        width: cells.width(),
+        height: cells.height(),
+        walls: Vec::new(),
+        start: Point::new(0, 0),
+        end: Point::new(0, 0),
+        checkpoints: Vec::new(),
+        optional_checkpoints: Vec::new(),
+    };
+
+    for y in 0..cells.height() {
+        for x in 0..cells.width() {
+            match cells.get(x, y) {
+                SquareType::Start => lvl.start = Point::new(x, y),
+                SquareType::End => lvl.end = Point::new(x, y),
+                SquareType::Checkpoint => lvl.checkpoints.push(Point::new(x, y)),
+                SquareType::OptionalCheckpoint => {
+                    lvl.optional_checkpoints.push(Point::new(x, y));
+                }
+                SquareType::Wall | SquareType::DynamicWall(_) | SquareType::Empty => (),
+            }
+        }
+    }
+
+    let is_wall = |axial: Axial| {
+        axial.to_offset().is_some_and(|point| {
+            point.x < cells.width()
+                && point.y < cells.height()
+                && cells.get(point.x, point.y) == SquareType::Wall
+        })
+    };
+    for axis in HexAxis::ALL {
+        for y in 0..cells.height() {
+            for x in 0..cells.width() {
+                let start = Axial::from(Point::new(x, y));
+                // Only start at the first cell of a run
+                if !is_wall(start) || is_wall(start.step(axis, false)) {
+                    continue;
+                }
+
+                let mut length = 1;
+                let mut next = start.step(axis, true);
+                while is_wall(next) {
+                    length += 1;
+                    next = next.step(axis, true);
+                }
+                if axis == HexAxis::E || length > 1 {
+                    lvl.walls.push(HexWall {
+                        start: Point::new(x, y),
+                        axis,
+                        length,
+                    });
+                }
+            }
+        }
+    }
+
+    let long: HashSet<Point> = lvl
+        .walls
+        .iter()
+        .filter(|wall| wall.axis != HexAxis::E)
+        .flat_map(|wall| wall.cells())
+        .collect();
+    lvl.walls
+        .retain(|wall| wall.length > 1 || wall.axis != HexAxis::E || !long.contains(&wall.start));
+
+    lvl
+}
+
+/// Sample the middle pixel of every complete cell.
+fn hex_cells(pixels: &Grid, cell_size: u32) -> Grid {
+    let cell_size = cell_size.max(1);
+    let shift = cell_size / 2;
+    let height = pixels.height() / cell_size;
+    let width = if height > 1 {
+        pixels.width().saturating_sub(shift) / cell_size
+    } else {
+        pixels.width() / cell_size
+    };
+
+    let mut cells = Grid::new(width, height);
+    for row in 0..height {
+        for col in 0..width {
+            let x = col * cell_size + if row % 2 == 1 { shift } else { 0 } + cell_size / 2;
+            let y = row * cell_size + cell_size / 2;
+            cells.set(col, row, pixels.get(x, y));
+        }
+    }
+
+    cells
+}
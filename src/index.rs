@@ -0,0 +1,180 @@
+//! Spatial index over the walls of a level.
+//!
+//! A quadtree over the smallest power of two square that holds the level.
+//! Every wall is stored in the smallest node whose square holds its whole
+//! bounding box, so short walls sink to the leaves and only walls crossing
+//! the middle of a node stay in it. A query visits the nodes overlapping
+//! it and checks the walls stored there.
+
+use crate::{Lvl, Rect, Wall};
+
+/// Walls of a level by position, see [`Lvl::build_index`].
+#[derive(Debug, Clone)]
+pub struct WallIndex<'a> {
+    walls: &'a [Wall],
+    root: Node,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    area: Rect,
+    /// Indices of the walls that don't fit into a single child
+    walls: Vec<usize>,
+    children: Option<Box<[Node; 4]>>,
+}
+
+impl Node {
+    fn new(x: u32, y: u32, size: u32) -> Self {
+        Self {
+            area: Rect {
+                x,
+                y,
+                width: size,
+                height: size,
+            },
+            walls: Vec::new(),
+            children: None,
+        }
+    }
+
+    fn insert(&mut self, index: usize, bounds: Rect) {
+        let Rect { x, y, width, .. } = self.area;
+        let half = width / 2;
+        if half > 0 {
+            let children = self.children.get_or_insert_with(|| {
+                Box::new([
+                    Self::new(x, y, half),
+                    Self::new(x + half, y, half),
+                    Self::new(x, y + half, half),
+                    Self::new(x + half, y + half, half),
+                ])
+            });
+            if let Some(child) = children
+                .iter_mut()
+                .find(|child| child.area.intersection(bounds) == Some(bounds))
+            {
+                child.insert(index, bounds);
+                return;
+            }
+        }
+
+        self.walls.push(index);
+    }
+}
+
+impl<'a> WallIndex<'a> {
+    /// Index `walls` of a `width` by `height` level. Walls that
+    /// [wrap](Wall::wraps) are indexed over their whole row or column.
+    pub fn new(walls: &'a [Wall], width: u32, height: u32) -> Self {
+        let size = width.max(height).max(1).next_power_of_two();
+        let mut root = Node::new(0, 0, size);
+        for (index, wall) in walls.iter().enumerate() {
+            root.insert(index, bounds(*wall, width, height));
+        }
+
+        Self { walls, root }
+    }
+
+    /// Walls covering the square at `(x, y)`, in the order of the level.
+    pub fn query_point(&self, x: u32, y: u32) -> Vec<&'a Wall> {
+        let mut found = Vec::new();
+        let mut node = Some(&self.root);
+        while let Some(current) = node.filter(|node| node.area.contains(x, y)) {
+            found.extend(
+                current
+                    .walls
+                    .iter()
+                    .copied()
+                    .filter(|index| self.walls[*index].contains(x, y)),
+            );
+            node = current
+                .children
+                .as_deref()
+                .and_then(|children| children.iter().find(|child| child.area.contains(x, y)));
+        }
+
+        self.in_order(found)
+    }
+
+    /// Walls covering at least one square of `rect`, in the order of the
+    /// level.
+    pub fn query_rect(&self, rect: Rect) -> Vec<&'a Wall> {
+        let mut found = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            let Some(overlap) = node.area.intersection(rect) else {
+                continue;
+            };
+            found.extend(
+                node.walls
+                    .iter()
+                    .copied()
+                    .filter(|index| overlaps(self.walls[*index], overlap)),
+            );
+            stack.extend(node.children.iter().flat_map(|children| children.iter()));
+        }
+
+        self.in_order(found)
+    }
+
+    fn in_order(&self, mut indices: Vec<usize>) -> Vec<&'a Wall> {
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .map(|index| &self.walls[index])
+            .collect()
+    }
+}
+
+impl Lvl {
+    /// Index the `walls` for queries by position, which only visit the
+    /// walls near the query instead of all of them.
+    pub fn build_index(&self) -> WallIndex<'_> {
+        WallIndex::new(&self.walls, self.width, self.height)
+    }
+}
+
+/// Bounding box of the wall as a rectangle, wrapping walls cover their
+/// whole row or column.
+fn bounds(wall: Wall, width: u32, height: u32) -> Rect {
+    let (top_left, bottom_right) = wall.bounding_box();
+    if wall.wraps() && top_left.y == bottom_right.y {
+        return Rect {
+            x: 0,
+            y: top_left.y,
+            width,
+            height: 1,
+        };
+    }
+    if wall.wraps() {
+        return Rect {
+            x: top_left.x,
+            y: 0,
+            width: 1,
+            height,
+        };
+    }
+
+    Rect {
+        x: top_left.x,
+        y: top_left.y,
+        width: bottom_right.x - top_left.x + 1,
+        height: bottom_right.y - top_left.y + 1,
+    }
+}
+
+fn overlaps(wall: Wall, rect: Rect) -> bool {
+    if !wall.wraps() {
+        return wall.clip(rect).is_some();
+    }
+
+    // Only the squares of the rectangle on the wall's row or column
+    let end = wall.end.unwrap_or(wall.start);
+    if end.y == wall.start.y {
+        rect.contains(rect.x, end.y)
+            && (rect.x..rect.x + rect.width).any(|x| wall.contains(x, end.y))
+    } else {
+        rect.contains(end.x, rect.y)
+            && (rect.y..rect.y + rect.height).any(|y| wall.contains(end.x, y))
+    }
+}
@@ -0,0 +1,546 @@
+//! Levels as LDtk projects, with every field LDtk 1.5.3 saves.
+//!
+//! The project has a single level with two layers: `Entities` holds `Start`,
+//! `End`, `Checkpoint` and `OptionalCheckpoint` instances and `Walls` is an
+//! IntGrid with 1 for walls and 2 for dynamic walls, whose periods are lost.
+//! Unique ids are numbered from 0 in a fixed order and iids count up from
+//! `00000000-0000-4000-8000-000000000000`, so the same level always makes the
+//! same project.
+
+use serde::Serialize;
+
+use crate::{Grid, Lvl, SquareType};
+
+const APP_VERSION: &str = "1.5.3";
+const BG_COLOR: &str = "#696A79";
+
+const LEVEL_UID: u32 = 0;
+const ENTITIES_LAYER_UID: u32 = 1;
+const WALLS_LAYER_UID: u32 = 2;
+/// Entities with their uid and color, the uids follow the layers.
+const ENTITIES: [(&str, u32, &str); 4] = [
+    ("Start", 3, "#00FF00"),
+    ("End", 4, "#FF0000"),
+    ("Checkpoint", 5, "#0000FF"),
+    ("OptionalCheckpoint", 6, "#8080FF"),
+];
+const NEXT_UID: u32 = 7;
+
+/// An LDtk project of a single level, see [`Lvl::to_ldtk`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkProject {
+    #[serde(rename = "__header__")]
+    header: Header,
+    iid: String,
+    json_version: &'static str,
+    app_build_id: f64,
+    next_uid: u32,
+    identifier_style: &'static str,
+    toc: Vec<()>,
+    world_layout: &'static str,
+    world_grid_width: u32,
+    world_grid_height: u32,
+    default_level_width: u32,
+    default_level_height: u32,
+    default_pivot_x: f64,
+    default_pivot_y: f64,
+    default_grid_size: u32,
+    default_entity_width: u32,
+    default_entity_height: u32,
+    bg_color: &'static str,
+    default_level_bg_color: &'static str,
+    minify_json: bool,
+    external_levels: bool,
+    export_tiled: bool,
+    simplified_export: bool,
+    image_export_mode: &'static str,
+    export_level_bg: bool,
+    png_file_pattern: Option<String>,
+    backup_on_save: bool,
+    backup_limit: u32,
+    backup_rel_path: Option<String>,
+    level_name_pattern: &'static str,
+    tutorial_desc: Option<String>,
+    custom_commands: Vec<()>,
+    flags: Vec<()>,
+    defs: Definitions,
+    levels: Vec<Level>,
+    worlds: Vec<()>,
+    dummy_world_iid: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Header {
+    file_type: &'static str,
+    app: &'static str,
+    doc: &'static str,
+    schema: &'static str,
+    app_author: &'static str,
+    app_version: &'static str,
+    url: &'static str,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Definitions {
+    layers: Vec<LayerDef>,
+    entities: Vec<EntityDef>,
+    tilesets: Vec<()>,
+    enums: Vec<()>,
+    external_enums: Vec<()>,
+    level_fields: Vec<()>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct LayerDef {
+    #[serde(rename = "__type")]
+    layer_type: &'static str,
+    identifier: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    uid: u32,
+    doc: Option<String>,
+    ui_color: Option<String>,
+    grid_size: u32,
+    guide_grid_wid: u32,
+    guide_grid_hei: u32,
+    display_opacity: f64,
+    inactive_opacity: f64,
+    hide_in_list: bool,
+    hide_fields_when_inactive: bool,
+    can_select_when_inactive: bool,
+    render_in_world_view: bool,
+    px_offset_x: i32,
+    px_offset_y: i32,
+    parallax_factor_x: f64,
+    parallax_factor_y: f64,
+    parallax_scaling: bool,
+    required_tags: Vec<String>,
+    excluded_tags: Vec<String>,
+    auto_tiles_killed_by_other_layer_uid: Option<u32>,
+    ui_filter_tags: Vec<String>,
+    use_async_render: bool,
+    int_grid_values: Vec<IntGridValue>,
+    int_grid_values_groups: Vec<()>,
+    auto_rule_groups: Vec<()>,
+    auto_source_layer_def_uid: Option<u32>,
+    tileset_def_uid: Option<u32>,
+    tile_pivot_x: f64,
+    tile_pivot_y: f64,
+    biome_field_uid: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct IntGridValue {
+    value: u8,
+    identifier: &'static str,
+    color: &'static str,
+    tile: Option<()>,
+    group_uid: u32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct EntityDef {
+    identifier: &'static str,
+    uid: u32,
+    tags: Vec<String>,
+    export_to_toc: bool,
+    allow_out_of_bounds: bool,
+    doc: Option<String>,
+    width: u32,
+    height: u32,
+    resizable_x: bool,
+    resizable_y: bool,
+    min_width: Option<u32>,
+    max_width: Option<u32>,
+    min_height: Option<u32>,
+    max_height: Option<u32>,
+    keep_aspect_ratio: bool,
+    tile_opacity: f64,
+    fill_opacity: f64,
+    line_opacity: f64,
+    hollow: bool,
+    color: &'static str,
+    render_mode: &'static str,
+    show_name: bool,
+    tileset_id: Option<u32>,
+    tile_render_mode: &'static str,
+    tile_rect: Option<()>,
+    ui_tile_rect: Option<()>,
+    nine_slice_borders: Vec<i32>,
+    max_count: u32,
+    limit_scope: &'static str,
+    limit_behavior: &'static str,
+    pivot_x: f64,
+    pivot_y: f64,
+    field_defs: Vec<()>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Level {
+    identifier: &'static str,
+    iid: String,
+    uid: u32,
+    world_x: i32,
+    world_y: i32,
+    world_depth: i32,
+    px_wid: u32,
+    px_hei: u32,
+    #[serde(rename = "__bgColor")]
+    computed_bg_color: &'static str,
+    bg_color: Option<String>,
+    use_auto_identifier: bool,
+    bg_rel_path: Option<String>,
+    bg_pos: Option<String>,
+    bg_pivot_x: f64,
+    bg_pivot_y: f64,
+    #[serde(rename = "__smartColor")]
+    smart_color: &'static str,
+    #[serde(rename = "__bgPos")]
+    computed_bg_pos: Option<()>,
+    external_rel_path: Option<String>,
+    field_instances: Vec<()>,
+    layer_instances: Vec<LayerInstance>,
+    #[serde(rename = "__neighbours")]
+    neighbours: Vec<()>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct LayerInstance {
+    #[serde(rename = "__identifier")]
+    identifier: &'static str,
+    #[serde(rename = "__type")]
+    layer_type: &'static str,
+    #[serde(rename = "__cWid")]
+    c_wid: u32,
+    #[serde(rename = "__cHei")]
+    c_hei: u32,
+    #[serde(rename = "__gridSize")]
+    grid_size: u32,
+    #[serde(rename = "__opacity")]
+    opacity: f64,
+    #[serde(rename = "__pxTotalOffsetX")]
+    px_total_offset_x: i32,
+    #[serde(rename = "__pxTotalOffsetY")]
+    px_total_offset_y: i32,
+    #[serde(rename = "__tilesetDefUid")]
+    tileset_def_uid: Option<u32>,
+    #[serde(rename = "__tilesetRelPath")]
+    tileset_rel_path: Option<String>,
+    iid: String,
+    level_id: u32,
+    layer_def_uid: u32,
+    px_offset_x: i32,
+    px_offset_y: i32,
+    visible: bool,
+    optional_rules: Vec<()>,
+    int_grid_csv: Vec<u8>,
+    auto_layer_tiles: Vec<()>,
+    seed: u32,
+    override_tileset_uid: Option<u32>,
+    grid_tiles: Vec<()>,
+    entity_instances: Vec<EntityInstance>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct EntityInstance {
+    #[serde(rename = "__identifier")]
+    identifier: &'static str,
+    #[serde(rename = "__grid")]
+    grid: [u32; 2],
+    #[serde(rename = "__pivot")]
+    pivot: [f64; 2],
+    #[serde(rename = "__tags")]
+    tags: Vec<String>,
+    #[serde(rename = "__tile")]
+    tile: Option<()>,
+    #[serde(rename = "__smartColor")]
+    smart_color: &'static str,
+    #[serde(rename = "__worldX")]
+    world_x: u32,
+    #[serde(rename = "__worldY")]
+    world_y: u32,
+    iid: String,
+    width: u32,
+    height: u32,
+    def_uid: u32,
+    px: [u32; 2],
+    field_instances: Vec<()>,
+}
+
+/// Iids in creation order.
+struct Iids(u64);
+
+impl Iids {
+    fn next(&mut self) -> String {
+        let iid = format!("00000000-0000-4000-8000-{:012x}", self.0);
+        self.0 += 1;
+        iid
+    }
+}
+
+impl Lvl {
+    /// The level as an LDtk project with `grid_size` pixel cells.
+    pub fn to_ldtk(&self, grid_size: u32) -> LdtkProject {
+        let mut iids = Iids(0);
+        let project_iid = iids.next();
+        let dummy_world_iid = iids.next();
+        let level_iid = iids.next();
+
+        let entity_instances = [(self.start, ENTITIES[0]), (self.end, ENTITIES[1])]
+            .into_iter()
+            .chain(self.checkpoints.iter().map(|point| (*point, ENTITIES[2])))
+            .chain(
+                self.optional_checkpoints
+                    .iter()
+                    .map(|point| (*point, ENTITIES[3])),
+            )
+            .map(|(point, (identifier, def_uid, color))| {
+                let px = [point.x * grid_size, point.y * grid_size];
+                EntityInstance {
+                    identifier,
+                    grid: [point.x, point.y],
+                    pivot: [0.0, 0.0],
+                    tags: Vec::new(),
+                    tile: None,
+                    smart_color: color,
+                    world_x: px[0],
+                    world_y: px[1],
+                    iid: iids.next(),
+                    width: grid_size,
+                    height: grid_size,
+                    def_uid,
+                    px,
+                    field_instances: Vec::new(),
+                }
+            })
+            .collect();
+
+        let grid = Grid::from_lvl(self);
+        let int_grid_csv = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| match grid.get(x, y) {
+                SquareType::Wall => 1,
+                SquareType::DynamicWall(_) => 2,
+                _ => 0,
+            })
+            .collect();
+
+        let layer_instance = |identifier, layer_type, layer_def_uid, iid| LayerInstance {
+            identifier,
+            layer_type,
+            c_wid: self.width,
+            c_hei: self.height,
+            grid_size,
+            opacity: 1.0,
+            px_total_offset_x: 0,
+            px_total_offset_y: 0,
+            tileset_def_uid: None,
+            tileset_rel_path: None,
+            iid,
+            level_id: LEVEL_UID,
+            layer_def_uid,
+            px_offset_x: 0,
+            px_offset_y: 0,
+            visible: true,
+            optional_rules: Vec::new(),
+            int_grid_csv: Vec::new(),
+            auto_layer_tiles: Vec::new(),
+            seed: 0,
+            override_tileset_uid: None,
+            grid_tiles: Vec::new(),
+            entity_instances: Vec::new(),
+        };
+        // Listed from the top layer down
+        let layer_instances = vec![
+            LayerInstance {
+                entity_instances,
+                ..layer_instance("Entities", "Entities", ENTITIES_LAYER_UID, iids.next())
+            },
+            LayerInstance {
+                int_grid_csv,
+                ..layer_instance("Walls", "IntGrid", WALLS_LAYER_UID, iids.next())
+            },
+        ];
+
+        let layer_def = |identifier, kind, uid, int_grid_values| LayerDef {
+            layer_type: kind,
+            identifier,
+            kind,
+            uid,
+            doc: None,
+            ui_color: None,
+            grid_size,
+            guide_grid_wid: 0,
+            guide_grid_hei: 0,
+            display_opacity: 1.0,
+            inactive_opacity: 1.0,
+            hide_in_list: false,
+            hide_fields_when_inactive: true,
+            can_select_when_inactive: true,
+            render_in_world_view: true,
+            px_offset_x: 0,
+            px_offset_y: 0,
+            parallax_factor_x: 0.0,
+            parallax_factor_y: 0.0,
+            parallax_scaling: true,
+            required_tags: Vec::new(),
+            excluded_tags: Vec::new(),
+            auto_tiles_killed_by_other_layer_uid: None,
+            ui_filter_tags: Vec::new(),
+            use_async_render: false,
+            int_grid_values,
+            int_grid_values_groups: Vec::new(),
+            auto_rule_groups: Vec::new(),
+            auto_source_layer_def_uid: None,
+            tileset_def_uid: None,
+            tile_pivot_x: 0.0,
+            tile_pivot_y: 0.0,
+            biome_field_uid: None,
+        };
+        let int_grid_value = |value, identifier, color| IntGridValue {
+            value,
+            identifier,
+            color,
+            tile: None,
+            group_uid: 0,
+        };
+        let entities = ENTITIES
+            .into_iter()
+            .map(|(identifier, uid, color)| EntityDef {
+                identifier,
+                uid,
+                tags: Vec::new(),
+                export_to_toc: false,
+                allow_out_of_bounds: false,
+                doc: None,
+                width: grid_size,
+                height: grid_size,
+                resizable_x: false,
+                resizable_y: false,
+                min_width: None,
+                max_width: None,
+                min_height: None,
+                max_height: None,
+                keep_aspect_ratio: false,
+                tile_opacity: 1.0,
+                fill_opacity: 1.0,
+                line_opacity: 1.0,
+                hollow: false,
+                color,
+                render_mode: "Rectangle",
+                show_name: true,
+                tileset_id: None,
+                tile_render_mode: "FitInside",
+                tile_rect: None,
+                ui_tile_rect: None,
+                nine_slice_borders: Vec::new(),
+                // The start and end are unique, checkpoints aren't
+                max_count: u32::from(matches!(identifier, "Start" | "End")),
+                limit_scope: "PerLevel",
+                limit_behavior: "MoveLastOne",
+                pivot_x: 0.0,
+                pivot_y: 0.0,
+                field_defs: Vec::new(),
+            })
+            .collect();
+
+        let px_wid = self.width * grid_size;
+        let px_hei = self.height * grid_size;
+        LdtkProject {
+            header: Header {
+                file_type: "LDtk Project JSON",
+                app: "LDtk",
+                doc: "https://ldtk.io/json",
+                schema: "https://ldtk.io/files/JSON_SCHEMA.json",
+                app_author: "Sebastien 'deepnight' Benard",
+                app_version: APP_VERSION,
+                url: "https://ldtk.io",
+            },
+            iid: project_iid,
+            json_version: APP_VERSION,
+            app_build_id: 473_703.0,
+            next_uid: NEXT_UID,
+            identifier_style: "Capitalize",
+            toc: Vec::new(),
+            world_layout: "Free",
+            world_grid_width: 256,
+            world_grid_height: 256,
+            default_level_width: px_wid,
+            default_level_height: px_hei,
+            default_pivot_x: 0.0,
+            default_pivot_y: 0.0,
+            default_grid_size: grid_size,
+            default_entity_width: grid_size,
+            default_entity_height: grid_size,
+            bg_color: "#40465B",
+            default_level_bg_color: BG_COLOR,
+            minify_json: false,
+            external_levels: false,
+            export_tiled: false,
+            simplified_export: false,
+            image_export_mode: "None",
+            export_level_bg: true,
+            png_file_pattern: None,
+            backup_on_save: false,
+            backup_limit: 10,
+            backup_rel_path: None,
+            level_name_pattern: "Level_%idx",
+            tutorial_desc: None,
+            custom_commands: Vec::new(),
+            flags: Vec::new(),
+            defs: Definitions {
+                layers: vec![
+                    layer_def("Entities", "Entities", ENTITIES_LAYER_UID, Vec::new()),
+                    layer_def(
+                        "Walls",
+                        "IntGrid",
+                        WALLS_LAYER_UID,
+                        vec![
+                            int_grid_value(1, "Wall", "#000000"),
+                            int_grid_value(2, "DynamicWall", "#404040"),
+                        ],
+                    ),
+                ],
+                entities,
+                tilesets: Vec::new(),
+                enums: Vec::new(),
+                external_enums: Vec::new(),
+                level_fields: Vec::new(),
+            },
+            levels: vec![Level {
+                identifier: "Level_0",
+                iid: level_iid,
+                uid: LEVEL_UID,
+                world_x: 0,
+                world_y: 0,
+                world_depth: 0,
+                px_wid,
+                px_hei,
+                computed_bg_color: BG_COLOR,
+                bg_color: None,
+                use_auto_identifier: true,
+                bg_rel_path: None,
+                bg_pos: None,
+                bg_pivot_x: 0.5,
+                bg_pivot_y: 0.5,
+                smart_color: "#ADADB5",
+                computed_bg_pos: None,
+                external_rel_path: None,
+                field_instances: Vec::new(),
+                layer_instances,
+                neighbours: Vec::new(),
+            }],
+            worlds: Vec::new(),
+            dummy_world_iid,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Levels made of walls and special squares, scanned from images.
+//!
+//! The `core` feature only builds the level types and their pure logic,
+//! [`SquareType`], [`Point`], [`Wall`], [`Lvl`] and [`LvlBuilder`] among
+//! them. They're `no_std` and only need `alloc`, so game runtimes can load
+//! levels without the image stack. The default `std` feature adds the
+//! parsers, scanning, analyses and the command line tool. The `bevy`
+//! feature loads levels as Bevy assets, see `src/asset.rs`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod analysis;
+#[cfg(feature = "std")]
+mod ascii;
+#[cfg(feature = "bevy")]
+mod asset;
+#[cfg(feature = "core")]
+mod binary;
+#[cfg(feature = "std")]
+mod bitset;
+#[cfg(feature = "core")]
+mod builder;
+#[cfg(feature = "std")]
+mod chunk;
+#[cfg(feature = "std")]
+mod collider;
+#[cfg(feature = "std")]
+mod contour;
+#[cfg(feature = "std")]
+mod csv;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "core")]
+mod edges;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "std")]
+mod godot;
+#[cfg(feature = "std")]
+mod graph;
+#[cfg(feature = "std")]
+mod grid;
+#[cfg(feature = "std")]
+mod hex;
+#[cfg(feature = "std")]
+mod index;
+#[cfg(feature = "std")]
+mod ldtk;
+#[cfg(feature = "core")]
+mod lvl;
+#[cfg(feature = "std")]
+mod marching;
+#[cfg(feature = "std")]
+mod mesh;
+#[cfg(feature = "core")]
+mod polygon;
+#[cfg(feature = "std")]
+mod quadtree;
+#[cfg(feature = "std")]
+mod scan;
+#[cfg(feature = "core")]
+mod square;
+#[cfg(feature = "std")]
+mod svg;
+#[cfg(feature = "std")]
+mod tmx;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm;
+
+#[cfg(feature = "std")]
+pub use analysis::{BorderGap, Junction, JunctionKind, Room, WallOverlap};
+#[cfg(feature = "std")]
+pub use ascii::{parse_ascii, AsciiError};
+#[cfg(feature = "bevy")]
+pub use asset::{LvlLoader, LvlLoaderError, LvlPlugin};
+#[cfg(feature = "core")]
+pub use binary::{BinError, BIN_MAGIC, BIN_VERSION};
+#[cfg(feature = "std")]
+pub use bitset::{parse_occupancy, BitOrder, OccupancyError};
+#[cfg(feature = "core")]
+pub use builder::{LvlBuilder, ValidationError, ValidationWarning};
+#[cfg(feature = "std")]
+pub use chunk::Chunk;
+#[cfg(feature = "std")]
+pub use collider::Collider;
+#[cfg(feature = "std")]
+pub use contour::contours;
+#[cfg(feature = "std")]
+pub use csv::{parse_csv, CsvError};
+#[cfg(feature = "std")]
+pub use diff::{Change, LvlDiff, Size};
+#[cfg(feature = "std")]
+pub use edges::{scan_edges, EdgeModelError};
+#[cfg(feature = "core")]
+pub use edges::{EdgeWall, Side};
+#[cfg(feature = "ffi")]
+pub use ffi::{
+    maze_lvl_free_string, maze_lvl_last_error, maze_lvl_parse_image, MAZE_LVL_ERR_BUFFER_TOO_SMALL,
+    MAZE_LVL_ERR_IMAGE, MAZE_LVL_ERR_NULL, MAZE_LVL_ERR_PANIC, MAZE_LVL_ERR_PATH, MAZE_LVL_OK,
+};
+#[cfg(feature = "std")]
+pub use graph::{CorridorGraph, GraphEdge, GraphNode, NodeKind};
+#[cfg(feature = "std")]
+pub use grid::{Grid, SizeMismatch, UnknownColor};
+#[cfg(feature = "std")]
+pub use hex::{scan_hex, Axial, HexAxis, HexLayout, HexLvl, HexWall};
+#[cfg(feature = "std")]
+pub use index::WallIndex;
+#[cfg(feature = "std")]
+pub use ldtk::LdtkProject;
+#[cfg(feature = "core")]
+pub use lvl::{
+    DynamicWall, Lvl, Orientation, OrientationFirst, Point, Rect, Wall, WallSortKey, Wrap,
+    FORMAT_VERSION,
+};
+#[cfg(feature = "std")]
+pub use marching::marching_squares;
+#[cfg(feature = "std")]
+pub use mesh::{greedy_rects, polylines, Mesh};
+#[cfg(feature = "core")]
+pub use polygon::{Polygon, Vertex};
+#[cfg(feature = "std")]
+pub use quadtree::{QuadNode, QuadTree};
+#[cfg(feature = "std")]
+pub use scan::{
+    scan_grid, scan_grid_with_progress, scan_image, MergeStrategy, ScanOptions, ScanPhase,
+    ScanProgress, PROGRESS_INTERVAL,
+};
+#[cfg(feature = "core")]
+pub use square::{ParseSquareError, SquareType};
+#[cfg(feature = "std")]
+pub use svg::{parse_svg, SvgError};
@@ -0,0 +1,1301 @@
+use alloc::{format, string::String, vec::Vec};
+use core::cmp::Reverse;
+
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use tracing::warn;
+
+use crate::{EdgeWall, Polygon, SquareType, ValidationError, ValidationWarning, Vertex};
+
+/// Square of the level, counted from the top left.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+pub struct Point {
+    /// Column, from the left
+    pub x: u32,
+    /// Row, from the top
+    pub y: u32,
+}
+
+impl Point {
+    pub const fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    /// Steps between the squares moving only along rows and columns.
+    pub fn manhattan_distance(self, other: Self) -> u64 {
+        u64::from(self.x.abs_diff(other.x)) + u64::from(self.y.abs_diff(other.y))
+    }
+}
+
+/// Direction a wall runs in, serialized next to its coordinates.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    /// Single square without an `end`
+    Point,
+}
+
+/// Edges of a level that connect to the opposite edge, like the tunnels of
+/// Pac-Man.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+#[serde(rename_all = "lowercase")]
+pub enum Wrap {
+    #[default]
+    #[cfg_attr(feature = "std", value(skip))]
+    None,
+    /// Left and right edge
+    X,
+    /// Top and bottom edge
+    Y,
+    Both,
+}
+
+impl Wrap {
+    pub const fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+
+    pub const fn wraps_x(self) -> bool {
+        matches!(self, Self::X | Self::Both)
+    }
+
+    pub const fn wraps_y(self) -> bool {
+        matches!(self, Self::Y | Self::Both)
+    }
+}
+
+/// Straight run of squares from `start` to `end`.
+///
+/// Walls are horizontal or vertical unless the level was scanned with
+/// diagonals, then `start` and `end` can also differ by the same amount in
+/// both coordinates. Diagonal walls start at their top square and run down
+/// to the right or down to the left.
+///
+/// In levels that [`Wrap`], a wall can run across the seam, see
+/// [`Wall::wraps`]. [`Wall::length`], [`Wall::squares`] and
+/// [`Wall::bounding_box`] don't know the level size and only describe walls
+/// that don't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+pub struct Wall {
+    pub start: Point,
+    pub end: Option<Point>,
+}
+
+impl Wall {
+    /// Wall covering the squares from `start` to `end`.
+    pub(crate) fn between(start: Point, end: Point) -> Self {
+        Self {
+            start,
+            end: (start != end).then_some(end),
+        }
+    }
+
+    /// The wall with its ends in reading order, `start` on the upper row or
+    /// left of `end` on the same row, which is how the scanner writes walls.
+    /// A diagonal down to the left still has `start.x` greater than `end.x`.
+    ///
+    /// Walls across the seam of a wrapping level have reversed ends on
+    /// purpose, see [`Wall::wraps`], and normalizing them gives the wall
+    /// between the ends instead.
+    ///
+    /// ```
+    /// use maze_lvl_maker::{Point, Wall};
+    ///
+    /// let wall = Wall {
+    ///     start: Point::new(5, 2),
+    ///     end: Some(Point::new(1, 2)),
+    /// };
+    /// assert_eq!(wall.normalize().start, Point::new(1, 2));
+    /// ```
+    #[must_use]
+    pub fn normalize(self) -> Self {
+        match self.end {
+            Some(end) if (end.y, end.x) < (self.start.y, self.start.x) => Self {
+                start: end,
+                end: Some(self.start),
+            },
+            _ => self,
+        }
+    }
+
+    /// Number of squares covered by the wall.
+    pub fn length(self) -> u32 {
+        let end = self.end.unwrap_or(self.start);
+        self.start
+            .x
+            .abs_diff(end.x)
+            .max(self.start.y.abs_diff(end.y))
+            + 1
+    }
+
+    /// Part of the wall inside `rect`, `None` when none of it is.
+    pub fn clip(self, rect: Rect) -> Option<Self> {
+        let mut inside = self
+            .squares()
+            .filter(|point| rect.contains(point.x, point.y));
+        let start = inside.next()?;
+        Some(Self::between(start, inside.last().unwrap_or(start)))
+    }
+
+    /// Top left and bottom right square of the smallest rectangle around the
+    /// wall.
+    ///
+    /// ```
+    /// use maze_lvl_maker::{Point, Wall};
+    ///
+    /// let wall = Wall {
+    ///     start: Point::new(4, 1),
+    ///     end: Some(Point::new(2, 3)),
+    /// };
+    /// assert_eq!(wall.bounding_box(), (Point::new(2, 1), Point::new(4, 3)));
+    /// ```
+    pub fn bounding_box(self) -> (Point, Point) {
+        let end = self.end.unwrap_or(self.start);
+        (
+            Point::new(self.start.x.min(end.x), self.start.y.min(end.y)),
+            Point::new(self.start.x.max(end.x), self.start.y.max(end.y)),
+        )
+    }
+
+    /// Number of squares in the [`bounding_box`](Wall::bounding_box), the
+    /// same as [`length`](Wall::length) unless the wall is diagonal.
+    ///
+    /// ```
+    /// use maze_lvl_maker::{Point, Wall};
+    ///
+    /// let horizontal = Wall {
+    ///     start: Point::new(1, 2),
+    ///     end: Some(Point::new(5, 2)),
+    /// };
+    /// assert_eq!(horizontal.area(), 5);
+    ///
+    /// let diagonal = Wall {
+    ///     start: Point::new(0, 0),
+    ///     end: Some(Point::new(2, 2)),
+    /// };
+    /// assert_eq!(diagonal.area(), 9);
+    /// ```
+    pub fn area(self) -> u32 {
+        let (top_left, bottom_right) = self.bounding_box();
+        (bottom_right.x - top_left.x + 1) * (bottom_right.y - top_left.y + 1)
+    }
+
+    /// Flat `[x1, y1, x2, y2]` form, single squares end where they start.
+    pub fn to_array(self) -> [u32; 4] {
+        let end = self.end.unwrap_or(self.start);
+        [self.start.x, self.start.y, end.x, end.y]
+    }
+
+    pub fn orientation(self) -> Orientation {
+        match self.end {
+            None => Orientation::Point,
+            Some(_) if self.is_diagonal() => Orientation::Diagonal,
+            Some(end) if end.y == self.start.y => Orientation::Horizontal,
+            Some(_) => Orientation::Vertical,
+        }
+    }
+
+    /// Whether `start` and `end` differ in both coordinates.
+    pub fn is_diagonal(self) -> bool {
+        self.end
+            .is_some_and(|end| end.x != self.start.x && end.y != self.start.y)
+    }
+
+    /// Squares covered by the wall, from `start` to `end`.
+    pub fn squares(self) -> impl Iterator<Item = Point> {
+        let end = self.end.unwrap_or(self.start);
+        let step = |from: u32, to: u32| i64::from(to.cmp(&from) as i8);
+        let (dx, dy) = (step(self.start.x, end.x), step(self.start.y, end.y));
+        let start = self.start;
+
+        (0..i64::from(self.length())).map(move |i| {
+            Point::new(
+                (i64::from(start.x) + dx * i) as u32,
+                (i64::from(start.y) + dy * i) as u32,
+            )
+        })
+    }
+
+    /// Whether the wall runs off the right edge and continues at the left
+    /// one, with `end.x` less than `start.x` on the same row, or off the
+    /// bottom edge with `end.y` less than `start.y` in the same column.
+    pub fn wraps(self) -> bool {
+        self.end.is_some_and(|end| {
+            (end.y == self.start.y && end.x < self.start.x)
+                || (end.x == self.start.x && end.y < self.start.y)
+        })
+    }
+
+    /// The wall as pieces that don't wrap in a `width` by `height` level,
+    /// the part up to the edge and the part from the opposite edge on.
+    #[cfg(feature = "std")]
+    pub(crate) fn split_at_seam(self, width: u32, height: u32) -> Vec<Self> {
+        if !self.wraps() {
+            return alloc::vec![self];
+        }
+
+        let end = self.end.unwrap_or(self.start);
+        let (to_edge, from_edge) = if end.y == self.start.y {
+            (
+                Point::new(width.saturating_sub(1), end.y),
+                Point::new(0, end.y),
+            )
+        } else {
+            (
+                Point::new(end.x, height.saturating_sub(1)),
+                Point::new(end.x, 0),
+            )
+        };
+        alloc::vec![
+            Self::between(self.start, to_edge),
+            Self::between(from_edge, end),
+        ]
+    }
+
+    /// Whether the wall covers the square at `(x, y)`.
+    pub fn contains(self, x: u32, y: u32) -> bool {
+        let end = self.end.unwrap_or(self.start);
+        if self.wraps() {
+            return if end.y == self.start.y {
+                y == end.y && (x >= self.start.x || x <= end.x)
+            } else {
+                x == end.x && (y >= self.start.y || y <= end.y)
+            };
+        }
+
+        let in_range =
+            |value: u32, from: u32, to: u32| from.min(to) <= value && value <= from.max(to);
+        if !in_range(x, self.start.x, end.x) || !in_range(y, self.start.y, end.y) {
+            return false;
+        }
+
+        !self.is_diagonal() || self.start.x.abs_diff(x) == self.start.y.abs_diff(y)
+    }
+}
+
+impl Serialize for Wall {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut wall = serializer.serialize_struct("Wall", 3)?;
+        wall.serialize_field("start", &self.start)?;
+        wall.serialize_field("end", &self.end)?;
+        wall.serialize_field("orientation", &self.orientation())?;
+        wall.end()
+    }
+}
+
+/// Accepts both the `{"start", "end"}` objects and the flat arrays written
+/// with compact walls. Single squares can have a null `end`, leave it out or
+/// repeat `start` in it, which is how TOML writes them since it has no null.
+/// All three read back as a wall without `end`.
+impl<'de> Deserialize<'de> for Wall {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Shape {
+            Flat([u32; 4]),
+            Object { start: Point, end: Option<Point> },
+        }
+
+        Ok(match Shape::deserialize(deserializer)? {
+            Shape::Flat([x1, y1, x2, y2]) => Self::between(Point::new(x1, y1), Point::new(x2, y2)),
+            Shape::Object { start, end } => Self::between(start, end.unwrap_or(start)),
+        })
+    }
+}
+
+/// Schema of the objects written by [`Serialize`], the flat arrays of
+/// compact walls are only read.
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Wall {
+    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+        "Wall".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Fields {
+            /// First square of the run, the top one of diagonals
+            start: Point,
+            /// Last square of the run, `null` for single squares
+            end: Option<Point>,
+            /// Direction from `start` to `end`
+            orientation: Orientation,
+        }
+
+        Fields::json_schema(generator)
+    }
+}
+
+/// Walls are ordered by length, ties are broken by the start, then by
+/// [`Orientation`] in declaration order and finally by the end, so different
+/// walls never compare as equal.
+impl Ord for Wall {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        let self_length = self.length();
+        let other_length = other.length();
+
+        self_length
+            .cmp(&other_length)
+            .then(self.start.y.cmp(&other.start.y))
+            .then(self.start.x.cmp(&other.start.x))
+            .then_with(|| self.orientation().cmp(&other.orientation()))
+            .then_with(|| {
+                let self_end = self.end.map(|end| (end.y, end.x));
+                let other_end = other.end.map(|end| (end.y, end.x));
+                self_end.cmp(&other_end)
+            })
+    }
+}
+
+impl PartialOrd for Wall {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Which of horizontal and vertical walls come first in
+/// [`WallSortKey::Orientation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrientationFirst {
+    Horizontal,
+    Vertical,
+}
+
+/// Order for [`Lvl::sort_walls`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallSortKey {
+    /// Longest first, the order walls are scanned in
+    Length,
+    /// Reading order of the start, top to bottom and then left to right
+    Position,
+    /// Horizontal and vertical walls grouped, then diagonal walls and single
+    /// squares, the order inside a group is kept
+    Orientation(OrientationFirst),
+}
+
+impl WallSortKey {
+    fn sort<T>(self, walls: &mut [T], wall: impl Fn(&T) -> Wall) {
+        match self {
+            Self::Length => walls.sort_by_key(|item| Reverse(wall(item))),
+            Self::Position => walls.sort_by_key(|item| {
+                let wall = wall(item);
+                (wall.start.y, wall.start.x, wall)
+            }),
+            Self::Orientation(first) => {
+                walls.sort_by_key(|item| match (wall(item).orientation(), first) {
+                    (Orientation::Horizontal, OrientationFirst::Horizontal)
+                    | (Orientation::Vertical, OrientationFirst::Vertical) => 0,
+                    (Orientation::Horizontal | Orientation::Vertical, _) => 1,
+                    (Orientation::Diagonal, _) => 2,
+                    (Orientation::Point, _) => 3,
+                })
+            }
+        }
+    }
+}
+
+/// Axis aligned rectangle of squares.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+pub struct Rect {
+    /// Column of the left squares
+    pub x: u32,
+    /// Row of the top squares
+    pub y: u32,
+    /// Number of columns
+    pub width: u32,
+    /// Number of rows
+    pub height: u32,
+}
+
+impl Rect {
+    pub const fn contains(&self, x: u32, y: u32) -> bool {
+        self.x <= x && x - self.x < self.width && self.y <= y && y - self.y < self.height
+    }
+
+    /// Squares in both rectangles, `None` when they don't overlap.
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        // Rectangles reaching past `u32::MAX` are cut off there
+        let right = self
+            .x
+            .saturating_add(self.width)
+            .min(other.x.saturating_add(other.width));
+        let bottom = self
+            .y
+            .saturating_add(self.height)
+            .min(other.y.saturating_add(other.height));
+
+        (x < right && y < bottom).then(|| Self {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        })
+    }
+}
+
+/// Wall that toggles on a timer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+pub struct DynamicWall {
+    #[serde(flatten)]
+    pub wall: Wall,
+    /// Number of ticks between toggles
+    pub period: u8,
+}
+
+/// Version of the serialized [`Lvl`], bumped whenever a field is added,
+/// removed or written differently.
+///
+/// - 1: files without a `format_version`, walls have no `orientation`
+/// - 2: `format_version` and an `orientation` for every wall
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Versionless files were written before the version was introduced.
+const fn legacy_format_version() -> u32 {
+    1
+}
+
+/// Read the version, refusing ones newer than [`FORMAT_VERSION`] since their
+/// fields can't be trusted to mean the same.
+fn deserialize_format_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(serde::de::Error::custom(format_args!(
+            "format_version {version} isn't supported, only 1 to {FORMAT_VERSION} are"
+        )));
+    }
+    Ok(version)
+}
+
+/// Level of `width` by `height` squares, as written to json.
+///
+/// Reads back everything the command line tool writes, lists that are left
+/// out are empty.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect, bevy::asset::Asset))]
+pub struct Lvl {
+    /// Shape the level was written in, see [`FORMAT_VERSION`]. Levels made
+    /// by this build are at the current version, [`Lvl::upgrade`] brings
+    /// older ones up to it
+    #[serde(
+        default = "legacy_format_version",
+        deserialize_with = "deserialize_format_version"
+    )]
+    pub format_version: u32,
+    /// Number of columns
+    pub width: u32,
+    /// Number of rows
+    pub height: u32,
+    /// Edges that connect to the opposite one, see [`Lvl::set_wrap`]
+    #[serde(default, skip_serializing_if = "Wrap::is_none")]
+    pub wrap: Wrap,
+    /// Merged runs of wall squares
+    #[serde(default)]
+    pub walls: Vec<Wall>,
+    /// Walls that toggle on a timer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dynamic_walls: Vec<DynamicWall>,
+    /// Wall squares as rectangles, used instead of `walls` by `Mesh::Rect`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rects: Vec<Rect>,
+    /// Wall squares as polylines, used instead of `walls` by `Mesh::Polyline`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wall_paths: Vec<Vec<Point>>,
+    /// Wall areas as polygons, used instead of `walls` by `Mesh::Contour`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collision: Vec<Polygon>,
+    /// Walls between cells, used instead of `walls` by
+    /// [`scan_edges`](crate::scan_edges). The grid based analyses don't see
+    /// them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edge_walls: Vec<EdgeWall>,
+    /// Where the player starts
+    pub start: Point,
+    /// Square to reach after every checkpoint
+    pub end: Point,
+    /// Squares the player has to visit before the end, in reading order
+    pub checkpoints: Vec<Point>,
+    /// Squares the player can skip, in reading order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub optional_checkpoints: Vec<Point>,
+}
+
+impl Lvl {
+    /// Mark the level as written at [`FORMAT_VERSION`]. Older files read into
+    /// the current fields already: walls get their `orientation` from the
+    /// coordinates and single squares end up without an `end`.
+    pub fn upgrade(&mut self) {
+        self.format_version = FORMAT_VERSION;
+    }
+
+    /// Move everything in the level by `(dx, dy)` squares, for levels that
+    /// were cut out of a bigger image.
+    pub fn offset(&mut self, dx: u32, dy: u32) {
+        self.translate(i64::from(dx), i64::from(dy));
+    }
+
+    /// Copy of the level with `other` moved by `offset` and added to it,
+    /// growing the size to fit both.
+    ///
+    /// Checkpoints on the same square are only kept once. Each level has a
+    /// start and an end, so the ones of `self` win and a warning is logged
+    /// when `other` puts them somewhere else.
+    pub fn merge(&self, other: &Self, offset: Point) -> Self {
+        let mut other = other.clone();
+        other.offset(offset.x, offset.y);
+
+        let mut lvl = self.clone();
+        lvl.width = lvl.width.max(other.width + offset.x);
+        lvl.height = lvl.height.max(other.height + offset.y);
+        if other.start != lvl.start {
+            warn!(
+                "Both levels have a start, keeping {:?} over {:?}",
+                lvl.start, other.start
+            );
+        }
+        if other.end != lvl.end {
+            warn!(
+                "Both levels have an end, keeping {:?} over {:?}",
+                lvl.end, other.end
+            );
+        }
+        lvl.absorb(other);
+
+        lvl
+    }
+
+    /// `nx` by `ny` copies of the level next to each other. Only the top
+    /// left copy keeps its start and end.
+    ///
+    /// # Panics
+    ///
+    /// When the copies are more than `u32::MAX` squares wide or high.
+    pub fn tile(&self, nx: u32, ny: u32) -> Self {
+        let mut lvl = self.clone();
+        // Every offset below is smaller than these
+        lvl.width = self
+            .width
+            .checked_mul(nx)
+            .expect("the tiled level is too wide");
+        lvl.height = self
+            .height
+            .checked_mul(ny)
+            .expect("the tiled level is too high");
+        for y in 0..ny {
+            for x in 0..nx {
+                if (x, y) != (0, 0) {
+                    let mut copy = self.clone();
+                    copy.offset(x * self.width, y * self.height);
+                    lvl.absorb(copy);
+                }
+            }
+        }
+
+        lvl
+    }
+
+    /// Add the walls, meshes and checkpoints of `other`, checkpoints on the
+    /// same square are only kept once.
+    fn absorb(&mut self, mut other: Self) {
+        self.walls.append(&mut other.walls);
+        self.walls.sort_by_key(|wall| Reverse(*wall));
+        self.dynamic_walls.append(&mut other.dynamic_walls);
+        self.dynamic_walls
+            .sort_by_key(|dynamic_wall| Reverse(dynamic_wall.wall));
+        self.rects.append(&mut other.rects);
+        self.wall_paths.append(&mut other.wall_paths);
+        self.collision.append(&mut other.collision);
+        self.edge_walls.append(&mut other.edge_walls);
+        for checkpoint in other.checkpoints {
+            if !self.checkpoints.contains(&checkpoint) {
+                self.checkpoints.push(checkpoint);
+            }
+        }
+        for checkpoint in other.optional_checkpoints {
+            if !self.optional_checkpoints.contains(&checkpoint) {
+                self.optional_checkpoints.push(checkpoint);
+            }
+        }
+    }
+
+    /// Copy of the part of the level inside `rect`, moved so `(rect.x,
+    /// rect.y)` becomes `(0, 0)`. Walls are clipped to the rectangle, points
+    /// outside of it are dropped except for the start and end, which are
+    /// moved onto its nearest square. An empty `rect` gives a level without
+    /// walls or checkpoints, with the start and end at `(0, 0)`.
+    pub fn subregion(&self, rect: Rect) -> Self {
+        let empty = rect.width == 0 || rect.height == 0;
+        let clamp = |point: Point| {
+            if empty {
+                return Point::new(rect.x, rect.y);
+            }
+            Point::new(
+                point.x.clamp(rect.x, rect.x.saturating_add(rect.width - 1)),
+                point
+                    .y
+                    .clamp(rect.y, rect.y.saturating_add(rect.height - 1)),
+            )
+        };
+        let inside = |point: &Point| rect.contains(point.x, point.y);
+
+        let mut lvl = Self {
+            format_version: self.format_version,
+            width: rect.width,
+            height: rect.height,
+            wrap: Wrap::None,
+            walls: self
+                .walls
+                .iter()
+                .filter_map(|wall| wall.clip(rect))
+                .collect(),
+            dynamic_walls: self
+                .dynamic_walls
+                .iter()
+                .filter_map(|dynamic_wall| {
+                    Some(DynamicWall {
+                        wall: dynamic_wall.wall.clip(rect)?,
+                        period: dynamic_wall.period,
+                    })
+                })
+                .collect(),
+            rects: self
+                .rects
+                .iter()
+                .filter_map(|wall_rect| wall_rect.intersection(rect))
+                .collect(),
+            wall_paths: self
+                .wall_paths
+                .iter()
+                .flat_map(|path| clip_path(path, rect))
+                .collect(),
+            collision: self
+                .collision
+                .iter()
+                .filter_map(|polygon| polygon.clip(rect))
+                .collect(),
+            edge_walls: self
+                .edge_walls
+                .iter()
+                .copied()
+                .filter(|edge_wall| rect.contains(edge_wall.x, edge_wall.y))
+                .collect(),
+            start: clamp(self.start),
+            end: clamp(self.end),
+            checkpoints: self.checkpoints.iter().copied().filter(inside).collect(),
+            optional_checkpoints: self
+                .optional_checkpoints
+                .iter()
+                .copied()
+                .filter(inside)
+                .collect(),
+        };
+        lvl.translate(-i64::from(rect.x), -i64::from(rect.y));
+
+        lvl
+    }
+
+    /// Copy of the level moved so its bounding box starts at `(0, 0)`, with
+    /// `width` and `height` shrunk to fit the bounding box.
+    pub fn normalize(&self) -> Self {
+        let (mut min, mut max) = ((u32::MAX, u32::MAX), (0, 0));
+        let mut include = |x: u32, y: u32| {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        };
+
+        let walls = self.walls.iter().chain(
+            self.dynamic_walls
+                .iter()
+                .map(|dynamic_wall| &dynamic_wall.wall),
+        );
+        for wall in walls {
+            include(wall.start.x, wall.start.y);
+            if let Some(end) = wall.end {
+                include(end.x, end.y);
+            }
+        }
+        for rect in &self.rects {
+            include(rect.x, rect.y);
+            include(rect.x + rect.width - 1, rect.y + rect.height - 1);
+        }
+        for point in self.wall_paths.iter().flatten() {
+            include(point.x, point.y);
+        }
+        for edge_wall in &self.edge_walls {
+            include(edge_wall.x, edge_wall.y);
+        }
+        // Vertices are corners, the squares are the ones inside
+        for polygon in &self.collision {
+            let mut corners = (f64::INFINITY, f64::INFINITY, 0.0_f64, 0.0_f64);
+            for vertex in &polygon.outer {
+                corners = (
+                    corners.0.min(vertex.x),
+                    corners.1.min(vertex.y),
+                    corners.2.max(vertex.x),
+                    corners.3.max(vertex.y),
+                );
+            }
+            // Corners aren't negative, so `as` rounds down like `floor`,
+            // which like `ceil` needs std
+            let ceil = |value: f64| {
+                let down = value as u32;
+                down + u32::from(f64::from(down) < value)
+            };
+            if !polygon.outer.is_empty() {
+                include(corners.0 as u32, corners.1 as u32);
+                include(
+                    ceil(corners.2).saturating_sub(1),
+                    ceil(corners.3).saturating_sub(1),
+                );
+            }
+        }
+        let points = [self.start, self.end]
+            .into_iter()
+            .chain(self.checkpoints.iter().copied())
+            .chain(self.optional_checkpoints.iter().copied());
+        for point in points {
+            include(point.x, point.y);
+        }
+
+        let mut lvl = self.clone();
+        lvl.translate(-i64::from(min.0), -i64::from(min.1));
+        lvl.width = max.0 - min.0 + 1;
+        lvl.height = max.1 - min.1 + 1;
+        lvl
+    }
+
+    /// Copy of the level reflected left to right.
+    pub fn mirror_horizontal(&self) -> Self {
+        let (width, height) = (self.width, self.height);
+        self.remap(
+            (width, height),
+            |point| Point::new(width - 1 - point.x, point.y),
+            |corner| Vertex {
+                x: f64::from(width) - corner.x,
+                y: corner.y,
+            },
+            true,
+        )
+    }
+
+    /// Copy of the level reflected top to bottom.
+    pub fn mirror_vertical(&self) -> Self {
+        let (width, height) = (self.width, self.height);
+        self.remap(
+            (width, height),
+            |point| Point::new(point.x, height - 1 - point.y),
+            |corner| Vertex {
+                x: corner.x,
+                y: f64::from(height) - corner.y,
+            },
+            true,
+        )
+    }
+
+    /// Copy of the level turned a quarter clockwise, `width` and `height`
+    /// trade places.
+    pub fn rotate_90(&self) -> Self {
+        let height = self.height;
+        self.remap(
+            (self.height, self.width),
+            |point| Point::new(height - 1 - point.y, point.x),
+            |corner| Vertex {
+                x: f64::from(height) - corner.y,
+                y: corner.x,
+            },
+            false,
+        )
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        self.rotate_90().rotate_90()
+    }
+
+    pub fn rotate_270(&self) -> Self {
+        self.rotate_180().rotate_90()
+    }
+
+    /// Copy of the level with every square moved by `point` and every
+    /// polygon corner by `corner`. Reflections flip the winding, so
+    /// `reflects` reverses the rings to keep the wall on the left.
+    fn remap(
+        &self,
+        (width, height): (u32, u32),
+        point: impl Fn(Point) -> Point,
+        corner: impl Fn(Vertex) -> Vertex,
+        reflects: bool,
+    ) -> Self {
+        let move_point = |moved: &mut Point| *moved = point(*moved);
+        let move_wall = |wall: &mut Wall| {
+            let end = point(wall.end.unwrap_or(wall.start));
+            *wall = Wall::between(point(wall.start), end).normalize();
+        };
+
+        let mut lvl = self.clone();
+        lvl.width = width;
+        lvl.height = height;
+        lvl.walls.iter_mut().for_each(move_wall);
+        lvl.dynamic_walls
+            .iter_mut()
+            .for_each(|dynamic_wall| move_wall(&mut dynamic_wall.wall));
+        for rect in &mut lvl.rects {
+            let a = corner(Vertex::from(Point::new(rect.x, rect.y)));
+            let b = corner(Vertex::from(Point::new(
+                rect.x + rect.width,
+                rect.y + rect.height,
+            )));
+            *rect = Rect {
+                x: a.x.min(b.x) as u32,
+                y: a.y.min(b.y) as u32,
+                width: (a.x - b.x).abs() as u32,
+                height: (a.y - b.y).abs() as u32,
+            };
+        }
+        lvl.wall_paths.iter_mut().flatten().for_each(move_point);
+        for ring in lvl
+            .collision
+            .iter_mut()
+            .flat_map(|polygon| core::iter::once(&mut polygon.outer).chain(&mut polygon.holes))
+        {
+            for vertex in ring.iter_mut() {
+                *vertex = corner(*vertex);
+            }
+            if reflects {
+                ring.reverse();
+            }
+        }
+        move_point(&mut lvl.start);
+        move_point(&mut lvl.end);
+        lvl.checkpoints.iter_mut().for_each(move_point);
+        lvl.optional_checkpoints.iter_mut().for_each(move_point);
+        lvl
+    }
+
+    fn translate(&mut self, dx: i64, dy: i64) {
+        let shift = |point: &mut Point| {
+            point.x = (i64::from(point.x) + dx) as u32;
+            point.y = (i64::from(point.y) + dy) as u32;
+        };
+        let shift_wall = |wall: &mut Wall| {
+            shift(&mut wall.start);
+            if let Some(end) = &mut wall.end {
+                shift(end);
+            }
+        };
+
+        self.walls.iter_mut().for_each(shift_wall);
+        self.dynamic_walls
+            .iter_mut()
+            .for_each(|dynamic_wall| shift_wall(&mut dynamic_wall.wall));
+        for rect in &mut self.rects {
+            rect.x = (i64::from(rect.x) + dx) as u32;
+            rect.y = (i64::from(rect.y) + dy) as u32;
+        }
+        self.wall_paths.iter_mut().flatten().for_each(shift);
+        for polygon in &mut self.collision {
+            for vertex in core::iter::once(&mut polygon.outer)
+                .chain(&mut polygon.holes)
+                .flatten()
+            {
+                vertex.x += dx as f64;
+                vertex.y += dy as f64;
+            }
+        }
+        for edge_wall in &mut self.edge_walls {
+            edge_wall.x = (i64::from(edge_wall.x) + dx) as u32;
+            edge_wall.y = (i64::from(edge_wall.y) + dy) as u32;
+        }
+        shift(&mut self.start);
+        shift(&mut self.end);
+        self.checkpoints.iter_mut().for_each(shift);
+        self.optional_checkpoints.iter_mut().for_each(shift);
+    }
+
+    /// Connect the edges of `wrap` and join the walls that run up to both of
+    /// them into a single wall across the seam.
+    ///
+    /// The transforms don't know about joined walls, wrap after them.
+    pub fn set_wrap(&mut self, wrap: Wrap) {
+        self.wrap = wrap;
+        let size = (self.width, self.height);
+
+        let mut walls = self.walls.iter().map(|wall| (*wall, ())).collect();
+        join_across_seams(&mut walls, size, wrap);
+        self.walls = walls.into_iter().map(|(wall, ())| wall).collect();
+
+        let mut dynamic_walls = self
+            .dynamic_walls
+            .iter()
+            .map(|dynamic_wall| (dynamic_wall.wall, dynamic_wall.period))
+            .collect();
+        join_across_seams(&mut dynamic_walls, size, wrap);
+        self.dynamic_walls = dynamic_walls
+            .into_iter()
+            .map(|(wall, period)| DynamicWall { wall, period })
+            .collect();
+    }
+
+    /// Reorder the walls and the dynamic walls by `key`.
+    pub fn sort_walls(&mut self, key: WallSortKey) {
+        key.sort(&mut self.walls, |wall| *wall);
+        key.sort(&mut self.dynamic_walls, |dynamic_wall| dynamic_wall.wall);
+    }
+
+    /// Reorder the checkpoints by Manhattan distance from the start, closest
+    /// first. Ties keep their order.
+    pub fn sort_checkpoints_by_proximity_to_start(&mut self) {
+        let start = self.start;
+        self.checkpoints
+            .sort_by_key(|checkpoint| checkpoint.manhattan_distance(start));
+    }
+
+    /// Reorder the checkpoints by Manhattan distance from the end, closest
+    /// first. Ties keep their order.
+    pub fn sort_checkpoints_by_proximity_to_end(&mut self) {
+        let end = self.end;
+        self.checkpoints
+            .sort_by_key(|checkpoint| checkpoint.manhattan_distance(end));
+    }
+
+    /// Reorder the checkpoints in reading order, top to bottom and then left
+    /// to right.
+    pub fn sort_checkpoints_lexicographic(&mut self) {
+        self.checkpoints
+            .sort_by_key(|checkpoint| (checkpoint.y, checkpoint.x));
+    }
+
+    /// Put everything the scan leaves in no particular order into one that
+    /// only depends on the level, so small edits reorder little. Walls and
+    /// dynamic walls go by the row and column of their start, then their
+    /// orientation and length, checkpoints and optional checkpoints in
+    /// reading order.
+    pub fn canonicalize(&mut self) {
+        let key = |wall: Wall| {
+            (
+                wall.start.y,
+                wall.start.x,
+                wall.orientation(),
+                wall.length(),
+                wall,
+            )
+        };
+        self.walls.sort_by_key(|wall| key(*wall));
+        self.dynamic_walls
+            .sort_by_key(|dynamic_wall| (key(dynamic_wall.wall), dynamic_wall.period));
+        self.sort_checkpoints_lexicographic();
+        self.optional_checkpoints
+            .sort_by_key(|checkpoint| (checkpoint.y, checkpoint.x));
+    }
+
+    /// Every square as rows of [`Lvl::cell_at`], `height` rows of `width`
+    /// squares. Takes O(width × height) memory and as many lookups,
+    /// [`Grid::from_lvl`](crate::Grid::from_lvl) paints the walls instead,
+    /// which is faster on large levels.
+    pub fn to_grid(&self) -> Vec<Vec<SquareType>> {
+        (0..self.height)
+            .map(|y| (0..self.width).map(|x| self.cell_at(x, y)).collect())
+            .collect()
+    }
+
+    /// Rectangles covering the squares of the wall, split in two across
+    /// the seam of a wrapping level and into its squares when diagonal.
+    #[cfg(feature = "std")]
+    pub(crate) fn wall_rects(&self, wall: Wall) -> Vec<Rect> {
+        let square = |point: Point| Rect {
+            x: point.x,
+            y: point.y,
+            width: 1,
+            height: 1,
+        };
+        if wall.is_diagonal() {
+            return wall.squares().map(square).collect();
+        }
+
+        wall.split_at_seam(self.width, self.height)
+            .into_iter()
+            .map(|piece| {
+                let (top_left, bottom_right) = piece.bounding_box();
+                Rect {
+                    x: top_left.x,
+                    y: top_left.y,
+                    width: bottom_right.x - top_left.x + 1,
+                    height: bottom_right.y - top_left.y + 1,
+                }
+            })
+            .collect()
+    }
+
+    /// Warn about a start or end away from the outer edge, for games where
+    /// players enter and leave the level at its border.
+    pub fn validate_boundary_start_end(&self) -> Vec<ValidationWarning> {
+        let on_boundary = |point: Point| {
+            point.x == 0
+                || point.y == 0
+                || point.x.saturating_add(1) == self.width
+                || point.y.saturating_add(1) == self.height
+        };
+        let mut warnings = Vec::new();
+        if !on_boundary(self.start) {
+            warnings.push(ValidationWarning::StartNotOnBoundary(self.start));
+        }
+        if !on_boundary(self.end) {
+            warnings.push(ValidationWarning::EndNotOnBoundary(self.end));
+        }
+        warnings
+    }
+
+    /// Check that every point, wall, rectangle and wall path is inside the
+    /// `width` by `height` of the level, for levels that weren't built or
+    /// scanned, like ones read back from a file. The error names the first
+    /// field that isn't, like `checkpoints[2]`.
+    pub fn check_bounds(&self) -> Result<(), ValidationError> {
+        let check = |points: &[Point], field: &dyn Fn() -> String| match points
+            .iter()
+            .find(|point| point.x >= self.width || point.y >= self.height)
+        {
+            Some(point) => Err(ValidationError::FieldOutOfBounds {
+                field: field(),
+                point: *point,
+            }),
+            None => Ok(()),
+        };
+
+        check(&[self.start], &|| "start".into())?;
+        check(&[self.end], &|| "end".into())?;
+        for (index, point) in self.checkpoints.iter().enumerate() {
+            check(&[*point], &|| format!("checkpoints[{index}]"))?;
+        }
+        for (index, point) in self.optional_checkpoints.iter().enumerate() {
+            check(&[*point], &|| format!("optional_checkpoints[{index}]"))?;
+        }
+        for (index, wall) in self.walls.iter().enumerate() {
+            let ends = [wall.start, wall.end.unwrap_or(wall.start)];
+            check(&ends, &|| format!("walls[{index}]"))?;
+        }
+        for (index, dynamic_wall) in self.dynamic_walls.iter().enumerate() {
+            let wall = dynamic_wall.wall;
+            let ends = [wall.start, wall.end.unwrap_or(wall.start)];
+            check(&ends, &|| format!("dynamic_walls[{index}]"))?;
+        }
+        for (index, rect) in self.rects.iter().enumerate() {
+            let corner = Point::new(
+                (rect.x + rect.width).saturating_sub(1),
+                (rect.y + rect.height).saturating_sub(1),
+            );
+            check(&[corner], &|| format!("rects[{index}]"))?;
+        }
+        for (index, path) in self.wall_paths.iter().enumerate() {
+            check(path, &|| format!("wall_paths[{index}]"))?;
+        }
+        Ok(())
+    }
+
+    /// Start, end, checkpoints and optional checkpoints with the type of
+    /// their square, in that order.
+    ///
+    /// ```
+    /// use maze_lvl_maker::{LvlBuilder, Point, SquareType};
+    ///
+    /// let lvl = LvlBuilder::new(4, 4)
+    ///     .start(Point::new(0, 0))
+    ///     .end(Point::new(3, 3))
+    ///     .checkpoint(Point::new(2, 1))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut checkpoints = 0;
+    /// for (point, kind) in lvl.all_special_points() {
+    ///     assert_eq!(lvl.cell_at(point.x, point.y), kind);
+    ///     if kind == SquareType::Checkpoint {
+    ///         checkpoints += 1;
+    ///     }
+    /// }
+    /// assert_eq!(checkpoints, 1);
+    /// assert_eq!(lvl.special_points_count(), 3);
+    /// ```
+    pub fn all_special_points(&self) -> impl Iterator<Item = (Point, SquareType)> + '_ {
+        let kind = |kind| move |point: &Point| (*point, kind);
+        core::iter::once((self.start, SquareType::Start))
+            .chain(core::iter::once((self.end, SquareType::End)))
+            .chain(self.checkpoints.iter().map(kind(SquareType::Checkpoint)))
+            .chain(
+                self.optional_checkpoints
+                    .iter()
+                    .map(kind(SquareType::OptionalCheckpoint)),
+            )
+    }
+
+    /// Number of points [`Lvl::all_special_points`] yields.
+    pub fn special_points_count(&self) -> usize {
+        2 + self.checkpoints.len() + self.optional_checkpoints.len()
+    }
+
+    /// Type of the square at `(x, y)`, reconstructed from the walls and points.
+    pub fn cell_at(&self, x: u32, y: u32) -> SquareType {
+        let point = Point::new(x, y);
+        if point == self.start {
+            SquareType::Start
+        } else if point == self.end {
+            SquareType::End
+        } else if self.checkpoints.contains(&point) {
+            SquareType::Checkpoint
+        } else if self.optional_checkpoints.contains(&point) {
+            SquareType::OptionalCheckpoint
+        } else if check_if_point_is_wall(x, y, &self.walls)
+            || self.rects.iter().any(|rect| rect.contains(x, y))
+            || self
+                .wall_paths
+                .iter()
+                .flat_map(|path| path_walls(path))
+                .any(|wall| check_if_point_is_wall(x, y, &[wall]))
+            || self.collision.iter().any(|polygon| polygon.contains(x, y))
+        {
+            SquareType::Wall
+        } else if let Some(dynamic_wall) = self
+            .dynamic_walls
+            .iter()
+            .find(|dynamic_wall| check_if_point_is_wall(x, y, &[dynamic_wall.wall]))
+        {
+            SquareType::DynamicWall(dynamic_wall.period)
+        } else {
+            SquareType::Empty
+        }
+    }
+}
+
+/// Join a wall ending on the last square of a row or column with the wall
+/// with the same key starting on the first square of it. Rows are joined
+/// first, walls covering a whole row or column are left alone.
+fn join_across_seams<K: PartialEq>(
+    walls: &mut Vec<(Wall, K)>,
+    (width, height): (u32, u32),
+    wrap: Wrap,
+) {
+    // Rows in the same order as columns with their coordinates swapped
+    let axes = [
+        (wrap.wraps_x(), width, Orientation::Horizontal, false),
+        (wrap.wraps_y(), height, Orientation::Vertical, true),
+    ];
+    for (wraps, size, orientation, swapped) in axes {
+        if !wraps || size < 2 {
+            continue;
+        }
+        let along = |point: Point| {
+            if swapped {
+                (point.y, point.x)
+            } else {
+                (point.x, point.y)
+            }
+        };
+        // (line, first square, last square) of walls along the axis
+        let span = |wall: Wall| {
+            let joinable =
+                !wall.wraps() && [orientation, Orientation::Point].contains(&wall.orientation());
+            let (first, line) = along(wall.start);
+            let (last, _) = along(wall.end.unwrap_or(wall.start));
+            joinable.then_some((line, first, last))
+        };
+
+        let mut index = 0;
+        while index < walls.len() {
+            let Some((line, first, last)) = span(walls[index].0) else {
+                index += 1;
+                continue;
+            };
+            if first == 0 || last != size - 1 {
+                index += 1;
+                continue;
+            }
+
+            let head = walls.iter().position(|(wall, key)| {
+                *key == walls[index].1
+                    && span(*wall).is_some_and(|(other_line, other_first, other_last)| {
+                        other_line == line && other_first == 0 && other_last < size - 1
+                    })
+            });
+            if let Some(head) = head {
+                let (head_wall, _) = walls.remove(head);
+                if head < index {
+                    index -= 1;
+                }
+                walls[index].0.end = Some(head_wall.end.unwrap_or(head_wall.start));
+            }
+            index += 1;
+        }
+    }
+}
+
+/// Pieces of a polyline inside `rect`, a path that leaves and comes back
+/// is split in two.
+pub(crate) fn clip_path(path: &[Point], rect: Rect) -> Vec<Vec<Point>> {
+    let squares = path.first().copied().into_iter().chain(
+        path.windows(2)
+            .flat_map(|pair| Wall::between(pair[0], pair[1]).squares().skip(1)),
+    );
+
+    let mut pieces: Vec<Vec<Point>> = Vec::new();
+    let mut was_inside = false;
+    for square in squares {
+        let inside = rect.contains(square.x, square.y);
+        if inside {
+            if !was_inside {
+                pieces.push(Vec::new());
+            }
+            let piece = pieces.last_mut().expect("piece was just started");
+            // Only keep the corners of the polyline
+            if let [.., before, last] = piece[..] {
+                let step = |a: Point, b: Point| {
+                    (
+                        i64::from(b.x) - i64::from(a.x),
+                        i64::from(b.y) - i64::from(a.y),
+                    )
+                };
+                if step(before, last) == step(last, square) {
+                    piece.pop();
+                }
+            }
+            piece.push(square);
+        }
+        was_inside = inside;
+    }
+
+    pieces
+}
+
+#[inline]
+pub(crate) fn check_if_point_is_wall(x: u32, y: u32, walls: &[Wall]) -> bool {
+    walls.iter().any(|wall| wall.contains(x, y))
+}
+
+/// The straight pieces of a polyline as walls.
+pub(crate) fn path_walls(path: &[Point]) -> impl Iterator<Item = Wall> + '_ {
+    let single = (path.len() == 1).then(|| Wall {
+        start: path[0],
+        end: None,
+    });
+    let pieces = path.windows(2).map(|pair| {
+        let start = Point::new(pair[0].x.min(pair[1].x), pair[0].y.min(pair[1].y));
+        let end = Point::new(pair[0].x.max(pair[1].x), pair[0].y.max(pair[1].y));
+        Wall {
+            start,
+            end: (start != end).then_some(end),
+        }
+    });
+
+    single.into_iter().chain(pieces)
+}
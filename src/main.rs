@@ -1,235 +1,2280 @@
-use std::{error::Error, fmt::Display, fs::OpenOptions, io::BufWriter, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    error::Error,
+    ffi::OsString,
+    fmt::Display,
+    fs::{self, File},
+    io::{BufWriter, Cursor, IntoInnerError, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-use clap::Parser;
-use image::{io::Reader as ImageReader, GenericImageView};
+use clap::{parser::ValueSource, ArgGroup, CommandFactory, Parser, Subcommand, ValueEnum};
+use figment::{
+    providers::{Format as _, Toml},
+    Figment,
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use image::{
+    imageops::{self, FilterType},
+    ImageFormat, ImageReader,
+};
+use indicatif::{ProgressBar, ProgressStyle};
+use maze_lvl_maker::{
+    parse_ascii, parse_csv, parse_svg, scan_edges, scan_grid, scan_grid_with_progress, scan_hex,
+    BitOrder, Chunk, Collider, CorridorGraph, Grid, HexAxis, Junction, Lvl, MergeStrategy, Mesh,
+    Orientation, OrientationFirst, Point, QuadTree, Rect, Room, ScanOptions, SquareType,
+    WallSortKey, Wrap, FORMAT_VERSION,
+};
+use owo_colors::{OwoColorize, Style};
+#[cfg(feature = "ron")]
+use ron::ser::PrettyConfig;
 use serde::Serialize;
-use tracing::debug;
+use serde_json::ser::Formatter;
+use terminal_size::{terminal_size_of, Width};
+use tracing::{error, warn};
 use tracing_subscriber::EnvFilter;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SquareType {
-    Wall,
-    Checkpoint,
-    Start,
-    End,
-    Empty,
-}
-
-impl Display for SquareType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{:?}", self))
-    }
-}
-
-impl From<[u8; 4]> for SquareType {
-    fn from(value: [u8; 4]) -> Self {
-        match value {
-            [0, 0, 0, _] => Self::Wall,         // Black
-            [255, 0, 0, _] => Self::End,        // Red
-            [0, 255, 0, _] => Self::Start,      // Green
-            [0, 0, 255, _] => Self::Checkpoint, // Blue
-            [255, 255, 255, _] => Self::Empty,  // White
-            _ => unimplemented!("{:?}", value),
-        }
-    }
-}
-
 /// Lvl maker from image
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
+#[command(group(ArgGroup::new("input").required(true).args(["image", "svg", "ascii", "csv"])))]
+#[command(
+    after_help = "Defaults for the flags are read from .maze-lvl-maker.toml in the current \
+directory, or else in the home directory. Its keys are the flag names with underscores, like \
+`emit = \"grid\"` or `pretty = true`, and flags on the command line take precedence."
+)]
 struct Args {
     #[clap(short, long)]
-    image: PathBuf,
+    image: Option<PathBuf>,
+
+    /// Print the JSON Schema of the level format and exit, the same as the
+    /// `schema` subcommand without flags
+    #[cfg(feature = "schema")]
+    #[clap(long, group = "input", exclusive = true)]
+    dump_schema: bool,
+
+    /// Extra image of the same size whose non-white pixels are drawn over
+    /// the input, later layers win
+    #[clap(long)]
+    layer: Vec<PathBuf>,
+
+    /// Shrink the images by this factor before scanning, like `1/4` or `4`
+    /// when every square is drawn as 4x4 pixels
+    #[clap(long, default_value = "1", value_parser = parse_scale)]
+    scale: u32,
+
+    /// Image of another level to add to this one, see `--merge-offset`
+    #[clap(long)]
+    merge: Option<PathBuf>,
+
+    /// Where the top left square of `--merge` goes, as `<x>,<y>`
+    #[clap(long, default_value = "0,0", value_parser = parse_point, requires = "merge")]
+    merge_offset: Point,
+
+    /// Repeat the level `<nx>x<ny>` times, only the first copy keeps the
+    /// start and end
+    #[clap(long, value_parser = parse_tile)]
+    tile: Option<(u32, u32)>,
+
+    /// Only use the `<x>,<y>,<width>,<height>` region of the images, in
+    /// pixels before scaling. Only for `--image` input
+    #[clap(long, value_parser = parse_crop, conflicts_with_all = ["svg", "ascii", "csv"])]
+    crop: Option<Rect>,
+
+    /// Keep the coordinates of a cropped level relative to the whole image.
+    /// The level grows to reach the far corner of the crop, with the squares
+    /// outside of the crop left empty. Not with `--tile`, `--normalize`,
+    /// `--mirror` or `--rotate`, which move the level
+    #[clap(
+        long,
+        default_value = "false",
+        requires = "crop",
+        conflicts_with_all = ["tile", "normalize", "mirror", "rotate"]
+    )]
+    crop_origin_offset: bool,
+
+    /// Move the level so its bounding box starts at `(0, 0)` and shrink the
+    /// size to fit
+    #[clap(long, default_value = "false")]
+    normalize: bool,
+
+    /// What the squares are written as
+    #[clap(long, value_enum, default_value_t)]
+    emit: Emit,
+
+    /// Keep the walls next to the `grid`, `rows_rle`, `quadtree`, `graph`,
+    /// `colliders`, `ascii` or `occupancy` of `--emit`
+    #[clap(long, default_value = "false")]
+    with_walls: bool,
+
+    /// Fail when the level is wider than this many squares with `--emit
+    /// ascii`, rather than writing rows too long to read
+    #[clap(long)]
+    max_width: Option<u32>,
+
+    /// Shrink every box of `--emit colliders` by this many squares on each
+    /// side, so neighboring boxes don't touch
+    #[clap(long, default_value = "0")]
+    collider_padding: f64,
+
+    /// Reflect the level left to right (`h`) or top to bottom (`v`)
+    #[clap(long, value_enum)]
+    mirror: Option<Mirror>,
+
+    /// Turn the level clockwise by 90, 180 or 270 degrees
+    #[clap(long, value_parser = ["90", "180", "270"])]
+    rotate: Option<String>,
+
+    /// Connect the left and right (`x`), top and bottom (`y`) or both edges
+    /// of the level, walls running up to both edges are joined across the
+    /// seam with their end before their start
+    #[clap(long, value_enum)]
+    wrap: Option<Wrap>,
+
+    /// Order of the walls: `length` (longest first), `position` (reading
+    /// order of the starts), `horizontal-first` or `vertical-first`
+    #[clap(long, value_parser = parse_sort_key)]
+    sort_by: Option<WallSortKey>,
+
+    /// Order of the checkpoints, closest to the start or the end first, or
+    /// in reading order. Ties keep their order
+    #[clap(long, value_enum)]
+    checkpoint_sort: Option<CheckpointSort>,
 
+    /// Read the level from the rects and circles of an SVG instead
+    #[clap(long)]
+    svg: Option<PathBuf>,
+
+    /// Read the level from a text file, `#` walls, `S` start, `E` end,
+    /// `C` checkpoints and `.` or space for empty squares
+    #[clap(long)]
+    ascii: Option<PathBuf>,
+
+    /// Read the level from a CSV file of square type names like `Wall`,
+    /// empty fields are empty squares
+    #[clap(long)]
+    csv: Option<PathBuf>,
+
+    /// Where to write the level instead of stdout, `-` for stdout. It's
+    /// written to `<outfile>.tmp` first and renamed once complete, which
+    /// replaces the outfile atomically except on Windows
     #[clap(short, long)]
     outfile: Option<PathBuf>,
 
     #[clap(short, long, default_value = "false")]
     pretty: bool,
+
+    /// Json that diffs well when it's committed: walls and dynamic walls
+    /// sorted by the row and column of their start, orientation and length,
+    /// checkpoints in reading order, the keys of every object in
+    /// alphabetical order, indented by two spaces and ending in a newline.
+    /// The same level is always written byte for byte the same
+    #[clap(long, conflicts_with_all = ["sort_by", "checkpoint_sort"])]
+    canonical: bool,
+
+    /// Serializer for the output, by default `yaml` for a `.yaml` or `.yml`
+    /// outfile, `toml` for a `.toml` one, `msgpack` for a `.msgpack` or
+    /// `.mpk` one, `cbor` for a `.cbor` one, `ron` for a `.ron` one and
+    /// `json` otherwise. `bin` is never inferred
+    #[clap(long, value_enum)]
+    format: Option<Format>,
+
+    /// Leave the `orientation` out of the walls to keep the output small
+    #[clap(long, default_value = "false")]
+    no_orientation: bool,
+
+    /// Write every wall as a flat `[x1, y1, x2, y2]` array, single squares
+    /// repeat their start as the end
+    #[clap(long, default_value = "false")]
+    compact_walls: bool,
+
+    /// Naming convention of the keys, `camel` writes `format_version` as
+    /// `formatVersion` and `pascal` as `FormatVersion`. Levels are read in
+    /// any of them
+    #[clap(long, value_enum, default_value_t)]
+    field_case: FieldCase,
+
+    /// Split the walls into `chunks` of this many squares per side, each
+    /// with its `cx` and `cy`. Walls are cut where they cross into the next
+    /// chunk and the points get the `cx` and `cy` of their chunk
+    #[clap(long, conflicts_with = "compact_walls", value_parser = clap::value_parser!(u32).range(1..))]
+    chunk_size: Option<u32>,
+
+    /// Make sure every wall square is covered by exactly one wall
+    #[clap(long, default_value = "false")]
+    no_overlap: bool,
+
+    /// Merge the horizontal runs first (`h`) or the vertical ones (`v`).
+    /// The first orientation keeps its single squares, and with
+    /// `--no-overlap` claims its squares before the other is split around
+    /// them
+    #[clap(long, value_enum, default_value_t)]
+    merge_strategy: MergeStrategy,
+
+    /// Merge staircases of single squares into diagonal walls whose start
+    /// and end differ in both coordinates
+    #[clap(long, default_value = "false")]
+    diagonals: bool,
+
+    /// Fail with exit code 4 when the level has more than this many walls,
+    /// counted in what's written: walls, rects, wall paths, polygons, edge
+    /// walls or colliders
+    #[clap(long)]
+    max_walls: Option<usize>,
+
+    /// Cover every wall square exactly once with as few walls as the greedy
+    /// longest first choice finds
+    #[clap(long, default_value = "false")]
+    optimize_walls: bool,
+
+    /// Empty the inside of solid wall areas before merging, keeping a shell
+    /// one square thick
+    #[clap(long, default_value = "false")]
+    hollow: bool,
+
+    /// Read the squares as cells (`cells`) or as a `2 * width + 1` by
+    /// `2 * height + 1` maze whose even rows and columns are thin walls
+    /// between the cells (`edges`), written to `edge_walls`
+    #[clap(long, value_enum, default_value_t)]
+    wall_model: WallModel,
+
+    /// Shape of the cells, `hex` reads pointy topped hexagons in odd-r
+    /// offset layout of `--cell-size` pixels each and writes walls as runs
+    /// along the `e`, `se` and `sw` axes
+    #[clap(long, value_enum, default_value_t)]
+    grid: GridShape,
+
+    /// Pixels per hex cell, odd rows are shifted right by half of it. Also
+    /// the tile size of `--format tmx` and `ldtk`, 1 by default
+    #[clap(long, required_if_eq("grid", "hex"), value_parser = clap::value_parser!(u32).range(2..))]
+    cell_size: Option<u32>,
+
+    /// How the walls are written out
+    #[clap(long, value_enum, default_value_t)]
+    mesh: Mesh,
+
+    /// Simplify `--mesh marching` outlines, vertices closer than this many
+    /// squares to a shortcut are dropped as long as no corridor gets cut
+    #[clap(long, default_value = "0")]
+    simplify: f64,
+
+    /// Show a progress bar while merging the walls, only together with
+    /// `--outfile` so it can't get mixed into json on stdout
+    #[clap(long, default_value = "false")]
+    progress: bool,
+
+    /// Compress the output, by default with `gzip` for a `.gz` outfile,
+    /// `zstd` for a `.zst` one and `none` otherwise. `--compress` alone is
+    /// `gzip`. Adds the extension to the outfile if it's missing
+    #[clap(long, value_enum, num_args = 0..=1, default_missing_value = "gzip")]
+    compress: Option<Compressor>,
+
+    /// Compression level, 0 to 9 for gzip and 1 to 22 for zstd, by default
+    /// the smallest output: 9 for gzip and 19 for zstd
+    #[clap(long, value_parser = clap::value_parser!(u32).range(0..=22))]
+    compress_level: Option<u32>,
+
+    /// Add a heuristic difficulty score between 0 and 1
+    #[clap(long, default_value = "false")]
+    difficulty: bool,
+
+    /// Add the empty squares with a single way out
+    #[clap(long, default_value = "false")]
+    dead_ends: bool,
+
+    /// Add the wall squares where walls meet as corners, T or cross
+    /// junctions
+    #[clap(long, default_value = "false")]
+    junctions: bool,
+
+    /// Add the number and sizes of the connected open regions
+    #[clap(long, default_value = "false")]
+    components: bool,
+
+    /// Add the number of squares in the largest connected open region
+    #[clap(long, default_value = "false")]
+    largest_open_area: bool,
+
+    /// Add the open rectangular rooms
+    #[clap(long, default_value = "false")]
+    rooms: bool,
+
+    /// Smallest area for a room to be reported
+    #[clap(long, default_value = "4")]
+    min_room_size: u32,
+
+    /// Add walls along the empty squares of every edge that doesn't wrap,
+    /// after scanning and the transforms
+    #[clap(long, default_value = "false")]
+    auto_border: bool,
+
+    /// Fail when the outer border has gaps players could walk through
+    #[clap(long, default_value = "false")]
+    require_closed_border: bool,
+
+    /// Fail when the start or the end isn't on the outer edge of the level,
+    /// which is otherwise only logged as a warning
+    #[clap(long, default_value = "false")]
+    strict_boundary: bool,
+
+    /// Add the shortest path lengths between start, end and checkpoints
+    #[clap(long, default_value = "false")]
+    checkpoint_graph: bool,
+
+    /// Write a json object from every wall square, as `"x,y"`, to the index
+    /// of its wall in `walls` to this file, in reading order. Squares of
+    /// more than one wall get the first one. Not with `--chunk-size` or
+    /// `--diff`, whose walls aren't the ones it indexes
+    #[clap(long, conflicts_with_all = ["chunk_size", "diff"])]
+    source_map: Option<PathBuf>,
+
+    /// Write a json array with the `[x, y]` squares of every wall, in the
+    /// order of `walls`, to this file. Not with `--chunk-size` or `--diff`
+    #[clap(long, conflicts_with_all = ["chunk_size", "diff"])]
+    inverse_source_map: Option<PathBuf>,
+
+    /// Fail instead of writing a `--source-map` or `--inverse-source-map`
+    /// with more squares than this
+    #[clap(long, default_value = "1000000")]
+    max_source_map_size: usize,
+
+    /// Add the `solution`, the squares of a shortest route from the start
+    /// through the checkpoints in the order they're listed to the end, or
+    /// null with a warning when the level can't be solved
+    #[clap(long, default_value = "false")]
+    embed_solution: bool,
+
+    /// Fail when the end or a required checkpoint can't be reached from the
+    /// start
+    #[clap(long, default_value = "false")]
+    check_solvable: bool,
+
+    /// Print every pair of walls sharing a square to stderr as json lines
+    /// and fail with exit code 3 if there are any. Walls merged by default
+    /// meet at corners, combine it with `--no-overlap` or `--optimize-walls`
+    #[clap(long, default_value = "false")]
+    report_overlaps: bool,
+
+    /// Compare the level with an earlier version of it written as json,
+    /// gzipped or zstd compressed by extension, and write what changed
+    /// instead of the level. Exits with 1 when they differ and 2 when the
+    /// earlier version can't be read
+    #[clap(long)]
+    diff: Option<PathBuf>,
+
+    /// Print the level to stderr as text, one character per square: `#`
+    /// walls, `%` dynamic walls, `S` start, `E` end, `C` checkpoints, `c`
+    /// optional checkpoints and `.` empty. Lines are cut at the width of the
+    /// terminal
+    #[clap(long, default_value = "false")]
+    preview: bool,
+
+    /// Color the `--preview`, unless `NO_COLOR` is set
+    #[clap(long, default_value = "false", requires = "preview")]
+    color_output: bool,
+
+    /// Characters per square in the `--preview`, 2 looks about square in
+    /// most terminal fonts
+    #[clap(long, requires = "preview", value_parser = clap::value_parser!(u32).range(1..))]
+    scale_preview: Option<u32>,
+
+    /// Run every step but write nothing, print a summary of the level and
+    /// the warnings to stderr instead
+    #[clap(long, default_value = "false")]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Draw a level as an SVG with a rect per wall and a circle per point
+    /// over a light grid, in the order the level lists them, or as a PNG in
+    /// the palette images are read in
+    Render(RenderArgs),
+    /// Print the JSON Schema of the level json, by default for levels
+    /// written without `--compact-walls` or `--no-orientation`. Fields added
+    /// by `--emit` aren't part of it, extra fields are allowed
+    #[cfg(feature = "schema")]
+    Schema(SchemaArgs),
+    /// Rewrite level json written by older builds at the current
+    /// `format_version`, in place and compressed like it was. Files from a
+    /// newer build are refused
+    Upgrade(UpgradeArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct UpgradeArgs {
+    /// Level files, `.gz` and `.zst` ones are decompressed and compressed
+    /// again
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Pretty print the rewritten json
+    #[clap(short, long)]
+    pretty: bool,
 }
 
-#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
-struct Point {
-    x: u32,
-    y: u32,
+#[cfg(feature = "schema")]
+#[derive(clap::Args, Debug)]
+struct SchemaArgs {
+    /// Walls as the `[x1, y1, x2, y2]` arrays `--compact-walls` writes
+    #[clap(long)]
+    compact_walls: bool,
+
+    /// Walls and dynamic walls without the `orientation` `--no-orientation`
+    /// leaves out
+    #[clap(long)]
+    no_orientation: bool,
 }
 
-impl Point {
-    fn new(x: u32, y: u32) -> Self {
-        Self { x, y }
+#[derive(clap::Args, Debug)]
+#[command(group(ArgGroup::new("level").required(true).args(["image", "lvl"])))]
+struct RenderArgs {
+    /// Image to scan with the default options
+    #[clap(short, long)]
+    image: Option<PathBuf>,
+
+    /// Level json written by maze-lvl-maker, decompressed by its `.gz` or
+    /// `.zst` extension
+    #[clap(long)]
+    lvl: Option<PathBuf>,
+
+    /// SVG units per square
+    #[clap(long, default_value = "10", value_parser = clap::value_parser!(u32).range(1..))]
+    scale: u32,
+
+    /// Write the SVG to this file instead of stdout, `-` for stdout
+    #[clap(short, long)]
+    outfile: Option<PathBuf>,
+
+    /// Write a PNG to this file instead of the SVG, walls black, start
+    /// green, end red, checkpoints blue and the rest white
+    #[clap(long, conflicts_with_all = ["scale", "outfile"])]
+    png: Option<PathBuf>,
+
+    /// Pixels per square of the `--png`
+    #[clap(long, requires = "png", default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+    cell_size: u32,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Emit {
+    /// Merged walls in the chosen `--mesh`
+    #[default]
+    Walls,
+    /// Rows of tile codes in `grid`: 0 empty, 1 wall, 2 start, 3 end, 4
+    /// checkpoint, 5 optional checkpoint, 6 dynamic wall
+    Grid,
+    /// Per row in `rows_rle`, alternating runs of open and wall squares
+    /// starting with open
+    Rle,
+    /// Quadtree of the solid walls in `quadtree`
+    Quadtree,
+    /// Corridors between dead ends, junctions and checkpoints in `graph`
+    Graph,
+    /// Rows of characters in `ascii` like the `--ascii` input, `#` wall, `S`
+    /// start, `E` end, `C` checkpoint, `c` optional checkpoint, `%` dynamic
+    /// wall and `.` empty
+    Ascii,
+    /// Axis aligned boxes `{cx, cy, hx, hy}` in `colliders`, one per wall or
+    /// per rectangle with `--mesh rect`
+    Colliders,
+    /// The wall squares as a base64 bitset in `occupancy`, one bit per
+    /// square in reading order, in the `bit_order` it names
+    Bitset,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    /// Same structure as the json, always indented so `--pretty` doesn't
+    /// apply. Needs the `yaml` feature
+    Yaml,
+    /// Same structure as the json with lists of objects as arrays of
+    /// tables. Single square walls have an `end` equal to their `start`
+    /// since TOML has no null. Needs the `toml` feature
+    Toml,
+    /// Same structure as the json in binary MessagePack, with maps keyed by
+    /// field name. Needs the `msgpack` feature
+    Msgpack,
+    /// Same structure as the json in binary CBOR, with maps keyed by field
+    /// name. Needs the `cbor` feature
+    Cbor,
+    /// Written from the level instead of the json, so wall ends are
+    /// `Some(..)` or `None` and enums are variants. The top level and dynamic
+    /// walls are maps since they flatten other structs. Walls that aren't
+    /// emitted are empty lists. Needs the `ron` feature
+    Ron,
+    /// Packed little endian records of the level, only with `--format`,
+    /// see `src/binary.rs` for the layout
+    Bin,
+    /// Tiled map with the squares as a tile layer and the start, end and
+    /// checkpoints as point objects, see `src/tmx.rs`
+    Tmx,
+    /// LDtk project with one level of a walls IntGrid layer and an entities
+    /// layer for the start, end and checkpoints, see `src/ldtk.rs`
+    Ldtk,
+    /// Godot 4 `.tres` resource with the level as typed metadata, listed in
+    /// its header comment, see `src/godot.rs`
+    Godot,
+}
+
+impl Format {
+    /// Cargo feature the serializer is behind when this build doesn't have it.
+    const fn missing_feature(self) -> Option<&'static str> {
+        match self {
+            #[cfg(not(feature = "yaml"))]
+            Self::Yaml => Some("yaml"),
+            #[cfg(not(feature = "toml"))]
+            Self::Toml => Some("toml"),
+            #[cfg(not(feature = "msgpack"))]
+            Self::Msgpack => Some("msgpack"),
+            #[cfg(not(feature = "cbor"))]
+            Self::Cbor => Some("cbor"),
+            #[cfg(not(feature = "ron"))]
+            Self::Ron => Some("ron"),
+            _ => None,
+        }
     }
 }
 
-#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
-struct Wall {
-    start: Point,
-    end: Option<Point>,
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Compressor {
+    Gzip,
+    Zstd,
+    None,
 }
 
-impl Wall {
-    fn length(self) -> u32 {
-        if let Some(end) = self.end {
-            (end.x - self.start.x) + (end.y - self.start.y)
-        } else {
-            1
+impl Compressor {
+    const fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::Gzip => Some("gz"),
+            Self::Zstd => Some("zst"),
+            Self::None => None,
         }
     }
 }
 
-impl Ord for Wall {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let self_length = self.length();
-        let other_length = other.length();
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum GridShape {
+    #[default]
+    Square,
+    Hex,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum FieldCase {
+    /// `format_version`
+    #[default]
+    Snake,
+    /// `formatVersion`
+    Camel,
+    /// `FormatVersion`
+    Pascal,
+}
 
-        self_length.cmp(&other_length)
+impl FieldCase {
+    /// A snake case `key` in this case.
+    fn convert(self, key: &str) -> String {
+        if self == Self::Snake {
+            return key.to_owned();
+        }
+        key.split('_')
+            .enumerate()
+            .map(|(index, word)| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) if index > 0 || self == Self::Pascal => {
+                        first.to_uppercase().chain(chars).collect()
+                    }
+                    _ => word.to_owned(),
+                }
+            })
+            .collect()
+    }
+
+    /// Rename the keys of every object in `value`, nested ones included.
+    fn rename_keys(self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Array(values) => {
+                values.iter_mut().for_each(|value| self.rename_keys(value));
+            }
+            serde_json::Value::Object(object) => {
+                *object = std::mem::take(object)
+                    .into_iter()
+                    .map(|(key, mut value)| {
+                        self.rename_keys(&mut value);
+                        (self.convert(&key), value)
+                    })
+                    .collect();
+            }
+            _ => (),
+        }
     }
 }
 
-impl PartialOrd for Wall {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+/// Rename the camel or pascal case keys of every object in `value` back to
+/// snake case, which is what the level is read in.
+fn snake_case_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Array(values) => values.iter_mut().for_each(snake_case_keys),
+        serde_json::Value::Object(object) => {
+            *object = std::mem::take(object)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    snake_case_keys(&mut value);
+                    let mut snake = String::with_capacity(key.len() + 4);
+                    for (index, char) in key.chars().enumerate() {
+                        if char.is_uppercase() && index > 0 {
+                            snake.push('_');
+                        }
+                        snake.extend(char.to_lowercase());
+                    }
+                    (snake, value)
+                })
+                .collect();
+        }
+        _ => (),
     }
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum WallModel {
+    #[default]
+    Cells,
+    Edges,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum CheckpointSort {
+    /// Manhattan distance from the start
+    ProximityStart,
+    /// Manhattan distance from the end
+    ProximityEnd,
+    /// Top to bottom and then left to right
+    Lexicographic,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Mirror {
+    #[value(name = "h", alias = "horizontal")]
+    Horizontal,
+    #[value(name = "v", alias = "vertical")]
+    Vertical,
+}
+
+/// The level plus any optional analysis requested on the command line.
 #[derive(Serialize)]
-struct Lvl {
-    width: u32,
-    height: u32,
-    walls: Vec<Wall>,
-    start: Point,
-    end: Point,
-    checkpoints: Vec<Point>,
-}
-
-#[inline]
-fn check_if_point_is_wall(x: u32, y: u32, walls: &[Wall]) -> bool {
-    walls.iter().any(|wall| {
-        (wall.start.x <= x && x <= wall.end.map_or_else(|| wall.start.x, |end| end.x))
-            && (wall.start.y <= y && y <= wall.end.map_or_else(|| wall.start.y, |end| end.y))
-    })
+struct Output<'a> {
+    #[serde(flatten)]
+    lvl: &'a Lvl,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grid: Option<Vec<Vec<u8>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rows_rle: Option<Vec<Vec<u32>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quadtree: Option<QuadTree>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    graph: Option<CorridorGraph>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    colliders: Option<Vec<Collider>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ascii: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    occupancy: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bit_order: Option<BitOrder>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dead_ends: Option<Vec<Point>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    junctions: Option<Vec<Junction>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    components: Option<Components>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    largest_open_area: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rooms: Option<Vec<Room>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checkpoint_graph: Option<CheckpointGraph>,
+
+    /// Null when the level can't be solved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    solution: Option<Option<Vec<Point>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunk_size: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunks: Option<Vec<Chunk>>,
+}
+
+#[derive(Serialize)]
+struct CheckpointGraph {
+    /// Start, end and the checkpoints, indexed by `adjacency`
+    nodes: Vec<Point>,
+    adjacency: Vec<Vec<(usize, u32)>>,
+}
+
+#[derive(Serialize)]
+struct Components {
+    count: usize,
+    sizes: Vec<usize>,
+}
+
+impl From<Vec<Vec<Point>>> for Components {
+    fn from(components: Vec<Vec<Point>>) -> Self {
+        Self {
+            count: components.len(),
+            sizes: components.iter().map(Vec::len).collect(),
+        }
+    }
+}
+
+/// Exit code for [`WallLimitExceeded`], every other error exits with 1
+/// unless it has a code of its own.
+const WALL_LIMIT_EXIT_CODE: u8 = 4;
+
+/// Exit code for [`OverlappingWalls`].
+const OVERLAP_EXIT_CODE: u8 = 3;
+
+/// Exit code for [`UnreadableLevel`].
+const UNREADABLE_LEVEL_EXIT_CODE: u8 = 2;
+
+/// More walls than `--max-walls` allows.
+#[derive(Debug)]
+struct WallLimitExceeded {
+    count: usize,
+    limit: usize,
+    /// What was counted, like `rects`
+    kind: &'static str,
+    /// Flags that could bring the count down
+    suggestions: Vec<&'static str>,
+}
+
+impl Display for WallLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the level has {} {}, more than the --max-walls limit of {}",
+            self.count, self.kind, self.limit
+        )?;
+        if !self.suggestions.is_empty() {
+            write!(f, ", try {}", self.suggestions.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for WallLimitExceeded {}
+
+/// Walls sharing squares found by `--report-overlaps`.
+#[derive(Debug)]
+struct OverlappingWalls(usize);
+
+impl Display for OverlappingWalls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} pairs of walls overlap", self.0)
+    }
+}
+
+impl Error for OverlappingWalls {}
+
+/// `--diff` level that can't be read or parsed.
+#[derive(Debug)]
+struct UnreadableLevel {
+    path: PathBuf,
+    error: Box<dyn Error>,
+}
+
+impl Display for UnreadableLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "can't read {} as a level: {}",
+            self.path.display(),
+            self.error
+        )
+    }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+impl Error for UnreadableLevel {}
+
+/// `--diff` found changes, reported with exit code 1 and no message since
+/// the diff is the output.
+#[derive(Debug)]
+struct LevelsDiffer;
+
+impl Display for LevelsDiffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the levels differ")
+    }
+}
+
+impl Error for LevelsDiffer {}
+
+/// What `--dry-run` prints instead of the output.
+struct Summary {
+    /// Squares read from the input, after `--crop` and `--scale`
+    image: (u32, u32),
+    level: (u32, u32),
+    /// Written walls and what they are, like `rects`
+    walls: (usize, &'static str),
+    /// Squares covered by the walls, only for runs of squares
+    wall_length: Option<u64>,
+    /// Walls per orientation or hex axis
+    orientations: Vec<(&'static str, usize)>,
+    checkpoints: usize,
+    optional_checkpoints: usize,
+    /// Only checked with `--check-solvable`
+    solvable: Option<bool>,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "image: {}x{}", self.image.0, self.image.1)?;
+        writeln!(f, "level: {}x{}", self.level.0, self.level.1)?;
+        writeln!(f, "{}: {}", self.walls.1, self.walls.0)?;
+        if let Some(length) = self.wall_length {
+            writeln!(f, "wall length: {length}")?;
+        }
+        for (orientation, count) in &self.orientations {
+            writeln!(f, "{orientation} walls: {count}")?;
+        }
+        writeln!(f, "checkpoints: {}", self.checkpoints)?;
+        writeln!(f, "optional checkpoints: {}", self.optional_checkpoints)?;
+        if let Some(solvable) = self.solvable {
+            writeln!(f, "solvable: {}", if solvable { "yes" } else { "no" })?;
+        }
+        Ok(())
+    }
+}
+
+/// Print the `--dry-run` summary, then fail if `--check-solvable` found the
+/// level unsolvable.
+fn finish_dry_run(summary: &Summary) -> Result<(), Box<dyn Error>> {
+    eprint!("{summary}");
+    check_solvable(summary.solvable)
+}
+
+/// The `--preview` of the level, with lines cut at `columns` characters.
+fn preview(lvl: &Lvl, color: bool, scale: u32, columns: Option<usize>) -> String {
+    let scale = scale as usize;
+    let width = columns.map_or(lvl.width, |columns| {
+        lvl.width
+            .min(u32::try_from(columns / scale).unwrap_or(u32::MAX))
+    });
+    let grid = Grid::from_lvl(lvl);
+    let mut preview = String::new();
+    for y in 0..lvl.height {
+        for x in 0..width {
+            let (character, style) = match grid.get(x, y) {
+                SquareType::Wall => ('#', Style::new().white().on_black()),
+                SquareType::DynamicWall(_) => ('%', Style::new().white().on_black().dimmed()),
+                SquareType::Start => ('S', Style::new().bright_green()),
+                SquareType::End => ('E', Style::new().bright_red()),
+                SquareType::Checkpoint => ('C', Style::new().bright_blue()),
+                SquareType::OptionalCheckpoint => ('c', Style::new().blue()),
+                SquareType::Empty => ('.', Style::new().bright_black().dimmed()),
+            };
+            let square = character.to_string().repeat(scale);
+            if color {
+                preview.push_str(&square.style(style).to_string());
+            } else {
+                preview.push_str(&square);
+            }
+        }
+        preview.push('\n');
+    }
+    preview
+}
+
+fn check_solvable(solvable: Option<bool>) -> Result<(), Box<dyn Error>> {
+    if solvable == Some(false) {
+        return Err("the end or a checkpoint can't be reached from the start".into());
+    }
+    Ok(())
+}
+
+/// Read a level written as json, decompressed by its extension like the
+/// outfile is compressed.
+fn read_lvl(path: &Path) -> Result<Lvl, Box<dyn Error>> {
+    let bytes = fs::read(path)?;
+    let json = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("gz") => {
+            let mut json = Vec::new();
+            GzDecoder::new(bytes.as_slice()).read_to_end(&mut json)?;
+            json
+        }
+        Some("zst") => zstd::decode_all(bytes.as_slice())?,
+        _ => bytes,
+    };
+    let mut json: serde_json::Value = serde_json::from_slice(&json)?;
+    // Written with any --field-case
+    snake_case_keys(&mut json);
+    Ok(serde_json::from_value(json)?)
+}
+
+/// Config file with defaults for the flags, see [`parse_args`].
+const CONFIG_FILE: &str = ".maze-lvl-maker.toml";
+
+/// The command line arguments with the defaults from the first
+/// [`CONFIG_FILE`] in the current or the home directory filled in, and
+/// warnings about keys that aren't flags.
+///
+/// The defaults are added as flags after the command line ones, except for
+/// flags that are already given, on the command line or in their
+/// environment variable, or that are in a group where another flag is.
+/// Clap then parses them all, so they're checked like the command line.
+fn parse_args() -> Result<(Args, Vec<String>), Box<dyn Error>> {
+    let mut argv: Vec<OsString> = std::env::args_os().collect();
+    let home = std::env::var_os("HOME").map(|home| Path::new(&home).join(CONFIG_FILE));
+    let Some(path) = [Some(PathBuf::from(CONFIG_FILE)), home]
+        .into_iter()
+        .flatten()
+        .find(|path| path.is_file())
+    else {
+        return Ok((Args::parse_from(argv), Vec::new()));
+    };
+    let config: serde_json::Map<String, serde_json::Value> = Figment::from(Toml::file_exact(&path))
+        .extract()
+        .map_err(|error| format!("invalid {}: {error}", path.display()))?;
+
+    let command = Args::command();
+    let matches = command.clone().ignore_errors(true).get_matches_from(&argv);
+    let given = |id: &str| {
+        matches!(
+            matches.value_source(id),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        )
+    };
+    // Subcommands have flags of their own, the config only holds the main ones
+    if matches.subcommand().is_some()
+        || command
+            .get_arguments()
+            .any(|arg| arg.is_exclusive_set() && given(arg.get_id().as_str()))
+    {
+        return Ok((Args::parse_from(argv), Vec::new()));
+    }
+
+    let mut warnings = Vec::new();
+    for (key, value) in config {
+        let Some(long) = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == key.as_str())
+            .and_then(|arg| arg.get_long())
+        else {
+            warnings.push(format!("unknown key `{key}` in {}", path.display()));
+            continue;
+        };
+        // Derive adds a group of every field that allows them all together
+        let grouped_with_given = command.get_groups().any(|group| {
+            !group.clone().is_multiple()
+                && group.get_args().any(|id| id == key.as_str())
+                && group.get_args().any(|id| given(id.as_str()))
+        });
+        if given(&key) || grouped_with_given {
+            continue;
+        }
+
+        let values = match value {
+            serde_json::Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            match value {
+                serde_json::Value::Bool(true) => argv.push(format!("--{long}").into()),
+                serde_json::Value::Bool(false) => (),
+                serde_json::Value::String(value) => argv.push(format!("--{long}={value}").into()),
+                serde_json::Value::Number(value) => argv.push(format!("--{long}={value}").into()),
+                _ => warnings.push(format!(
+                    "`{key}` in {} isn't a string, number or boolean",
+                    path.display()
+                )),
+            }
+        }
+    }
+    Ok((Args::parse_from(argv), warnings))
+}
+
+fn main() -> ExitCode {
+    let (mut args, warnings) = match parse_args() {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if args.outfile.as_deref() == Some(Path::new("-")) {
+        args.outfile = None;
+    }
+    // Printed rather than traced so they show without RUST_LOG
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+    // Warnings are part of what a dry run reports
+    let filter = if args.dry_run {
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"))
+    } else {
+        EnvFilter::from_default_env()
+    };
     tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
         .init();
 
-    let args = Args::parse();
-    let img = ImageReader::open(args.image)?.decode()?;
-    debug!("Lvl Size {}x{}", img.width(), img.height());
-    let mut lvl = Lvl {
-        width: img.width(),
-        height: img.height(),
-        walls: Vec::new(),
-        start: Point { x: 0, y: 0 },
-        end: Point { x: 0, y: 0 },
-        checkpoints: Vec::new(),
-    };
-
-    let mut horizontal_walls = Vec::new();
-
-    let mut x;
-    let mut y = 0;
-    while y < img.height() {
-        x = 0;
-        while x < img.width() {
-            let pixel = img.get_pixel(x, y);
-            match SquareType::from(pixel.0) {
-                SquareType::Wall => {
-                    // Only check for horizontal lines
-                    let start = Point::new(x, y);
-                    while (x + 1) < img.width()
-                        && SquareType::from(img.get_pixel(x + 1, y).0) == SquareType::Wall
-                    {
-                        x += 1;
-                        tracing::trace!("Wall detected at: {}-{}", x, y);
-                    }
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) if error.is::<WallLimitExceeded>() => {
+            eprintln!("Error: {error}");
+            ExitCode::from(WALL_LIMIT_EXIT_CODE)
+        }
+        Err(error) if error.is::<OverlappingWalls>() => {
+            eprintln!("Error: {error}");
+            ExitCode::from(OVERLAP_EXIT_CODE)
+        }
+        Err(error) if error.is::<UnreadableLevel>() => {
+            eprintln!("Error: {error}");
+            ExitCode::from(UNREADABLE_LEVEL_EXIT_CODE)
+        }
+        Err(error) if error.is::<LevelsDiffer>() => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-                    // Always insert, even if it's a single wall block
-                    horizontal_walls.push(Wall {
-                        start,
-                        end: (start.x != x).then_some(Point::new(x, y)),
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(Command::Render(render)) = &args.command {
+        return render_level(render);
+    }
+    if let Some(Command::Upgrade(upgrade)) = &args.command {
+        return upgrade_levels(upgrade);
+    }
+    #[cfg(feature = "schema")]
+    if let Some(Command::Schema(schema)) = &args.command {
+        let schema = level_schema(schema.compact_walls, schema.no_orientation)?;
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+    #[cfg(feature = "schema")]
+    if args.dump_schema {
+        let schema = level_schema(false, false)?;
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+    if let Some(feature) = output_format(&args).missing_feature() {
+        return Err(format!(
+            "--format {feature} isn't built in, install with `--features {feature}` for it"
+        )
+        .into());
+    }
+    let mut grid = if let Some(image) = &args.image {
+        read_image(image, args.crop, args.scale)?
+    } else if let Some(svg) = &args.svg {
+        parse_svg(&fs::read_to_string(svg)?)?
+    } else if let Some(ascii) = &args.ascii {
+        parse_ascii(&fs::read_to_string(ascii)?)?
+    } else if let Some(csv) = &args.csv {
+        parse_csv(&fs::read_to_string(csv)?)?
+    } else {
+        unreachable!("clap requires an input")
+    };
+    for layer in &args.layer {
+        grid.overlay(&read_image(layer, args.crop, args.scale)?)?;
+    }
+    let format = output_format(&args);
+    check_combinations(&args, format)?;
+    if args.grid == GridShape::Hex {
+        let cell_size = args.cell_size.expect("clap requires a cell size for hex");
+        let lvl = scan_hex(&grid, cell_size);
+        let solvable = lvl.is_solvable();
+        if !solvable && !args.check_solvable {
+            warn!("The end or a checkpoint can't be reached from the start");
+        }
+        check_wall_limit(&args, lvl.walls.len(), "walls")?;
+        let solvable = args.check_solvable.then_some(solvable);
+        if args.dry_run {
+            return finish_dry_run(&Summary {
+                image: (grid.width(), grid.height()),
+                level: (lvl.width, lvl.height),
+                walls: (lvl.walls.len(), "walls"),
+                wall_length: Some(lvl.walls.iter().map(|wall| u64::from(wall.length)).sum()),
+                orientations: HexAxis::ALL
+                    .into_iter()
+                    .map(|axis| {
+                        let name = match axis {
+                            HexAxis::E => "e",
+                            HexAxis::Se => "se",
+                            HexAxis::Sw => "sw",
+                        };
+                        let count = lvl.walls.iter().filter(|wall| wall.axis == axis).count();
+                        (name, count)
                     })
+                    .collect(),
+                checkpoints: lvl.checkpoints.len(),
+                optional_checkpoints: lvl.optional_checkpoints.len(),
+                solvable,
+            });
+        }
+        check_solvable(solvable)?;
+        return write_output(&args, &lvl, None, &serde_json::to_value(&lvl)?);
+    }
+
+    let options = ScanOptions {
+        no_overlap: args.no_overlap,
+        merge_strategy: args.merge_strategy,
+        diagonals: args.diagonals,
+        optimize_walls: args.optimize_walls,
+        hollow: args.hollow,
+        mesh: args.mesh,
+        simplify: args.simplify,
+    };
+    let progress_bar = (args.progress && args.outfile.is_some()).then(|| {
+        let style = ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len} eta {eta}")
+            .expect("progress template is valid");
+        ProgressBar::new(0).with_style(style)
+    });
+    let scan = |grid: &Grid| -> Result<Lvl, Box<dyn Error>> {
+        match args.wall_model {
+            WallModel::Cells => Ok(scan_grid_with_progress(grid, options, |progress| {
+                if let Some(bar) = &progress_bar {
+                    bar.set_length(u64::from(progress.total));
+                    bar.set_position(u64::from(progress.done));
+                    bar.set_message(format!("{}, {} walls", progress.phase, progress.walls));
                 }
-                SquareType::End => lvl.end = Point::new(x, y),
-                SquareType::Checkpoint => lvl.checkpoints.push(Point::new(x, y)),
-                SquareType::Start => lvl.start = Point::new(x, y),
-                SquareType::Empty => (),
-            }
+            })),
+            // The message says how to fix the image, which the debug output
+            // main ends with doesn't
+            WallModel::Edges => scan_edges(grid).map_err(|error| error.to_string().into()),
+        }
+    };
+    let mut lvl = scan(&grid)?;
+    if let Some(bar) = &progress_bar {
+        bar.finish_and_clear();
+    }
+    if let Some(crop) = args.crop.filter(|_| args.crop_origin_offset) {
+        let (dx, dy) = (crop.x / args.scale, crop.y / args.scale);
+        lvl.offset(dx, dy);
+        lvl.width += dx;
+        lvl.height += dy;
+    }
+    if let Some(merge) = &args.merge {
+        let other = scan(&read_image(merge, args.crop, args.scale)?)?;
+        lvl = lvl.merge(&other, args.merge_offset);
+    }
+    if let Some((nx, ny)) = args.tile {
+        if lvl.width.checked_mul(nx).is_none() || lvl.height.checked_mul(ny).is_none() {
+            return Err(format!(
+                "--tile {nx}x{ny} of a {}x{} level is more than {} squares wide or high",
+                lvl.width,
+                lvl.height,
+                u32::MAX
+            )
+            .into());
+        }
+        lvl = lvl.tile(nx, ny);
+    }
+    if args.normalize {
+        lvl = lvl.normalize();
+    }
+    match args.mirror {
+        Some(Mirror::Horizontal) => lvl = lvl.mirror_horizontal(),
+        Some(Mirror::Vertical) => lvl = lvl.mirror_vertical(),
+        None => (),
+    }
+    match args.rotate.as_deref() {
+        Some("90") => lvl = lvl.rotate_90(),
+        Some("180") => lvl = lvl.rotate_180(),
+        Some("270") => lvl = lvl.rotate_270(),
+        _ => (),
+    }
+    if let Some(wrap) = args.wrap {
+        lvl.set_wrap(wrap);
+    }
+    if args.auto_border {
+        lvl.add_border_walls();
+    }
+    if let Some(key) = args.sort_by {
+        lvl.sort_walls(key);
+    }
+    match args.checkpoint_sort {
+        Some(CheckpointSort::ProximityStart) => lvl.sort_checkpoints_by_proximity_to_start(),
+        Some(CheckpointSort::ProximityEnd) => lvl.sort_checkpoints_by_proximity_to_end(),
+        Some(CheckpointSort::Lexicographic) => lvl.sort_checkpoints_lexicographic(),
+        None => (),
+    }
+    if args.canonical {
+        lvl.canonicalize();
+    }
+    if args.preview {
+        let color =
+            args.color_output && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+        let columns = terminal_size_of(std::io::stderr()).map(|(Width(columns), _)| columns);
+        eprint!(
+            "{}",
+            preview(
+                &lvl,
+                color,
+                args.scale_preview.unwrap_or(1),
+                columns.map(usize::from)
+            )
+        );
+    }
 
-            x += 1;
+    if args.require_closed_border {
+        let gaps = lvl.border_gaps();
+        for gap in &gaps {
+            error!("{gap}");
+        }
+        if !gaps.is_empty() {
+            return Err(format!("the outer border has {} gaps", gaps.len()).into());
+        }
+    }
+    let off_boundary = lvl.validate_boundary_start_end();
+    for warning in &off_boundary {
+        if args.strict_boundary {
+            error!("{warning}");
+        } else {
+            warn!("{warning}");
+        }
+    }
+    if args.strict_boundary && !off_boundary.is_empty() {
+        return Err("--strict-boundary needs the start and end on the edge of the level".into());
+    }
+    if args.report_overlaps {
+        let overlaps = lvl.find_overlapping_walls();
+        for overlap in &overlaps {
+            eprintln!("{}", serde_json::to_string(overlap)?);
+        }
+        if !overlaps.is_empty() {
+            return Err(OverlappingWalls(overlaps.len()).into());
         }
-        y += 1;
     }
 
-    // Add vertical walls
-    let mut vertical_walls = Vec::new();
-    x = 0;
-    while x < img.width() {
-        y = 0;
-        while y < img.height() {
-            let pixel = img.get_pixel(x, y);
-            if SquareType::from(pixel.0) == SquareType::Wall {
-                let start = Point::new(x, y);
+    let (count, kind) = if args.wall_model == WallModel::Edges {
+        (lvl.edge_walls.len(), "edge walls")
+    } else if args.emit == Emit::Colliders {
+        (lvl.colliders(args.collider_padding).len(), "colliders")
+    } else {
+        match args.mesh {
+            Mesh::Lines => (lvl.walls.len(), "walls"),
+            Mesh::Rect => (lvl.rects.len(), "rects"),
+            Mesh::Polyline => (lvl.wall_paths.len(), "wall paths"),
+            Mesh::Contour | Mesh::Marching => (lvl.collision.len(), "polygons"),
+        }
+    };
+    check_wall_limit(&args, count, kind)?;
+    if let Some(max_width) = args.max_width {
+        if args.emit != Emit::Ascii {
+            return Err("--max-width only applies to --emit ascii".into());
+        }
+        if lvl.width > max_width {
+            return Err(format!(
+                "the level is {} squares wide, more than --max-width {max_width}",
+                lvl.width
+            )
+            .into());
+        }
+    }
 
-                while (y + 1) < img.height()
-                    && SquareType::from(img.get_pixel(x, y + 1).0) == SquareType::Wall
-                {
-                    y += 1;
-                    tracing::trace!("Wall detected at: {}-{}", x, y);
-                }
+    if lvl.needs_dynamic_walls() {
+        warn!("Every route to the end goes through a dynamic wall, solving it depends on timing");
+    }
+    let solvable = args.check_solvable.then(|| lvl.is_solvable());
+    if args.dry_run {
+        let runs = args.wall_model == WallModel::Cells && args.mesh == Mesh::Lines;
+        return finish_dry_run(&Summary {
+            image: (grid.width(), grid.height()),
+            level: (lvl.width, lvl.height),
+            walls: (count, kind),
+            wall_length: runs.then(|| lvl.walls.iter().map(|wall| u64::from(wall.length())).sum()),
+            orientations: if runs {
+                [
+                    Orientation::Horizontal,
+                    Orientation::Vertical,
+                    Orientation::Diagonal,
+                    Orientation::Point,
+                ]
+                .into_iter()
+                .map(|orientation| {
+                    let name = match orientation {
+                        Orientation::Horizontal => "horizontal",
+                        Orientation::Vertical => "vertical",
+                        Orientation::Diagonal => "diagonal",
+                        Orientation::Point => "single square",
+                    };
+                    let count = lvl
+                        .walls
+                        .iter()
+                        .filter(|wall| wall.orientation() == orientation)
+                        .count();
+                    (name, count)
+                })
+                .collect()
+            } else {
+                Vec::new()
+            },
+            checkpoints: lvl.checkpoints.len(),
+            optional_checkpoints: lvl.optional_checkpoints.len(),
+            solvable,
+        });
+    }
+    check_solvable(solvable)?;
 
-                let wall = Wall {
-                    start,
-                    end: (start.y != y).then_some(Point::new(x, y)),
-                };
+    if let Some(path) = &args.diff {
+        let earlier = read_lvl(path).map_err(|error| UnreadableLevel {
+            path: path.clone(),
+            error,
+        })?;
+        let diff = earlier.diff(&lvl);
+        write_output(&args, &diff, None, &serde_json::to_value(&diff)?)?;
+        return if diff.is_empty() {
+            Ok(())
+        } else {
+            Err(LevelsDiffer.into())
+        };
+    }
 
-                // Only insert none 1 block walls
-                if wall.end.is_some() {
-                    debug!("{:?}", wall);
-                    vertical_walls.push(wall);
-                }
+    // Only list the walls when they are emitted, the json drops them altogether
+    let lists_walls = args.emit == Emit::Walls || args.with_walls;
+    let listed = if lists_walls && args.chunk_size.is_none() {
+        Cow::Borrowed(&lvl)
+    } else if lists_walls {
+        // Listed in the chunks instead
+        Cow::Owned(Lvl {
+            walls: Vec::new(),
+            dynamic_walls: Vec::new(),
+            rects: Vec::new(),
+            wall_paths: Vec::new(),
+            edge_walls: Vec::new(),
+            ..lvl.clone()
+        })
+    } else {
+        Cow::Owned(Lvl {
+            walls: Vec::new(),
+            rects: Vec::new(),
+            wall_paths: Vec::new(),
+            collision: Vec::new(),
+            ..lvl.clone()
+        })
+    };
+    let solution = args.embed_solution.then(|| lvl.solution());
+    if solution == Some(None) {
+        warn!("The level can't be solved, the solution is null");
+    }
+    let output = Output {
+        lvl: &listed,
+        // Rebuilt from the level so it matches the walls after every transform
+        grid: (args.emit == Emit::Grid).then(|| Grid::from_lvl(&lvl).codes()),
+        rows_rle: (args.emit == Emit::Rle).then(|| Grid::from_lvl(&lvl).row_runs()),
+        graph: (args.emit == Emit::Graph).then(|| CorridorGraph::from_grid(&Grid::from_lvl(&lvl))),
+        quadtree: (args.emit == Emit::Quadtree).then(|| QuadTree::from_grid(&Grid::from_lvl(&lvl))),
+        colliders: (args.emit == Emit::Colliders).then(|| lvl.colliders(args.collider_padding)),
+        ascii: (args.emit == Emit::Ascii).then(|| {
+            Grid::from_lvl(&lvl)
+                .to_ascii()
+                .lines()
+                .map(str::to_owned)
+                .collect()
+        }),
+        occupancy: (args.emit == Emit::Bitset).then(|| Grid::from_lvl(&lvl).to_occupancy()),
+        bit_order: (args.emit == Emit::Bitset).then(BitOrder::default),
+        difficulty: args.difficulty.then(|| lvl.difficulty_score()),
+        dead_ends: args.dead_ends.then(|| lvl.dead_ends()),
+        junctions: args.junctions.then(|| lvl.junctions()),
+        components: args.components.then(|| lvl.connected_components().into()),
+        largest_open_area: args.largest_open_area.then(|| lvl.largest_open_area()),
+        rooms: args.rooms.then(|| lvl.rooms(args.min_room_size)),
+        checkpoint_graph: args.checkpoint_graph.then(|| CheckpointGraph {
+            nodes: lvl.checkpoint_graph_nodes(),
+            adjacency: lvl.checkpoint_graph(),
+        }),
+        solution,
+        chunk_size: args.chunk_size,
+        chunks: args
+            .chunk_size
+            .filter(|_| lists_walls)
+            .map(|size| lvl.chunks(size)),
+    };
+
+    let mut json = serde_json::to_value(&output)?;
+    if !lists_walls || args.chunk_size.is_some() {
+        if let Some(object) = json.as_object_mut() {
+            for key in ["walls", "rects", "wall_paths", "collision"] {
+                object.shift_remove(key);
+            }
+        }
+    }
+    if args.compact_walls {
+        json["walls"] = serde_json::to_value(
+            lvl.walls
+                .iter()
+                .map(|wall| wall.to_array())
+                .collect::<Vec<_>>(),
+        )?;
+    }
+    if let Some(size) = args.chunk_size {
+        for key in ["start", "end"] {
+            annotate_chunk(&mut json[key], size);
+        }
+        for key in ["checkpoints", "optional_checkpoints"] {
+            if let Some(points) = json.get_mut(key).and_then(serde_json::Value::as_array_mut) {
+                points
+                    .iter_mut()
+                    .for_each(|point| annotate_chunk(point, size));
             }
+        }
+    }
+    if args.no_orientation {
+        strip_orientation(&mut json);
+        if let Some(chunks) = json
+            .get_mut("chunks")
+            .and_then(serde_json::Value::as_array_mut)
+        {
+            chunks.iter_mut().for_each(strip_orientation);
+        }
+    }
 
-            y += 1
+    // Built first so a source map that's too big fails before anything is
+    // written
+    let source_map = args
+        .source_map
+        .as_deref()
+        .map(|path| (path, lvl.source_map()));
+    let inverse_source_map = args
+        .inverse_source_map
+        .as_deref()
+        .map(|path| (path, lvl.inverse_source_map()));
+    let sizes = [
+        ("source map", source_map.as_ref().map(|(_, map)| map.len())),
+        (
+            "inverse source map",
+            inverse_source_map
+                .as_ref()
+                .map(|(_, walls)| walls.iter().map(Vec::len).sum()),
+        ),
+    ];
+    for (name, size) in sizes {
+        if let Some(size) = size.filter(|size| *size > args.max_source_map_size) {
+            return Err(format!(
+                "the {name} has {size} squares, more than --max-source-map-size {}",
+                args.max_source_map_size
+            )
+            .into());
         }
+    }
 
-        x += 1;
+    write_output(&args, &output, Some(&lvl), &json)?;
+    if let Some((path, map)) = source_map {
+        write_source_map(path, &map)?;
+    }
+    if let Some((path, walls)) = inverse_source_map {
+        write_atomically(path, |writer| Ok(serde_json::to_writer(writer, &walls)?))?;
     }
+    Ok(())
+}
 
-    // Filter single block horizontal_walls that are in multi block vertical walls
-    let mut walls: Vec<Wall> = horizontal_walls
+/// Write the `--source-map` as `{"x,y": index}`, in reading order so the
+/// file is the same for the same level.
+fn write_source_map(path: &Path, map: &HashMap<(u32, u32), usize>) -> Result<(), Box<dyn Error>> {
+    let mut squares: Vec<_> = map.iter().collect();
+    squares.sort_by_key(|((x, y), _)| (y, x));
+    let json: serde_json::Map<String, serde_json::Value> = squares
         .into_iter()
-        .filter(|h_wall| {
-            h_wall.end.is_some()
-                || !check_if_point_is_wall(h_wall.start.x, h_wall.start.y, &vertical_walls)
-        })
+        .map(|((x, y), index)| (format!("{x},{y}"), (*index).into()))
         .collect();
+    write_atomically(path, |writer| Ok(serde_json::to_writer(writer, &json)?))
+}
 
-    walls.append(&mut vertical_walls);
+/// Remove the `orientation` of the walls and dynamic walls listed in `json`.
+fn strip_orientation(json: &mut serde_json::Value) {
+    for key in ["walls", "dynamic_walls"] {
+        let Some(walls) = json.get_mut(key).and_then(serde_json::Value::as_array_mut) else {
+            continue;
+        };
+        for wall in walls
+            .iter_mut()
+            .filter_map(serde_json::Value::as_object_mut)
+        {
+            wall.shift_remove("orientation");
+        }
+    }
+}
 
-    walls.sort();
-    walls.reverse();
+/// Add the `cx` and `cy` of the chunk a serialized point is in.
+fn annotate_chunk(point: &mut serde_json::Value, size: u32) {
+    for (axis, chunk) in [("x", "cx"), ("y", "cy")] {
+        if let Some(value) = point[axis].as_u64() {
+            point[chunk] = (value / u64::from(size)).into();
+        }
+    }
+}
 
-    lvl.walls = walls;
+/// JSON Schema of [`Lvl`], with the walls changed the way `--compact-walls`
+/// and `--no-orientation` change them in the output.
+#[cfg(feature = "schema")]
+fn level_schema(
+    compact_walls: bool,
+    no_orientation: bool,
+) -> serde_json::Result<serde_json::Value> {
+    let mut schema = serde_json::to_value(schemars::schema_for!(Lvl))?;
+    if no_orientation {
+        for definition in ["Wall", "DynamicWall"] {
+            let definition = &mut schema["$defs"][definition];
+            if let Some(properties) = definition["properties"].as_object_mut() {
+                properties.shift_remove("orientation");
+            }
+            if let Some(required) = definition["required"].as_array_mut() {
+                required.retain(|name| name != "orientation");
+            }
+        }
+    }
+    if compact_walls {
+        let coordinate = serde_json::json!({
+            "type": "integer",
+            "format": "uint32",
+            "minimum": 0,
+        });
+        schema["properties"]["walls"]["items"] = serde_json::json!({
+            "description": "`[x1, y1, x2, y2]`, single squares repeat their start as the end",
+            "type": "array",
+            "prefixItems": [coordinate, coordinate, coordinate, coordinate],
+            "items": false,
+            "minItems": 4,
+        });
+    }
+    Ok(schema)
+}
 
-    if let Some(outfile) = args.outfile {
-        let handle = OpenOptions::new().write(true).create(true).open(outfile)?;
-        let writer = BufWriter::new(handle);
-        if args.pretty {
-            serde_json::to_writer_pretty(writer, &lvl)?;
-        } else {
-            serde_json::to_writer(writer, &lvl)?;
+/// Flags that can't be combined because of the value of another one, like
+/// `--grid hex`, which clap's `conflicts_with` can't express. Rules between
+/// flags that are either given or not are on [`Args`].
+fn check_combinations(args: &Args, format: Format) -> Result<(), Box<dyn Error>> {
+    if matches!(
+        format,
+        Format::Bin | Format::Tmx | Format::Ldtk | Format::Godot
+    ) {
+        let unsupported: Vec<&str> = [
+            ("--grid hex", args.grid == GridShape::Hex),
+            ("--emit", args.emit != Emit::Walls),
+            (
+                "--mesh",
+                match format {
+                    Format::Bin => !matches!(args.mesh, Mesh::Lines | Mesh::Rect),
+                    Format::Godot => matches!(args.mesh, Mesh::Contour | Mesh::Marching),
+                    _ => false,
+                },
+            ),
+            (
+                "--wall-model edges",
+                format == Format::Godot && args.wall_model == WallModel::Edges,
+            ),
+            ("--difficulty", args.difficulty),
+            ("--dead-ends", args.dead_ends),
+            ("--junctions", args.junctions),
+            ("--components", args.components),
+            ("--largest-open-area", args.largest_open_area),
+            ("--rooms", args.rooms),
+            ("--checkpoint-graph", args.checkpoint_graph),
+            ("--embed-solution", args.embed_solution),
+            ("--source-map", args.source_map.is_some()),
+            ("--inverse-source-map", args.inverse_source_map.is_some()),
+            ("--diff", args.diff.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(flag, used)| used.then_some(flag))
+        .collect();
+        if !unsupported.is_empty() {
+            return Err(format!(
+                "--format {} only holds the level and can't be combined with {}",
+                match format {
+                    Format::Bin => "bin",
+                    Format::Tmx => "tmx",
+                    Format::Ldtk => "ldtk",
+                    _ => "godot",
+                },
+                unsupported.join(", ")
+            )
+            .into());
+        }
+    }
+    if args.grid == GridShape::Hex {
+        let unsupported: Vec<&str> = [
+            ("--merge", args.merge.is_some()),
+            ("--tile", args.tile.is_some()),
+            ("--normalize", args.normalize),
+            ("--mirror", args.mirror.is_some()),
+            ("--rotate", args.rotate.is_some()),
+            ("--wrap", args.wrap.is_some()),
+            ("--sort-by", args.sort_by.is_some()),
+            ("--checkpoint-sort", args.checkpoint_sort.is_some()),
+            ("--canonical", args.canonical),
+            ("--crop-origin-offset", args.crop_origin_offset),
+            ("--emit", args.emit != Emit::Walls),
+            ("--max-width", args.max_width.is_some()),
+            ("--wall-model", args.wall_model != WallModel::Cells),
+            ("--mesh", args.mesh != Mesh::Lines),
+            ("--compact-walls", args.compact_walls),
+            ("--chunk-size", args.chunk_size.is_some()),
+            ("--no-overlap", args.no_overlap),
+            (
+                "--merge-strategy",
+                args.merge_strategy != MergeStrategy::HorizontalFirst,
+            ),
+            ("--diagonals", args.diagonals),
+            ("--optimize-walls", args.optimize_walls),
+            ("--hollow", args.hollow),
+            ("--difficulty", args.difficulty),
+            ("--dead-ends", args.dead_ends),
+            ("--junctions", args.junctions),
+            ("--components", args.components),
+            ("--largest-open-area", args.largest_open_area),
+            ("--rooms", args.rooms),
+            ("--auto-border", args.auto_border),
+            ("--require-closed-border", args.require_closed_border),
+            ("--strict-boundary", args.strict_boundary),
+            ("--checkpoint-graph", args.checkpoint_graph),
+            ("--report-overlaps", args.report_overlaps),
+            ("--diff", args.diff.is_some()),
+            ("--preview", args.preview),
+        ]
+        .into_iter()
+        .filter_map(|(flag, used)| used.then_some(flag))
+        .collect();
+        if !unsupported.is_empty() {
+            return Err(format!(
+                "--grid hex can't be combined with {}",
+                unsupported.join(", ")
+            )
+            .into());
+        }
+    }
+    // Edge walls have sides that these would have to turn, crop offsets are
+    // in image squares instead of cells and the solver walks the squares
+    // without knowing about edges
+    if args.wall_model == WallModel::Edges
+        && (args.mirror.is_some()
+            || args.rotate.is_some()
+            || args.wrap.is_some()
+            || args.crop_origin_offset
+            || args.auto_border
+            || args.embed_solution)
+    {
+        return Err(
+            "--wall-model edges can't be combined with --mirror, --rotate, --wrap, \
+             --crop-origin-offset, --auto-border or --embed-solution"
+                .into(),
+        );
+    }
+    if args.chunk_size.is_some() && matches!(args.mesh, Mesh::Contour | Mesh::Marching) {
+        return Err(
+            "--chunk-size can't split the collision polygons of --mesh contour or marching".into(),
+        );
+    }
+    // Source maps index the `walls` in the output
+    if (args.source_map.is_some() || args.inverse_source_map.is_some())
+        && (args.wall_model == WallModel::Edges
+            || args.mesh != Mesh::Lines
+            || !(args.emit == Emit::Walls || args.with_walls))
+    {
+        return Err(
+            "--source-map and --inverse-source-map need the walls of --mesh lines in the output, \
+             not edge walls or an --emit without --with-walls"
+                .into(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Fail with [`WallLimitExceeded`] when `count` is over `--max-walls`.
+fn check_wall_limit(
+    args: &Args,
+    count: usize,
+    kind: &'static str,
+) -> Result<(), WallLimitExceeded> {
+    let Some(limit) = args.max_walls.filter(|limit| count > *limit) else {
+        return Ok(());
+    };
+
+    let suggestions = [
+        (
+            "--optimize-walls",
+            args.mesh == Mesh::Lines && !args.optimize_walls,
+        ),
+        ("--mesh rect", args.mesh != Mesh::Rect),
+    ];
+    let hex_or_edges = args.grid == GridShape::Hex || args.wall_model == WallModel::Edges;
+    Err(WallLimitExceeded {
+        count,
+        limit,
+        kind,
+        suggestions: suggestions
+            .into_iter()
+            .filter(|(_, useful)| *useful && !hex_or_edges)
+            .map(|(flag, _)| flag)
+            .collect(),
+    })
+}
+
+/// Write the json, or the `output` it was made from for RON, to the outfile
+/// or stdout in the `--format`, compressed with `--compress`.
+#[cfg_attr(not(feature = "ron"), allow(unused_variables))]
+fn write_output(
+    args: &Args,
+    output: &impl Serialize,
+    lvl: Option<&Lvl>,
+    json: &serde_json::Value,
+) -> Result<(), Box<dyn Error>> {
+    let format = output_format(args);
+    match format {
+        Format::Yaml | Format::Tmx | Format::Godot if args.pretty => {
+            return Err(
+                "--pretty only applies to json, toml, ron and ldtk, yaml, tmx and godot have a \
+                 fixed layout"
+                    .into(),
+            );
+        }
+        Format::Msgpack | Format::Cbor | Format::Bin if args.pretty => {
+            return Err(
+                "--pretty only applies to json, toml, ron and ldtk, not to binary formats".into(),
+            );
         }
+        Format::Json => (),
+        _ if args.canonical => {
+            return Err("--canonical only applies to json".into());
+        }
+        Format::Ron | Format::Bin | Format::Tmx | Format::Ldtk | Format::Godot
+            if args.compact_walls
+                || args.no_orientation
+                || args.chunk_size.is_some()
+                || args.field_case != FieldCase::Snake =>
+        {
+            return Err(
+                "--compact-walls, --no-orientation, --chunk-size and --field-case only reshape \
+                 the json, ron, bin, tmx, ldtk and godot are written from the level"
+                    .into(),
+            );
+        }
+        _ => (),
+    }
+    let renamed;
+    let json = if args.field_case == FieldCase::Snake {
+        json
     } else {
-        let handle = std::io::stdout();
-        let writer = BufWriter::new(handle);
-        if args.pretty {
-            serde_json::to_writer_pretty(writer, &lvl)?;
-        } else {
-            serde_json::to_writer(writer, &lvl)?;
+        let mut json = json.clone();
+        args.field_case.rename_keys(&mut json);
+        renamed = json;
+        &renamed
+    };
+    let compressor = output_compressor(args);
+    if compressor == Compressor::Gzip && args.compress_level.is_some_and(|level| level > 9) {
+        return Err("gzip compression levels go from 0 to 9".into());
+    }
+    if compressor == Compressor::Zstd && args.compress_level == Some(0) {
+        return Err("zstd compression levels go from 1 to 22".into());
+    }
+
+    let write = |writer: &mut dyn Write| -> Result<(), Box<dyn Error>> {
+        match format {
+            Format::Json if args.canonical => {
+                let mut json = json.clone();
+                json.sort_all_objects();
+                write_json(&mut *writer, &json, true, args.compact_walls)?;
+                writer.write_all(b"\n")?;
+            }
+            Format::Json => write_json(writer, json, args.pretty, args.compact_walls)?,
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_writer(writer, json)?,
+            #[cfg(feature = "toml")]
+            Format::Toml => {
+                let mut json = json.clone();
+                fill_single_square_ends(&mut json, args.field_case);
+                let toml = if args.pretty {
+                    toml::to_string_pretty(&json)?
+                } else {
+                    toml::to_string(&json)?
+                };
+                writer.write_all(toml.as_bytes())?;
+            }
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => rmp_serde::encode::write_named(writer, json)?,
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::into_writer(json, writer)?,
+            #[cfg(feature = "ron")]
+            Format::Ron => {
+                let ron = if args.pretty {
+                    ron::ser::to_string_pretty(output, PrettyConfig::default())?
+                } else {
+                    ron::to_string(output)?
+                };
+                writer.write_all(ron.as_bytes())?;
+            }
+            Format::Bin => writer.write_all(
+                &lvl.expect("only levels are written as bin, not hex levels or diffs")
+                    .to_bin(),
+            )?,
+            Format::Tmx => writer.write_all(
+                lvl.expect("only levels are written as tmx, not hex levels or diffs")
+                    .to_tmx(args.cell_size.unwrap_or(1))
+                    .as_bytes(),
+            )?,
+            Format::Godot => writer.write_all(
+                lvl.expect("only levels are written for godot, not hex levels or diffs")
+                    .to_godot()
+                    .as_bytes(),
+            )?,
+            Format::Ldtk => {
+                let project = lvl
+                    .expect("only levels are written as ldtk, not hex levels or diffs")
+                    .to_ldtk(args.cell_size.unwrap_or(1));
+                if args.pretty {
+                    serde_json::to_writer_pretty(writer, &project)?;
+                } else {
+                    serde_json::to_writer(writer, &project)?;
+                }
+            }
+            #[cfg(not(all(
+                feature = "yaml",
+                feature = "toml",
+                feature = "msgpack",
+                feature = "cbor",
+                feature = "ron"
+            )))]
+            _ => unreachable!("run() rejects formats that aren't built in"),
         }
+        Ok(())
+    };
+    let compress = |writer: &mut dyn Write| -> Result<(), Box<dyn Error>> {
+        match compressor {
+            Compressor::Gzip => {
+                let level = Compression::new(args.compress_level.unwrap_or(9));
+                let mut encoder = GzEncoder::new(writer, level);
+                write(&mut encoder)?;
+                encoder.finish()?;
+            }
+            Compressor::Zstd => {
+                let level = args.compress_level.unwrap_or(19);
+                let mut encoder = zstd::Encoder::new(writer, i32::try_from(level)?)?;
+                write(&mut encoder)?;
+                encoder.finish()?;
+            }
+            Compressor::None => write(writer)?,
+        }
+        Ok(())
     };
 
+    let Some(outfile) = &args.outfile else {
+        let mut stdout = BufWriter::new(std::io::stdout());
+        compress(&mut stdout)?;
+        stdout.flush()?;
+        return Ok(());
+    };
+    let mut outfile = outfile.clone();
+    if let Some(compressed) = compressor.extension().filter(|compressed| {
+        outfile
+            .extension()
+            .is_none_or(|extension| extension != *compressed)
+    }) {
+        outfile.as_mut_os_string().push(format!(".{compressed}"));
+    }
+    write_atomically(&outfile, compress)
+}
+
+/// Write to `<outfile>.tmp` and rename it over the outfile once it's on
+/// disk, so a crash never leaves a partial file behind. Replacing a file by
+/// renaming isn't guaranteed to be atomic on Windows.
+fn write_atomically(
+    outfile: &Path,
+    write: impl Fn(&mut dyn Write) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut temporary = outfile.to_path_buf();
+    temporary.as_mut_os_string().push(".tmp");
+    let written = (|| -> Result<(), Box<dyn Error>> {
+        let mut file = BufWriter::new(File::create(&temporary)?);
+        write(&mut file)?;
+        file.into_inner()
+            .map_err(IntoInnerError::into_error)?
+            .sync_all()?;
+        fs::rename(&temporary, outfile)?;
+        Ok(())
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    written
+}
+
+/// `maze-lvl-maker render`, an SVG or a PNG of a scanned image or a written
+/// level.
+fn render_level(render: &RenderArgs) -> Result<(), Box<dyn Error>> {
+    let lvl = if let Some(image) = &render.image {
+        scan_grid(&read_image(image, None, 1)?, ScanOptions::default())
+    } else if let Some(lvl) = &render.lvl {
+        read_lvl(lvl)?
+    } else {
+        unreachable!("clap requires a level")
+    };
+    // Levels read back from a file can point anywhere
+    lvl.check_bounds().map_err(|error| error.to_string())?;
+
+    if let Some(png) = &render.png {
+        let mut bytes = Vec::new();
+        Grid::from_lvl(&lvl)
+            .to_image(render.cell_size)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+        return write_atomically(png, |writer| Ok(writer.write_all(&bytes)?));
+    }
+    let svg = lvl.to_svg(render.scale);
+    let Some(outfile) = render
+        .outfile
+        .as_ref()
+        .filter(|outfile| *outfile != Path::new("-"))
+    else {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(svg.as_bytes())?;
+        stdout.flush()?;
+        return Ok(());
+    };
+    write_atomically(outfile, |writer| Ok(writer.write_all(svg.as_bytes())?))
+}
+
+/// `maze-lvl-maker upgrade`, every file is read before any is rewritten so
+/// a file that can't be upgraded leaves all of them untouched.
+fn upgrade_levels(upgrade: &UpgradeArgs) -> Result<(), Box<dyn Error>> {
+    let mut levels = Vec::new();
+    for path in &upgrade.files {
+        let lvl = read_lvl(path).map_err(|error| format!("{}: {error}", path.display()))?;
+        levels.push((path, lvl));
+    }
+
+    for (path, mut lvl) in levels {
+        if lvl.format_version == FORMAT_VERSION {
+            continue;
+        }
+        lvl.upgrade();
+        let json = if upgrade.pretty {
+            serde_json::to_vec_pretty(&lvl)?
+        } else {
+            serde_json::to_vec(&lvl)?
+        };
+        write_atomically(path, |writer| {
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some("gz") => {
+                    let mut encoder = GzEncoder::new(writer, Compression::new(9));
+                    encoder.write_all(&json)?;
+                    encoder.finish()?;
+                }
+                Some("zst") => zstd::stream::copy_encode(json.as_slice(), writer, 19)?,
+                _ => writer.write_all(&json)?,
+            }
+            Ok(())
+        })?;
+    }
     Ok(())
 }
+
+/// `--format`, or the format the outfile's extension stands for.
+fn output_format(args: &Args) -> Format {
+    args.format.unwrap_or_else(|| {
+        let outfile = args.outfile.as_deref().map(|outfile| {
+            // Look past the `.gz` or `.zst` of compressed outfiles
+            match outfile.extension() {
+                Some(extension) if extension == "gz" || extension == "zst" => {
+                    Path::new(outfile.file_stem().unwrap_or_default())
+                }
+                _ => outfile,
+            }
+        });
+        match outfile.and_then(Path::extension) {
+            Some(extension) if extension == "yaml" || extension == "yml" => Format::Yaml,
+            Some(extension) if extension == "toml" => Format::Toml,
+            Some(extension) if extension == "msgpack" || extension == "mpk" => Format::Msgpack,
+            Some(extension) if extension == "cbor" => Format::Cbor,
+            Some(extension) if extension == "ron" => Format::Ron,
+            Some(extension) if extension == "tmx" => Format::Tmx,
+            Some(extension) if extension == "ldtk" => Format::Ldtk,
+            Some(extension) if extension == "tres" => Format::Godot,
+            _ => Format::Json,
+        }
+    })
+}
+
+/// `--compress`, or the compression the outfile's extension stands for.
+fn output_compressor(args: &Args) -> Compressor {
+    args.compress
+        .unwrap_or_else(|| match args.outfile.as_deref().and_then(Path::extension) {
+            Some(extension) if extension == "gz" => Compressor::Gzip,
+            Some(extension) if extension == "zst" => Compressor::Zstd,
+            _ => Compressor::None,
+        })
+}
+
+/// Replace the null `end` of single square walls with their `start`, for
+/// formats without null.
+#[cfg(feature = "toml")]
+fn fill_single_square_ends(value: &mut serde_json::Value, case: FieldCase) {
+    match value {
+        serde_json::Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| fill_single_square_ends(value, case)),
+        serde_json::Value::Object(object) => {
+            let (start, end) = (case.convert("start"), case.convert("end"));
+            if object.get(&end).is_some_and(serde_json::Value::is_null) {
+                if let Some(start) = object.get(&start).cloned() {
+                    object.insert(end, start);
+                }
+            }
+            object
+                .values_mut()
+                .for_each(|value| fill_single_square_ends(value, case));
+        }
+        _ => (),
+    }
+}
+
+/// Parse `--scale` as either `1/<factor>` or just `<factor>`.
+fn parse_scale(value: &str) -> Result<u32, String> {
+    let factor = value.strip_prefix("1/").unwrap_or(value);
+    match factor.trim().parse() {
+        Ok(0) | Err(_) => Err(format!("expected a factor like 1/4 or 4, got {value}")),
+        Ok(factor) => Ok(factor),
+    }
+}
+
+/// Parse `--sort-by`.
+fn parse_sort_key(value: &str) -> Result<WallSortKey, String> {
+    match value {
+        "length" => Ok(WallSortKey::Length),
+        "position" => Ok(WallSortKey::Position),
+        "horizontal-first" => Ok(WallSortKey::Orientation(OrientationFirst::Horizontal)),
+        "vertical-first" => Ok(WallSortKey::Orientation(OrientationFirst::Vertical)),
+        _ => Err(format!(
+            "expected length, position, horizontal-first or vertical-first, got {value}"
+        )),
+    }
+}
+
+/// Parse `--merge-offset` as `<x>,<y>`.
+fn parse_point(value: &str) -> Result<Point, String> {
+    let numbers: Vec<u32> = value
+        .split(',')
+        .map(|number| number.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|error| format!("{value} isn't a list of numbers: {error}"))?;
+    match numbers[..] {
+        [x, y] => Ok(Point::new(x, y)),
+        _ => Err(format!("expected <x>,<y>, got {value}")),
+    }
+}
+
+/// Parse `--tile` as `<nx>x<ny>`.
+fn parse_tile(value: &str) -> Result<(u32, u32), String> {
+    let counts = value
+        .split_once('x')
+        .and_then(|(nx, ny)| Some((nx.trim().parse().ok()?, ny.trim().parse().ok()?)));
+    match counts {
+        Some((nx, ny)) if nx > 0 && ny > 0 => Ok((nx, ny)),
+        _ => Err(format!("expected <nx>x<ny> like 2x3, got {value}")),
+    }
+}
+
+/// Parse `--crop` as `<x>,<y>,<width>,<height>`.
+fn parse_crop(value: &str) -> Result<Rect, String> {
+    let numbers: Vec<u32> = value
+        .split(',')
+        .map(|number| number.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|error| format!("{value} isn't a list of numbers: {error}"))?;
+    match numbers[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok(Rect {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => Err(format!("expected <x>,<y>,<width>,<height>, got {value}")),
+    }
+}
+
+/// Decode an image, cut down to `crop` and shrunk by `scale` with nearest
+/// neighbor sampling.
+fn read_image(path: &Path, crop: Option<Rect>, scale: u32) -> Result<Grid, Box<dyn Error>> {
+    let mut image = ImageReader::open(path)?.decode()?;
+    if let Some(crop) = crop {
+        if crop.x + crop.width > image.width() || crop.y + crop.height > image.height() {
+            return Err(format!(
+                "crop {}x{} at {},{} is outside of {} ({}x{})",
+                crop.width,
+                crop.height,
+                crop.x,
+                crop.y,
+                path.display(),
+                image.width(),
+                image.height()
+            )
+            .into());
+        }
+        image = image.crop_imm(crop.x, crop.y, crop.width, crop.height);
+    }
+    if scale > 1 {
+        if image.width() % scale != 0 || image.height() % scale != 0 {
+            warn!(
+                "{} is {}x{}, which isn't a multiple of the scale {scale}",
+                path.display(),
+                image.width(),
+                image.height()
+            );
+        }
+
+        let width = (image.width() / scale).max(1);
+        let height = (image.height() / scale).max(1);
+        image = imageops::resize(&image, width, height, FilterType::Nearest).into();
+    }
+
+    Ok(Grid::try_from_image(&image)?)
+}
+
+fn write_json(
+    writer: impl Write,
+    output: &serde_json::Value,
+    pretty: bool,
+    compact_walls: bool,
+) -> serde_json::Result<()> {
+    if pretty && compact_walls {
+        let mut serializer =
+            serde_json::Serializer::with_formatter(writer, InlineNestedArrays::default());
+        output.serialize(&mut serializer)
+    } else if pretty {
+        serde_json::to_writer_pretty(writer, output)
+    } else {
+        serde_json::to_writer(writer, output)
+    }
+}
+
+/// Pretty printing that keeps arrays nested in arrays on a single line, so
+/// every compact wall gets a line of its own.
+#[derive(Default)]
+struct InlineNestedArrays {
+    indent: usize,
+    has_value: bool,
+    /// Open containers, `true` for arrays
+    containers: Vec<bool>,
+    /// Containers opened since the current line went inline
+    inline: usize,
+}
+
+impl InlineNestedArrays {
+    fn newline(&self, writer: &mut (impl Write + ?Sized)) -> std::io::Result<()> {
+        writer.write_all(b"\n")?;
+        for _ in 0..self.indent {
+            writer.write_all(b"  ")?;
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self, writer: &mut (impl Write + ?Sized), array: bool) -> std::io::Result<()> {
+        if self.inline > 0 || self.containers.last() == Some(&true) && array {
+            self.inline += 1;
+        } else {
+            self.indent += 1;
+            self.has_value = false;
+        }
+        self.containers.push(array);
+        writer.write_all(if array { b"[" } else { b"{" })
+    }
+
+    fn end(&mut self, writer: &mut (impl Write + ?Sized)) -> std::io::Result<()> {
+        let array = self.containers.pop() == Some(true);
+        if self.inline > 0 {
+            self.inline -= 1;
+        } else {
+            self.indent -= 1;
+            if self.has_value {
+                self.newline(writer)?;
+            }
+        }
+        writer.write_all(if array { b"]" } else { b"}" })
+    }
+
+    fn begin_value(&self, writer: &mut (impl Write + ?Sized), first: bool) -> std::io::Result<()> {
+        if !first {
+            writer.write_all(b",")?;
+        }
+        if self.inline == 0 {
+            self.newline(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Formatter for InlineNestedArrays {
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.begin(writer, true)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.end(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> std::io::Result<()> {
+        self.begin_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> std::io::Result<()> {
+        self.has_value = true;
+        Ok(())
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.begin(writer, false)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.end(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> std::io::Result<()> {
+        self.begin_value(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(if self.inline > 0 { b":" } else { b": " })
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> std::io::Result<()> {
+        self.has_value = true;
+        Ok(())
+    }
+}
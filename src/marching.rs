@@ -0,0 +1,308 @@
+//! Smoothed wall outlines with marching squares.
+//!
+//! Every square is sampled at its center and the outline runs through the
+//! middle between a wall sample and anything else, so corners are cut
+//! diagonally instead of following the square sides. The polygons use the
+//! same corner coordinates and winding as [`contours`](crate::contours):
+//! outer boundaries counter-clockwise on screen, holes clockwise, the wall
+//! always on the left. Diagonal wall squares stay in separate polygons.
+//!
+//! With a tolerance the rings are simplified with Douglas-Peucker, but a
+//! shortcut is only taken when every dropped vertex is on the open side of
+//! it and no other vertex of any outline is between the shortcut and the
+//! dropped ones. The outline can only move into the wall, never into a
+//! corridor.
+
+use std::collections::HashMap;
+
+use crate::{
+    contour::{label_areas, signed_area},
+    Grid, Polygon, Vertex,
+};
+
+/// Sample corners of a marching cell relative to its center corner.
+const TOP_LEFT: (i64, i64) = (-1, -1);
+const TOP_RIGHT: (i64, i64) = (0, -1);
+const BOTTOM_RIGHT: (i64, i64) = (0, 0);
+const BOTTOM_LEFT: (i64, i64) = (-1, 0);
+
+/// Middle of a cell side in doubled coordinates relative to twice the
+/// center corner.
+const TOP: (i64, i64) = (0, -1);
+const RIGHT: (i64, i64) = (1, 0);
+const BOTTOM: (i64, i64) = (0, 1);
+const LEFT: (i64, i64) = (-1, 0);
+
+type Segment = ((i64, i64), (i64, i64), (i64, i64));
+
+/// Directed segments per cell case, with one wall sample on their left.
+/// Bits are top left 8, top right 4, bottom right 2 and bottom left 1.
+/// The saddles 5 and 10 keep the diagonal walls apart.
+const CASES: [&[Segment]; 16] = [
+    &[],
+    &[(BOTTOM, LEFT, BOTTOM_LEFT)],
+    &[(RIGHT, BOTTOM, BOTTOM_RIGHT)],
+    &[(RIGHT, LEFT, BOTTOM_LEFT)],
+    &[(TOP, RIGHT, TOP_RIGHT)],
+    &[(TOP, RIGHT, TOP_RIGHT), (BOTTOM, LEFT, BOTTOM_LEFT)],
+    &[(TOP, BOTTOM, TOP_RIGHT)],
+    &[(TOP, LEFT, BOTTOM_RIGHT)],
+    &[(LEFT, TOP, TOP_LEFT)],
+    &[(BOTTOM, TOP, TOP_LEFT)],
+    &[(LEFT, TOP, TOP_LEFT), (RIGHT, BOTTOM, BOTTOM_RIGHT)],
+    &[(RIGHT, TOP, TOP_LEFT)],
+    &[(LEFT, RIGHT, TOP_LEFT)],
+    &[(BOTTOM, RIGHT, TOP_LEFT)],
+    &[(LEFT, BOTTOM, TOP_LEFT)],
+    &[],
+];
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    from: (i64, i64),
+    to: (i64, i64),
+    area: usize,
+}
+
+/// Trace the marching squares outline of every wall area and simplify it
+/// with `tolerance` in squares, `0.0` keeps every vertex.
+///
+/// Polygons are in the order their top left square is found in reading
+/// order.
+pub fn marching_squares(grid: &Grid, tolerance: f64) -> Vec<Polygon> {
+    let (areas, area_count) = label_areas(grid);
+    let area_at = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= i64::from(grid.width()) || y >= i64::from(grid.height()) {
+            return None;
+        }
+        areas[grid.index(x as u32, y as u32)]
+    };
+
+    let mut edges = Vec::new();
+    for y in 0..=i64::from(grid.height()) {
+        for x in 0..=i64::from(grid.width()) {
+            let case = [TOP_LEFT, TOP_RIGHT, BOTTOM_RIGHT, BOTTOM_LEFT]
+                .into_iter()
+                .fold(0, |case, (dx, dy)| {
+                    case << 1 | usize::from(area_at(x + dx, y + dy).is_some())
+                });
+
+            for &(from, to, (dx, dy)) in CASES[case] {
+                let area = area_at(x + dx, y + dy).expect("sample on the left is a wall");
+                edges.push(Edge {
+                    from: (2 * x + from.0, 2 * y + from.1),
+                    to: (2 * x + to.0, 2 * y + to.1),
+                    area,
+                });
+            }
+        }
+    }
+
+    // The outline crosses every cell side at most once, so every point
+    // starts at most one edge
+    let starting: HashMap<(i64, i64), usize> = edges
+        .iter()
+        .enumerate()
+        .map(|(index, edge)| (edge.from, index))
+        .collect();
+
+    let mut rings = Vec::new();
+    let mut visited = vec![false; edges.len()];
+    for start in 0..edges.len() {
+        if visited[start] {
+            continue;
+        }
+
+        let mut ring = Vec::new();
+        let mut current = start;
+        loop {
+            visited[current] = true;
+            let following = starting[&edges[current].to];
+            if direction(edges[current]) != direction(edges[following]) {
+                let (x, y) = edges[current].to;
+                ring.push(Vertex {
+                    x: x as f64 / 2.0,
+                    y: y as f64 / 2.0,
+                });
+            }
+
+            current = following;
+            if current == start {
+                break;
+            }
+        }
+
+        rings.push((ring, edges[start].area));
+    }
+
+    let traced = Traced::new(rings.iter().map(|(ring, _)| ring));
+    let mut polygons: Vec<Polygon> = (0..area_count)
+        .map(|_| Polygon {
+            outer: Vec::new(),
+            holes: Vec::new(),
+        })
+        .collect();
+    for (ring, area) in rings {
+        let ring = simplify(ring, tolerance, &traced);
+        let polygon = &mut polygons[area];
+        if signed_area(&ring) < 0.0 {
+            polygon.outer = ring;
+        } else {
+            polygon.holes.push(ring);
+        }
+    }
+
+    polygons
+}
+
+/// Vertices of every traced ring by the square they're in.
+struct Traced(HashMap<(i64, i64), Vec<Vertex>>);
+
+impl Traced {
+    fn new<'a>(rings: impl Iterator<Item = &'a Vec<Vertex>>) -> Self {
+        let mut squares: HashMap<_, Vec<_>> = HashMap::new();
+        for &vertex in rings.flatten() {
+            squares.entry(square(vertex)).or_default().push(vertex);
+        }
+        Self(squares)
+    }
+
+    /// Whether a vertex that isn't in `chain` is inside or on the ring the
+    /// chain makes when closed by the shortcut from its last vertex to the
+    /// first.
+    fn any_between(&self, chain: &[Vertex]) -> bool {
+        let (mut min, mut max) = (square(chain[0]), square(chain[0]));
+        for (x, y) in chain.iter().map(|&vertex| square(vertex)) {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+
+        (min.1..=max.1)
+            .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+            .filter_map(|key| self.0.get(&key))
+            .flatten()
+            .any(|vertex| encloses(chain, *vertex) && !chain.contains(vertex))
+    }
+}
+
+fn square(vertex: Vertex) -> (i64, i64) {
+    (vertex.x.floor() as i64, vertex.y.floor() as i64)
+}
+
+/// Whether `point` is inside or on the closed ring.
+fn encloses(ring: &[Vertex], point: Vertex) -> bool {
+    let mut inside = false;
+    for (index, &from) in ring.iter().enumerate() {
+        let to = ring[(index + 1) % ring.len()];
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let cross = dx * (point.y - from.y) - dy * (point.x - from.x);
+        if cross == 0.0
+            && point.x >= from.x.min(to.x)
+            && point.x <= from.x.max(to.x)
+            && point.y >= from.y.min(to.y)
+            && point.y <= from.y.max(to.y)
+        {
+            return true;
+        }
+        if (from.y > point.y) != (to.y > point.y) && point.x < from.x + (point.y - from.y) * dx / dy
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn direction(edge: Edge) -> (i64, i64) {
+    (
+        (edge.to.0 - edge.from.0).signum(),
+        (edge.to.1 - edge.from.1).signum(),
+    )
+}
+
+/// Douglas-Peucker on a closed ring, split at the first vertex and the one
+/// farthest away from it. Rings that would collapse are kept as they are.
+fn simplify(ring: Vec<Vertex>, tolerance: f64, traced: &Traced) -> Vec<Vertex> {
+    if tolerance <= 0.0 || ring.len() <= 3 {
+        return ring;
+    }
+
+    let first = ring[0];
+    let (farthest, _) = ring
+        .iter()
+        .enumerate()
+        .map(|(index, vertex)| (index, (vertex.x - first.x).hypot(vertex.y - first.y)))
+        .fold((0, 0.0), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        });
+
+    let mut wrapped = ring[farthest..].to_vec();
+    wrapped.push(first);
+
+    let mut simplified = Vec::new();
+    simplify_chain(&ring[..=farthest], tolerance, traced, &mut simplified);
+    simplified.pop();
+    simplify_chain(&wrapped, tolerance, traced, &mut simplified);
+    simplified.pop();
+
+    if simplified.len() < 3 {
+        ring
+    } else {
+        simplified
+    }
+}
+
+/// Push the kept vertices of an open chain, including both ends.
+fn simplify_chain(chain: &[Vertex], tolerance: f64, traced: &Traced, kept: &mut Vec<Vertex>) {
+    let (first, last) = (chain[0], chain[chain.len() - 1]);
+    let inner = &chain[1..chain.len() - 1];
+
+    let farthest = inner
+        .iter()
+        .enumerate()
+        .map(|(index, vertex)| (index + 1, distance_to_segment(*vertex, first, last)))
+        .fold(None, |best: Option<(usize, f64)>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate),
+        });
+
+    let Some((split, distance)) = farthest else {
+        kept.push(first);
+        kept.push(last);
+        return;
+    };
+
+    if distance <= tolerance
+        && inner.iter().all(|vertex| !is_left(*vertex, first, last))
+        && !traced.any_between(chain)
+    {
+        kept.push(first);
+        kept.push(last);
+        return;
+    }
+
+    simplify_chain(&chain[..=split], tolerance, traced, kept);
+    kept.pop();
+    simplify_chain(&chain[split..], tolerance, traced, kept);
+}
+
+/// Whether `point` is strictly on the wall side of the line `from` to `to`.
+fn is_left(point: Vertex, from: Vertex, to: Vertex) -> bool {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    dx * (point.y - from.y) - dy * (point.x - from.x) < 0.0
+}
+
+fn distance_to_segment(point: Vertex, from: Vertex, to: Vertex) -> f64 {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point.x - from.x) * dx + (point.y - from.y) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+
+    (point.x - (from.x + t * dx)).hypot(point.y - (from.y + t * dy))
+}
@@ -0,0 +1,182 @@
+use serde::Deserialize;
+
+use crate::{Grid, Point, Rect, SquareType};
+
+/// How the wall squares are described in the output.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Mesh {
+    /// Horizontal and vertical runs in `walls`
+    #[default]
+    Lines,
+    /// Non-overlapping rectangles in `rects`
+    Rect,
+    /// Chains of runs in `wall_paths`
+    Polyline,
+    /// Boundary polygons with holes in `collision`
+    Contour,
+    /// Marching squares outlines in `collision`, see `--simplify`
+    Marching,
+}
+
+/// Cover the wall squares with non-overlapping rectangles.
+///
+/// Greedy: starting from the first uncovered wall square in reading order,
+/// grow as far right as possible, then grow down for as long as the whole
+/// row below is uncovered wall.
+pub fn greedy_rects(grid: &Grid) -> Vec<Rect> {
+    let width = grid.width();
+    let height = grid.height();
+    let mut covered = vec![false; grid.square_count()];
+    let free =
+        |covered: &[bool], x, y| grid.get(x, y) == SquareType::Wall && !covered[grid.index(x, y)];
+
+    let mut rects = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if !free(&covered, x, y) {
+                continue;
+            }
+
+            let mut right = x;
+            while right + 1 < width && free(&covered, right + 1, y) {
+                right += 1;
+            }
+            let mut bottom = y;
+            while bottom + 1 < height && (x..=right).all(|x| free(&covered, x, bottom + 1)) {
+                bottom += 1;
+            }
+
+            for covered_y in y..=bottom {
+                for covered_x in x..=right {
+                    covered[grid.index(covered_x, covered_y)] = true;
+                }
+            }
+            rects.push(Rect {
+                x,
+                y,
+                width: right - x + 1,
+                height: bottom - y + 1,
+            });
+        }
+    }
+
+    rects
+}
+
+/// Chain the wall squares into polylines through the square centers.
+///
+/// Only the end points and corners of a chain are listed. Chains stop at
+/// junctions, squares with three or four wall neighbors, so a junction is
+/// the end point of every chain that touches it. Closed loops repeat their
+/// first point at the end and a lone wall square is a single point.
+pub fn polylines(grid: &Grid) -> Vec<Vec<Point>> {
+    let mut visited = VisitedEdges::new(grid);
+    let mut paths = Vec::new();
+
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let point = Point::new(x, y);
+            if !is_wall(grid, point) || !is_node(grid, point) {
+                continue;
+            }
+
+            if wall_neighbors(grid, point).next().is_none() {
+                paths.push(vec![point]);
+            }
+            for neighbor in wall_neighbors(grid, point) {
+                if visited.visit(grid, point, neighbor) {
+                    paths.push(trace(grid, &mut visited, point, neighbor));
+                }
+            }
+        }
+    }
+
+    // Whatever is left are loops without junctions, the first square of a
+    // loop in reading order is always a corner
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let point = Point::new(x, y);
+            if !is_wall(grid, point) {
+                continue;
+            }
+
+            for neighbor in wall_neighbors(grid, point) {
+                if visited.visit(grid, point, neighbor) {
+                    paths.push(trace(grid, &mut visited, point, neighbor));
+                }
+            }
+        }
+    }
+
+    paths
+}
+
+fn is_wall(grid: &Grid, point: Point) -> bool {
+    grid.get(point.x, point.y) == SquareType::Wall
+}
+
+fn wall_neighbors(grid: &Grid, point: Point) -> impl Iterator<Item = Point> + '_ {
+    grid.neighbors(point)
+        .filter(|neighbor| is_wall(grid, *neighbor))
+}
+
+/// Ends and junctions, everything else is in the middle of a chain.
+fn is_node(grid: &Grid, point: Point) -> bool {
+    wall_neighbors(grid, point).count() != 2
+}
+
+/// Follow a chain from `start` over `next` until a node or back at `start`.
+fn trace(grid: &Grid, visited: &mut VisitedEdges, start: Point, next: Point) -> Vec<Point> {
+    let mut path = vec![start];
+    let (mut previous, mut current) = (start, next);
+    while current != start && !is_node(grid, current) {
+        let following = wall_neighbors(grid, current)
+            .find(|neighbor| *neighbor != previous)
+            .expect("chain squares have two wall neighbors");
+        visited.visit(grid, current, following);
+
+        let turns = (following.x == current.x) != (current.x == previous.x);
+        if turns {
+            path.push(current);
+        }
+        (previous, current) = (current, following);
+    }
+    path.push(current);
+
+    path
+}
+
+/// Edges between wall squares that are already part of a chain, indexed by
+/// the top or left square of the edge.
+struct VisitedEdges {
+    right: Vec<bool>,
+    down: Vec<bool>,
+}
+
+impl VisitedEdges {
+    fn new(grid: &Grid) -> Self {
+        let squares = grid.square_count();
+        Self {
+            right: vec![false; squares],
+            down: vec![false; squares],
+        }
+    }
+
+    /// Mark the edge, returns whether it wasn't visited before.
+    fn visit(&mut self, grid: &Grid, from: Point, to: Point) -> bool {
+        let first = if (from.y, from.x) < (to.y, to.x) {
+            from
+        } else {
+            to
+        };
+        let index = grid.index(first.x, first.y);
+        let visited = if from.y == to.y {
+            &mut self.right[index]
+        } else {
+            &mut self.down[index]
+        };
+
+        !std::mem::replace(visited, true)
+    }
+}
@@ -0,0 +1,128 @@
+//! Collision polygons in square corner coordinates, see
+//! [`contours`](crate::contours) for how they're traced.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Point, Rect};
+
+/// Polygon corner, outlines that cut corners put them between squares.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+pub struct Vertex {
+    /// Distance from the left edge in squares
+    pub x: f64,
+    /// Distance from the top edge in squares
+    pub y: f64,
+}
+
+impl From<Point> for Vertex {
+    fn from(value: Point) -> Self {
+        Self {
+            x: f64::from(value.x),
+            y: f64::from(value.y),
+        }
+    }
+}
+
+/// Boundary of one connected wall area.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "bevy", derive(bevy::reflect::Reflect))]
+pub struct Polygon {
+    /// Outline around the area
+    pub outer: Vec<Vertex>,
+    /// Outlines of the open areas inside of it
+    pub holes: Vec<Vec<Vertex>>,
+}
+
+impl Polygon {
+    /// Whether the square at `(x, y)` is inside the outer boundary and
+    /// outside of every hole.
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        core::iter::once(&self.outer)
+            .chain(&self.holes)
+            .filter(|ring| crosses(ring, x, y))
+            .count()
+            % 2
+            == 1
+    }
+
+    /// Part of the polygon inside `rect`, whose sides are on the corners
+    /// `rect.x` to `rect.x + rect.width`. `None` when nothing is left.
+    pub fn clip(&self, rect: Rect) -> Option<Self> {
+        let outer = clip_ring(&self.outer, rect);
+        if outer.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            outer,
+            holes: self
+                .holes
+                .iter()
+                .map(|hole| clip_ring(hole, rect))
+                .filter(|hole| !hole.is_empty())
+                .collect(),
+        })
+    }
+}
+
+/// Sutherland-Hodgman against each side of `rect`, which keeps the winding.
+fn clip_ring(ring: &[Vertex], rect: Rect) -> Vec<Vertex> {
+    let (left, top) = (f64::from(rect.x), f64::from(rect.y));
+    let (right, bottom) = (left + f64::from(rect.width), top + f64::from(rect.height));
+    // Whether the side limits x, where it is and which way is inside
+    let sides = [
+        (true, left, 1.0),
+        (true, right, -1.0),
+        (false, top, 1.0),
+        (false, bottom, -1.0),
+    ];
+
+    let mut ring = ring.to_vec();
+    for (limits_x, side, direction) in sides {
+        let inside = |vertex: Vertex| {
+            let coordinate = if limits_x { vertex.x } else { vertex.y };
+            direction * (coordinate - side)
+        };
+        let input = core::mem::take(&mut ring);
+        for (index, &to) in input.iter().enumerate() {
+            let from = input[(index + input.len() - 1) % input.len()];
+            let (from_inside, to_inside) = (inside(from), inside(to));
+            if (from_inside >= 0.0) != (to_inside >= 0.0) {
+                let t = from_inside / (from_inside - to_inside);
+                ring.push(Vertex {
+                    x: from.x + t * (to.x - from.x),
+                    y: from.y + t * (to.y - from.y),
+                });
+            }
+            if to_inside >= 0.0 {
+                ring.push(to);
+            }
+        }
+    }
+
+    ring
+}
+
+/// Whether a ray from the center of square `(x, y)` to the right crosses
+/// `ring` an odd number of times.
+fn crosses(ring: &[Vertex], x: u32, y: u32) -> bool {
+    let (center_x, center_y) = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+
+    let mut inside = false;
+    for (index, from) in ring.iter().enumerate() {
+        let to = ring[(index + 1) % ring.len()];
+        if (from.y > center_y) != (to.y > center_y) {
+            let crossing = from.x + (center_y - from.y) * (to.x - from.x) / (to.y - from.y);
+            if crossing > center_x {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
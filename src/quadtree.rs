@@ -0,0 +1,112 @@
+//! Wall occupancy as a quadtree for hierarchical collision queries.
+//!
+//! The tree covers the smallest power of two square that holds the level,
+//! the padding right of and below the level is empty. Nodes are `"full"`,
+//! `"empty"` or an array of the top left, top right, bottom left and bottom
+//! right quarter.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::{Grid, Point, SquareType};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuadNode {
+    /// Every square is a solid wall
+    Full,
+    /// No square is a solid wall
+    Empty,
+    #[serde(untagged)]
+    Split(Box<[QuadNode; 4]>),
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct QuadTree {
+    /// Side of the square covered by the root
+    pub size: u32,
+    /// Levels below the root, `0` when the root isn't split
+    pub depth: u32,
+    /// Number of nodes including the root
+    pub nodes: usize,
+    pub root: QuadNode,
+}
+
+impl QuadTree {
+    /// Build the tree over the solid walls of `grid`.
+    pub fn from_grid(grid: &Grid) -> Self {
+        let size = grid.width().max(grid.height()).max(1).next_power_of_two();
+        let root = build(grid, 0, 0, size);
+        let (depth, nodes) = measure(&root);
+
+        Self {
+            size,
+            depth,
+            nodes,
+            root,
+        }
+    }
+
+    /// Expand the tree back to the squares that are solid walls.
+    pub fn walls(&self) -> HashSet<Point> {
+        let mut walls = HashSet::new();
+        expand(&self.root, 0, 0, self.size, &mut walls);
+        walls
+    }
+}
+
+fn build(grid: &Grid, x: u32, y: u32, size: u32) -> QuadNode {
+    if size == 1 {
+        let wall = x < grid.width() && y < grid.height() && grid.get(x, y) == SquareType::Wall;
+        return if wall {
+            QuadNode::Full
+        } else {
+            QuadNode::Empty
+        };
+    }
+
+    let half = size / 2;
+    let children = [(x, y), (x + half, y), (x, y + half), (x + half, y + half)]
+        .map(|(x, y)| build(grid, x, y, half));
+    if children.iter().all(|child| *child == QuadNode::Full) {
+        QuadNode::Full
+    } else if children.iter().all(|child| *child == QuadNode::Empty) {
+        QuadNode::Empty
+    } else {
+        QuadNode::Split(Box::new(children))
+    }
+}
+
+/// Depth and node count of the subtree under `node`.
+fn measure(node: &QuadNode) -> (u32, usize) {
+    match node {
+        QuadNode::Full | QuadNode::Empty => (0, 1),
+        QuadNode::Split(children) => children.iter().map(measure).fold(
+            (0, 1),
+            |(depth, nodes), (child_depth, child_nodes)| {
+                (depth.max(child_depth + 1), nodes + child_nodes)
+            },
+        ),
+    }
+}
+
+fn expand(node: &QuadNode, x: u32, y: u32, size: u32, walls: &mut HashSet<Point>) {
+    match node {
+        QuadNode::Empty => (),
+        QuadNode::Full => {
+            for y in y..y + size {
+                for x in x..x + size {
+                    walls.insert(Point::new(x, y));
+                }
+            }
+        }
+        QuadNode::Split(children) => {
+            let half = size / 2;
+            let corners = [(x, y), (x + half, y), (x, y + half), (x + half, y + half)];
+            for (child, (x, y)) in children.iter().zip(corners) {
+                expand(child, x, y, half, walls);
+            }
+        }
+    }
+}
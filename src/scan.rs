@@ -0,0 +1,487 @@
+use std::{cmp::Reverse, collections::BinaryHeap, fmt::Display};
+
+use image::DynamicImage;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::{
+    contour::contours,
+    lvl::check_if_point_is_wall,
+    marching::marching_squares,
+    mesh::{greedy_rects, polylines, Mesh},
+    DynamicWall, Grid, Lvl, Point, SquareType, Wall, Wrap, FORMAT_VERSION,
+};
+
+/// Knobs for how squares are merged into walls.
+///
+/// Deserializes from an object with any of the fields, the rest keep their
+/// defaults.
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Every wall square belongs to exactly one wall. Walls of the
+    /// `merge_strategy` orientation claim their squares first and the others
+    /// are split around them.
+    pub no_overlap: bool,
+    /// Orientation whose runs are merged first, see [`MergeStrategy`]
+    pub merge_strategy: MergeStrategy,
+    /// Representation of the solid walls. Dynamic walls are always runs.
+    pub mesh: Mesh,
+    /// Merge staircases of squares left over by the horizontal and vertical
+    /// passes into diagonal walls.
+    pub diagonals: bool,
+    /// Pick horizontal or vertical per square so every square is covered by
+    /// exactly one wall and long walls are preferred, which ends up with
+    /// fewer walls.
+    pub optimize_walls: bool,
+    /// Only keep the outer shell of solid wall areas, see [`Grid::hollow`]
+    pub hollow: bool,
+    /// Douglas-Peucker tolerance in squares for [`Mesh::Marching`], `0.0`
+    /// keeps the exact outline
+    pub simplify: f64,
+}
+
+/// Which orientation [`ScanOptions`] merges first.
+///
+/// The first orientation keeps its single squares unless a longer run of the
+/// other one covers them, the other only keeps runs of two or more squares.
+/// Both end up covering the same squares with the same walls until
+/// `no_overlap` lets the first orientation claim its squares and splits the
+/// other one around them.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    #[default]
+    #[value(name = "h", alias = "horizontal-first")]
+    HorizontalFirst,
+    #[value(name = "v", alias = "vertical-first")]
+    VerticalFirst,
+}
+
+/// Rows or columns between two [`ScanProgress`] reports.
+pub const PROGRESS_INTERVAL: u32 = 100;
+
+/// Pass of the wall merging a [`ScanProgress`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanPhase {
+    /// Runs along the rows, counts rows
+    Horizontal,
+    /// Runs down the columns, counts columns
+    Vertical,
+}
+
+impl Display for ScanPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Horizontal => write!(f, "horizontal scan"),
+            Self::Vertical => write!(f, "vertical scan"),
+        }
+    }
+}
+
+/// How far [`scan_grid_with_progress`] got, reported every
+/// [`PROGRESS_INTERVAL`] rows or columns and at the end of each phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanProgress {
+    pub phase: ScanPhase,
+    /// Rows or columns done
+    pub done: u32,
+    /// Rows or columns in the phase
+    pub total: u32,
+    /// Walls found so far
+    pub walls: usize,
+}
+
+/// Build a level from an image, one pixel per square.
+pub fn scan_image(img: &DynamicImage, options: ScanOptions) -> Lvl {
+    scan_grid(&Grid::from_image(img), options)
+}
+
+/// Build a level from already classified squares.
+pub fn scan_grid(grid: &Grid, options: ScanOptions) -> Lvl {
+    scan_grid_with_progress(grid, options, |_| ())
+}
+
+/// [`scan_grid`] that reports how far merging the solid walls got, for
+/// large levels that take a while. Only `Mesh::Lines` merges walls.
+pub fn scan_grid_with_progress(
+    grid: &Grid,
+    options: ScanOptions,
+    mut progress: impl FnMut(ScanProgress),
+) -> Lvl {
+    if options.hollow {
+        return scan_grid_with_progress(
+            &grid.hollow(),
+            ScanOptions {
+                hollow: false,
+                ..options
+            },
+            progress,
+        );
+    }
+
+    debug!("Lvl Size {}x{}", grid.width(), grid.height());
+    let mut lvl = Lvl {
+        format_version: FORMAT_VERSION,
+        width: grid.width(),
+        height: grid.height(),
+        wrap: Wrap::None,
+        walls: Vec::new(),
+        dynamic_walls: Vec::new(),
+        rects: Vec::new(),
+        wall_paths: Vec::new(),
+        collision: Vec::new(),
+        edge_walls: Vec::new(),
+        start: Point { x: 0, y: 0 },
+        end: Point { x: 0, y: 0 },
+        checkpoints: Vec::new(),
+        optional_checkpoints: Vec::new(),
+    };
+
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            match grid.get(x, y) {
+                SquareType::End => lvl.end = Point::new(x, y),
+                SquareType::Checkpoint => lvl.checkpoints.push(Point::new(x, y)),
+                SquareType::OptionalCheckpoint => {
+                    lvl.optional_checkpoints.push(Point::new(x, y));
+                }
+                SquareType::Start => lvl.start = Point::new(x, y),
+                SquareType::Wall | SquareType::DynamicWall(_) | SquareType::Empty => (),
+            }
+        }
+    }
+
+    match options.mesh {
+        Mesh::Lines => {
+            lvl.walls = merge_runs(
+                grid,
+                options,
+                |square| (square == SquareType::Wall).then_some(()),
+                &mut progress,
+            )
+            .into_iter()
+            .map(|(wall, ())| wall)
+            .collect();
+        }
+        Mesh::Rect => lvl.rects = greedy_rects(grid),
+        Mesh::Polyline => lvl.wall_paths = polylines(grid),
+        Mesh::Contour => lvl.collision = contours(grid),
+        Mesh::Marching => lvl.collision = marching_squares(grid, options.simplify),
+    }
+
+    // Runs of dynamic walls split where the period changes
+    lvl.dynamic_walls = merge_runs(
+        grid,
+        options,
+        |square| match square {
+            SquareType::DynamicWall(period) => Some(period),
+            _ => None,
+        },
+        &mut |_| (),
+    )
+    .into_iter()
+    .map(|(wall, period)| DynamicWall { wall, period })
+    .collect();
+
+    if !grid.wrap().is_none() {
+        lvl.set_wrap(grid.wrap());
+    }
+
+    lvl
+}
+
+/// Merge neighboring squares with the same `key` into walls, longest first.
+///
+/// Squares without a key are not part of any wall.
+fn merge_runs<K: Copy + PartialEq>(
+    grid: &Grid,
+    options: ScanOptions,
+    key: impl Fn(SquareType) -> Option<K>,
+    progress: &mut dyn FnMut(ScanProgress),
+) -> Vec<(Wall, K)> {
+    let key_at = |x, y| key(grid.get(x, y));
+    let mut report = |phase, done: u32, total: u32, walls| {
+        if done.is_multiple_of(PROGRESS_INTERVAL) || done == total {
+            progress(ScanProgress {
+                phase,
+                done,
+                total,
+                walls,
+            });
+        }
+    };
+
+    let mut horizontal_walls = Vec::new();
+
+    let mut x;
+    let mut y = 0;
+    while y < grid.height() {
+        x = 0;
+        while x < grid.width() {
+            if let Some(run_key) = key_at(x, y) {
+                // Only check for horizontal lines
+                let start = Point::new(x, y);
+                while (x + 1) < grid.width() && key_at(x + 1, y) == Some(run_key) {
+                    x += 1;
+                    tracing::trace!("Wall detected at: {}-{}", x, y);
+                }
+
+                // Always insert, even if it's a single wall block
+                let wall = Wall {
+                    start,
+                    end: (start.x != x).then_some(Point::new(x, y)),
+                };
+                horizontal_walls.push((wall, run_key));
+            }
+
+            x += 1;
+        }
+        y += 1;
+        report(
+            ScanPhase::Horizontal,
+            y,
+            grid.height(),
+            horizontal_walls.len(),
+        );
+    }
+
+    // Add vertical walls
+    let mut vertical_walls = Vec::new();
+    x = 0;
+    while x < grid.width() {
+        y = 0;
+        while y < grid.height() {
+            if let Some(run_key) = key_at(x, y) {
+                let start = Point::new(x, y);
+
+                while (y + 1) < grid.height() && key_at(x, y + 1) == Some(run_key) {
+                    y += 1;
+                    tracing::trace!("Wall detected at: {}-{}", x, y);
+                }
+
+                let wall = Wall {
+                    start,
+                    end: (start.y != y).then_some(Point::new(x, y)),
+                };
+                debug!("{:?}", wall);
+                vertical_walls.push((wall, run_key));
+            }
+
+            y += 1
+        }
+
+        x += 1;
+        report(
+            ScanPhase::Vertical,
+            x,
+            grid.width(),
+            horizontal_walls.len() + vertical_walls.len(),
+        );
+    }
+
+    let (mut walls, mut second_walls) = match options.merge_strategy {
+        MergeStrategy::HorizontalFirst => (horizontal_walls, vertical_walls),
+        MergeStrategy::VerticalFirst => (vertical_walls, horizontal_walls),
+    };
+    // Single squares only stay in the first orientation, and only when no
+    // longer run of the second one covers them
+    second_walls.retain(|(wall, _)| wall.end.is_some());
+    let second: Vec<Wall> = second_walls.iter().map(|(wall, _)| *wall).collect();
+    walls.retain(|(wall, _)| {
+        wall.end.is_some() || !check_if_point_is_wall(wall.start.x, wall.start.y, &second)
+    });
+
+    if options.optimize_walls {
+        let before = walls.len() + second_walls.len();
+        walls.append(&mut second_walls);
+        walls = cover_greedily(grid, walls);
+        debug!(
+            "Optimized walls, {} before and {} after",
+            before,
+            walls.len()
+        );
+    } else if options.no_overlap {
+        let mut claimed = vec![false; grid.square_count()];
+        for (wall, _) in walls.iter().filter(|(wall, _)| wall.end.is_some()) {
+            for point in wall.squares() {
+                claimed[grid.index(point.x, point.y)] = true;
+            }
+        }
+
+        second_walls = second_walls
+            .into_iter()
+            .flat_map(|(wall, run_key)| {
+                split_unclaimed(grid, wall, &claimed)
+                    .into_iter()
+                    .map(move |piece| (piece, run_key))
+            })
+            .collect();
+    }
+
+    walls.append(&mut second_walls);
+
+    if options.diagonals {
+        let mut diagonal_walls = diagonal_runs(grid, options, &walls);
+        walls.retain(|(wall, _)| {
+            wall.end.is_some()
+                || !diagonal_walls
+                    .iter()
+                    .any(|(diagonal, _)| diagonal.contains(wall.start.x, wall.start.y))
+        });
+        walls.append(&mut diagonal_walls);
+    }
+
+    prune_covered(grid, &mut walls);
+    walls.sort_by_key(|(wall, _)| Reverse(*wall));
+
+    walls
+}
+
+/// Drop walls whose squares are all covered by other walls, shortest first,
+/// so the covered squares stay the same.
+fn prune_covered<K>(grid: &Grid, walls: &mut Vec<(Wall, K)>) {
+    let mut cover = vec![0_u32; grid.square_count()];
+    for point in walls.iter().flat_map(|(wall, _)| wall.squares()) {
+        cover[grid.index(point.x, point.y)] += 1;
+    }
+
+    let mut order: Vec<usize> = (0..walls.len()).collect();
+    order.sort_by_key(|&index| (walls[index].0.length(), walls[index].0));
+    let mut redundant = vec![false; walls.len()];
+    for index in order {
+        let wall = walls[index].0;
+        if wall
+            .squares()
+            .all(|point| cover[grid.index(point.x, point.y)] > 1)
+        {
+            for point in wall.squares() {
+                cover[grid.index(point.x, point.y)] -= 1;
+            }
+            redundant[index] = true;
+        }
+    }
+
+    let before = walls.len();
+    let mut redundant = redundant.into_iter();
+    walls.retain(|_| !redundant.next().unwrap_or_default());
+    debug!(
+        "Pruned {} walls covered by other walls",
+        before - walls.len()
+    );
+}
+
+/// Cover the squares of `runs` without overlaps, always taking the longest
+/// run that is still fully free. Runs that lost squares to a longer one are
+/// put back as their free pieces.
+fn cover_greedily<K: Copy>(grid: &Grid, runs: Vec<(Wall, K)>) -> Vec<(Wall, K)> {
+    let keys: Vec<K> = runs.iter().map(|(_, key)| *key).collect();
+    let mut queue: BinaryHeap<(Wall, usize)> = runs
+        .into_iter()
+        .enumerate()
+        .map(|(index, (wall, _))| (wall, index))
+        .collect();
+
+    let mut claimed = vec![false; grid.square_count()];
+    let mut walls = Vec::new();
+    while let Some((wall, index)) = queue.pop() {
+        let pieces = split_unclaimed(grid, wall, &claimed);
+        if pieces == [wall] {
+            for point in wall.squares() {
+                claimed[grid.index(point.x, point.y)] = true;
+            }
+            walls.push((wall, keys[index]));
+        } else {
+            queue.extend(pieces.into_iter().map(|piece| (piece, index)));
+        }
+    }
+
+    walls
+}
+
+/// Diagonal runs of at least two squares that are left as single squares
+/// by the horizontal and vertical passes, longest first.
+fn diagonal_runs<K: Copy + PartialEq>(
+    grid: &Grid,
+    options: ScanOptions,
+    walls: &[(Wall, K)],
+) -> Vec<(Wall, K)> {
+    let mut loose = vec![None; grid.square_count()];
+    for (wall, key) in walls.iter().filter(|(wall, _)| wall.end.is_none()) {
+        loose[grid.index(wall.start.x, wall.start.y)] = Some(*key);
+    }
+    let loose_at = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= i64::from(grid.width()) || y >= i64::from(grid.height()) {
+            return None;
+        }
+        loose[grid.index(x as u32, y as u32)]
+    };
+
+    // Down to the right, then down to the left
+    let mut runs = Vec::new();
+    for dx in [1, -1] {
+        for y in 0..i64::from(grid.height()) {
+            for x in 0..i64::from(grid.width()) {
+                let Some(run_key) = loose_at(x, y) else {
+                    continue;
+                };
+                // Only start at the top of a run
+                if loose_at(x - dx, y - 1) == Some(run_key) {
+                    continue;
+                }
+
+                let mut length = 1;
+                while loose_at(x + dx * length, y + length) == Some(run_key) {
+                    length += 1;
+                }
+                if length > 1 {
+                    let end = (x + dx * (length - 1), y + length - 1);
+                    let wall = Wall::between(
+                        Point::new(x as u32, y as u32),
+                        Point::new(end.0 as u32, end.1 as u32),
+                    );
+                    runs.push((wall, run_key));
+                }
+            }
+        }
+    }
+    runs.sort_by_key(|(wall, _)| Reverse(*wall));
+
+    if !options.no_overlap && !options.optimize_walls {
+        return runs;
+    }
+
+    // Longer runs claim crossing squares first
+    let mut claimed = vec![false; grid.square_count()];
+    let mut pieces = Vec::new();
+    for (wall, run_key) in runs {
+        for piece in split_unclaimed(grid, wall, &claimed) {
+            for point in piece.squares() {
+                claimed[grid.index(point.x, point.y)] = true;
+            }
+            pieces.push((piece, run_key));
+        }
+    }
+
+    pieces
+}
+
+/// Pieces of a wall that run between claimed squares.
+fn split_unclaimed(grid: &Grid, wall: Wall, claimed: &[bool]) -> Vec<Wall> {
+    let mut pieces = Vec::new();
+    let mut piece: Option<(Point, Point)> = None;
+    for point in wall.squares() {
+        if claimed[grid.index(point.x, point.y)] {
+            if let Some((start, end)) = piece.take() {
+                pieces.push(Wall::between(start, end));
+            }
+        } else {
+            piece = Some((piece.map_or(point, |(start, _)| start), point));
+        }
+    }
+    if let Some((start, end)) = piece {
+        pieces.push(Wall::between(start, end));
+    }
+
+    pieces
+}
@@ -0,0 +1,121 @@
+use alloc::{borrow::ToOwned, string::String};
+use core::{error::Error, fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquareType {
+    Wall,
+    /// Wall that toggles every `period` ticks
+    DynamicWall(u8),
+    Checkpoint,
+    OptionalCheckpoint,
+    Start,
+    End,
+    Empty,
+}
+
+impl Display for SquareType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_fmt(format_args!("{:?}", self))
+    }
+}
+
+impl SquareType {
+    /// Square for a pixel color, `None` when the color isn't in the palette.
+    pub const fn from_rgba(value: [u8; 4]) -> Option<Self> {
+        let square = match value {
+            // Translucent black, the alpha is the period. Fully transparent
+            // black is what most editors store for transparent pixels.
+            [0, 0, 0, alpha @ 1..=254] => Self::DynamicWall(alpha),
+            [0, 0, 0, _] => Self::Wall,                     // Black
+            [255, 0, 0, _] => Self::End,                    // Red
+            [0, 255, 0, _] => Self::Start,                  // Green
+            [0, 0, 255, _] => Self::Checkpoint,             // Blue
+            [128, 128, 255, _] => Self::OptionalCheckpoint, // Light blue
+            [255, 255, 255, _] => Self::Empty,              // White
+            _ => return None,
+        };
+
+        Some(square)
+    }
+
+    /// Pixel color of the square, the inverse of [`SquareType::from_rgba`]
+    /// except for dynamic walls with a period of 255, which read back as
+    /// solid walls.
+    pub const fn to_rgba(self) -> [u8; 4] {
+        match self {
+            Self::DynamicWall(period) => [0, 0, 0, period],
+            Self::Wall => [0, 0, 0, 255],
+            Self::End => [255, 0, 0, 255],
+            Self::Start => [0, 255, 0, 255],
+            Self::Checkpoint => [0, 0, 255, 255],
+            Self::OptionalCheckpoint => [128, 128, 255, 255],
+            Self::Empty => [255, 255, 255, 255],
+        }
+    }
+
+    /// Whether players can stand on the square. Only solid walls block,
+    /// dynamic walls open up every so often and count as passable.
+    pub const fn is_passable(&self) -> bool {
+        !matches!(self, Self::Wall)
+    }
+
+    /// Whether the square is a point of interest for the route: the start,
+    /// the end or a checkpoint, optional ones included. Walls and empty
+    /// squares aren't.
+    pub const fn is_special(&self) -> bool {
+        matches!(
+            self,
+            Self::Start | Self::End | Self::Checkpoint | Self::OptionalCheckpoint
+        )
+    }
+
+    /// Tile code used by the grid output: 0 empty, 1 wall, 2 start, 3 end,
+    /// 4 checkpoint, 5 optional checkpoint and 6 dynamic wall. The period of
+    /// a dynamic wall isn't part of its code.
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Empty => 0,
+            Self::Wall => 1,
+            Self::Start => 2,
+            Self::End => 3,
+            Self::Checkpoint => 4,
+            Self::OptionalCheckpoint => 5,
+            Self::DynamicWall(_) => 6,
+        }
+    }
+}
+
+impl From<[u8; 4]> for SquareType {
+    fn from(value: [u8; 4]) -> Self {
+        Self::from_rgba(value).unwrap_or_else(|| unimplemented!("{:?}", value))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSquareError(String);
+
+impl Display for ParseSquareError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unknown square type {:?}", self.0)
+    }
+}
+
+impl Error for ParseSquareError {}
+
+/// Parse a square type by name, ignoring case. Dynamic walls carry a
+/// period and can't be named.
+impl FromStr for SquareType {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wall" => Ok(Self::Wall),
+            "checkpoint" => Ok(Self::Checkpoint),
+            "optionalcheckpoint" => Ok(Self::OptionalCheckpoint),
+            "start" => Ok(Self::Start),
+            "end" => Ok(Self::End),
+            "empty" => Ok(Self::Empty),
+            _ => Err(ParseSquareError(s.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Read levels drawn as SVG shapes, one user unit per square.
+//!
+//! `<rect>` elements fill every square they touch, `<circle>` and
+//! `<ellipse>` elements mark the square under their center. Only shapes
+//! with a fill from the palette are used and transforms are ignored. The
+//! shapes are painted onto a [`Grid`] so the walls come out exactly as they
+//! would for the equivalent image.
+//!
+//! [`Lvl::to_svg`] draws a level back in the same palette over a light grid,
+//! so with a scale of 1 its output reads back into the squares it was drawn
+//! from. Dynamic walls are half transparent black with a `data-period`, and
+//! read back as solid walls.
+
+use std::{error::Error, fmt::Display};
+
+use roxmltree::{Document, Node};
+
+use crate::{lvl::path_walls, Grid, Lvl, Point, Rect, Side, SquareType, Vertex};
+
+/// Stroke of the grid lines behind the level.
+const GRID_COLOR: &str = "#dddddd";
+
+#[derive(Debug)]
+pub enum SvgError {
+    Xml(roxmltree::Error),
+    /// The root element has no usable `width`/`height` or `viewBox`
+    MissingSize,
+    /// A shape attribute is missing or isn't a number
+    InvalidAttribute {
+        element: String,
+        attribute: String,
+    },
+}
+
+impl Display for SvgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Xml(error) => write!(f, "invalid svg: {error}"),
+            Self::MissingSize => write!(f, "svg has no width/height or viewBox"),
+            Self::InvalidAttribute { element, attribute } => {
+                write!(f, "<{element}> has a missing or invalid {attribute}")
+            }
+        }
+    }
+}
+
+impl Error for SvgError {}
+
+impl From<roxmltree::Error> for SvgError {
+    fn from(value: roxmltree::Error) -> Self {
+        Self::Xml(value)
+    }
+}
+
+/// Paint the shapes of an SVG document onto a grid.
+pub fn parse_svg(text: &str) -> Result<Grid, SvgError> {
+    let document = Document::parse(text)?;
+    let root = document.root_element();
+    let (width, height) = size(root).ok_or(SvgError::MissingSize)?;
+
+    let mut grid = Grid::new(width, height);
+    for node in root.descendants().filter(Node::is_element) {
+        let Some(square) = fill(node).and_then(SquareType::from_rgba) else {
+            continue;
+        };
+
+        match node.tag_name().name() {
+            "rect" => {
+                let x = coordinate(node, "x")?;
+                let y = coordinate(node, "y")?;
+                let right = x + number(node, "width")?;
+                let bottom = y + number(node, "height")?;
+                for y in to_square(y)..to_square(bottom.ceil()) {
+                    for x in to_square(x)..to_square(right.ceil()) {
+                        grid.put(x, y, square);
+                    }
+                }
+            }
+            "circle" | "ellipse" => {
+                let cx = coordinate(node, "cx")?;
+                let cy = coordinate(node, "cy")?;
+                grid.put(to_square(cx), to_square(cy), square);
+            }
+            _ => (),
+        }
+    }
+
+    Ok(grid)
+}
+
+/// Level size from `width`/`height`, falling back to the `viewBox`.
+fn size(root: Node) -> Option<(u32, u32)> {
+    let attribute = |name| root.attribute(name).and_then(parse_length);
+    if let (Some(width), Some(height)) = (attribute("width"), attribute("height")) {
+        return Some((to_square(width.ceil()), to_square(height.ceil())));
+    }
+
+    let view_box: Vec<f64> = root
+        .attribute("viewBox")?
+        .split([' ', ','])
+        .filter(|part| !part.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match view_box[..] {
+        [_, _, width, height] => Some((to_square(width.ceil()), to_square(height.ceil()))),
+        _ => None,
+    }
+}
+
+/// Shapes can't be negative squares, anything left or above the level is
+/// clamped onto the first row or column.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_square(value: f64) -> u32 {
+    value.max(0.0).floor() as u32
+}
+
+fn number(node: Node, attribute: &str) -> Result<f64, SvgError> {
+    node.attribute(attribute)
+        .and_then(parse_length)
+        .ok_or_else(|| SvgError::InvalidAttribute {
+            element: node.tag_name().name().to_owned(),
+            attribute: attribute.to_owned(),
+        })
+}
+
+/// Position of a shape, which is `0` when it's left out like in SVG.
+fn coordinate(node: Node, attribute: &str) -> Result<f64, SvgError> {
+    if node.has_attribute(attribute) {
+        number(node, attribute)
+    } else {
+        Ok(0.0)
+    }
+}
+
+/// Number with an optional `px` unit.
+fn parse_length(value: &str) -> Option<f64> {
+    value.trim().trim_end_matches("px").parse().ok()
+}
+
+/// Fill color from either the `fill` attribute or the `style` attribute.
+fn fill(node: Node) -> Option<[u8; 4]> {
+    let from_style = node.attribute("style").and_then(|style| {
+        style.split(';').find_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            (property.trim() == "fill").then_some(value)
+        })
+    });
+
+    parse_color(from_style.or_else(|| node.attribute("fill"))?.trim())
+}
+
+fn parse_color(value: &str) -> Option<[u8; 4]> {
+    let color = match value.to_ascii_lowercase().as_str() {
+        "black" => [0, 0, 0, 255],
+        "red" => [255, 0, 0, 255],
+        "lime" => [0, 255, 0, 255],
+        "blue" => [0, 0, 255, 255],
+        "white" => [255, 255, 255, 255],
+        hex if hex.starts_with('#') && hex.is_ascii() => {
+            let hex = &hex[1..];
+            let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+            match hex.len() {
+                3 => {
+                    let short = |i: usize| channel(&hex[i..=i]).map(|digit| digit * 17);
+                    [short(0)?, short(1)?, short(2)?, 255]
+                }
+                6 => [
+                    channel(&hex[0..2])?,
+                    channel(&hex[2..4])?,
+                    channel(&hex[4..6])?,
+                    255,
+                ],
+                _ => return None,
+            }
+        }
+        rgb if rgb.starts_with("rgb(") && rgb.ends_with(')') => {
+            let mut channels = rgb[4..rgb.len() - 1]
+                .split(',')
+                .map(|channel| channel.trim().parse::<u8>().ok());
+            let color = [channels.next()??, channels.next()??, channels.next()??, 255];
+            if channels.next().is_some() {
+                return None;
+            }
+            color
+        }
+        _ => return None,
+    };
+
+    Some(color)
+}
+
+impl Lvl {
+    /// The level as an SVG of `scale` units per square: a white background
+    /// with a grid, a `<rect>` per wall, rectangle or wall path piece, a
+    /// `<path>` per polygon, a `<line>` per edge wall and a `<circle>` per
+    /// point, in the order they're stored so the output diffs cleanly.
+    pub fn to_svg(&self, scale: u32) -> String {
+        let unit = f64::from(scale);
+        let (width, height) = (self.width * scale, self.height * scale);
+        let rect = |rect: Rect, paint: &str| {
+            format!(
+                r#"  <rect x="{}" y="{}" width="{}" height="{}" {paint}/>"#,
+                rect.x * scale,
+                rect.y * scale,
+                rect.width * scale,
+                rect.height * scale
+            )
+        };
+        let circle = |point: Point, fill: &str| {
+            format!(
+                r#"  <circle cx="{}" cy="{}" r="{}" fill="{fill}"/>"#,
+                (f64::from(point.x) + 0.5) * unit,
+                (f64::from(point.y) + 0.5) * unit,
+                0.4 * unit
+            )
+        };
+
+        let mut lines = vec![
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+            ),
+            "  <defs>".to_owned(),
+            format!(
+                r#"    <pattern id="grid" width="{scale}" height="{scale}" patternUnits="userSpaceOnUse">"#
+            ),
+            format!(
+                r#"      <path d="M{scale} 0H0V{scale}" fill="none" stroke="{GRID_COLOR}" stroke-width="{}"/>"#,
+                unit / 10.0
+            ),
+            "    </pattern>".to_owned(),
+            "  </defs>".to_owned(),
+            format!(r#"  <rect width="{width}" height="{height}" fill="white"/>"#),
+            format!(r#"  <rect width="{width}" height="{height}" fill="url(#grid)"/>"#),
+        ];
+        let walls = self
+            .walls
+            .iter()
+            .copied()
+            .chain(self.wall_paths.iter().flat_map(|path| path_walls(path)))
+            .flat_map(|wall| self.wall_rects(wall))
+            .chain(self.rects.iter().copied());
+        lines.extend(walls.map(|wall| rect(wall, r#"fill="black""#)));
+        lines.extend(self.collision.iter().map(|polygon| {
+            let outline = |vertices: &[Vertex]| {
+                let points: Vec<String> = vertices
+                    .iter()
+                    .map(|vertex| format!("{} {}", vertex.x * unit, vertex.y * unit))
+                    .collect();
+                format!("M{}Z", points.join("L"))
+            };
+            let outlines: Vec<String> = core::iter::once(&polygon.outer)
+                .chain(&polygon.holes)
+                .map(|vertices| outline(vertices))
+                .collect();
+            format!(
+                r#"  <path d="{}" fill="black" fill-rule="evenodd"/>"#,
+                outlines.concat()
+            )
+        }));
+        for dynamic_wall in &self.dynamic_walls {
+            lines.extend(self.wall_rects(dynamic_wall.wall).into_iter().map(|wall| {
+                let paint = format!(
+                    r#"fill="black" fill-opacity="0.5" data-period="{}""#,
+                    dynamic_wall.period
+                );
+                rect(wall, &paint)
+            }));
+        }
+        lines.extend(self.edge_walls.iter().map(|edge_wall| {
+            let (x, y) = (edge_wall.x * scale, edge_wall.y * scale);
+            let (x1, y1, x2, y2) = match edge_wall.side {
+                Side::Top => (x, y, x + scale, y),
+                Side::Right => (x + scale, y, x + scale, y + scale),
+                Side::Bottom => (x, y + scale, x + scale, y + scale),
+                Side::Left => (x, y, x, y + scale),
+            };
+            format!(
+                r#"  <line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="black" stroke-width="{}"/>"#,
+                unit / 5.0
+            )
+        }));
+        lines.extend(self.checkpoints.iter().map(|point| circle(*point, "blue")));
+        lines.extend(
+            self.optional_checkpoints
+                .iter()
+                .map(|point| circle(*point, "#8080ff")),
+        );
+        lines.push(circle(self.end, "red"));
+        lines.push(circle(self.start, "lime"));
+        lines.push("</svg>".to_owned());
+
+        let mut svg = lines.join("\n");
+        svg.push('\n');
+        svg
+    }
+}
@@ -0,0 +1,107 @@
+//! Levels as Tiled maps, written the way Tiled 1.10 saves them.
+//!
+//! The map has a `squares` tile layer with one tile per square and a
+//! `points` object layer with the start, end and checkpoints as named point
+//! objects at the center of their square. The embedded `squares` tileset has
+//! a tile per [`SquareType`] without images, the gid of a square is its
+//! [`SquareType::code`] so empty squares are left without a tile. Dynamic
+//! wall periods aren't part of a gid and are lost. A wrapping level gets a
+//! `wrap` map property.
+
+use crate::{Grid, Lvl, Point, SquareType, Wrap};
+
+/// Tiles of the tileset in gid order, starting at gid 1.
+const TILES: [(SquareType, &str); 6] = [
+    (SquareType::Wall, "wall"),
+    (SquareType::Start, "start"),
+    (SquareType::End, "end"),
+    (SquareType::Checkpoint, "checkpoint"),
+    (SquareType::OptionalCheckpoint, "optional checkpoint"),
+    (SquareType::DynamicWall(0), "dynamic wall"),
+];
+
+impl Lvl {
+    /// The level as a TMX map of `tile_size` pixel square tiles.
+    pub fn to_tmx(&self, tile_size: u32) -> String {
+        let points: Vec<(&str, Point)> = [("start", self.start), ("end", self.end)]
+            .into_iter()
+            .chain(self.checkpoints.iter().map(|point| ("checkpoint", *point)))
+            .chain(
+                self.optional_checkpoints
+                    .iter()
+                    .map(|point| ("optional checkpoint", *point)),
+            )
+            .collect();
+
+        let mut lines = vec![
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+            format!(
+                r#"<map version="1.10" tiledversion="1.10.2" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{tile_size}" tileheight="{tile_size}" infinite="0" nextlayerid="3" nextobjectid="{}">"#,
+                self.width,
+                self.height,
+                points.len() + 1
+            ),
+        ];
+        let wrap = match self.wrap {
+            Wrap::None => None,
+            Wrap::X => Some("x"),
+            Wrap::Y => Some("y"),
+            Wrap::Both => Some("both"),
+        };
+        if let Some(wrap) = wrap {
+            lines.extend([
+                " <properties>".to_string(),
+                format!(r#"  <property name="wrap" value="{wrap}"/>"#),
+                " </properties>".to_string(),
+            ]);
+        }
+
+        lines.push(format!(
+            r#" <tileset firstgid="1" name="squares" tilewidth="{tile_size}" tileheight="{tile_size}" tilecount="{}" columns="0">"#,
+            TILES.len()
+        ));
+        lines.push(r#"  <grid orientation="orthogonal" width="1" height="1"/>"#.to_string());
+        for (square, name) in TILES {
+            lines.push(format!(
+                r#"  <tile id="{}" type="{name}"/>"#,
+                square.code() - 1
+            ));
+        }
+        lines.push(" </tileset>".to_string());
+
+        lines.push(format!(
+            r#" <layer id="1" name="squares" width="{}" height="{}">"#,
+            self.width, self.height
+        ));
+        lines.push(r#"  <data encoding="csv">"#.to_string());
+        let rows: Vec<String> = Grid::from_lvl(self)
+            .codes()
+            .iter()
+            .map(|row| row.iter().map(u8::to_string).collect::<Vec<_>>().join(","))
+            .collect();
+        // Every row but the last ends with a comma, like Tiled writes them
+        lines.push(rows.join(",\n"));
+        lines.push("</data>".to_string());
+        lines.push(" </layer>".to_string());
+
+        lines.push(r#" <objectgroup id="2" name="points">"#.to_string());
+        let center = |coordinate: u32| {
+            f64::from(coordinate) * f64::from(tile_size) + f64::from(tile_size) / 2.0
+        };
+        for (id, (name, point)) in (1..).zip(points) {
+            lines.push(format!(
+                r#"  <object id="{id}" name="{name}" x="{}" y="{}">"#,
+                center(point.x),
+                center(point.y)
+            ));
+            lines.push("   <point/>".to_string());
+            lines.push("  </object>".to_string());
+        }
+        lines.push(" </objectgroup>".to_string());
+        lines.push("</map>".to_string());
+
+        let mut tmx = lines.join("\n");
+        tmx.push('\n');
+        tmx
+    }
+}
@@ -0,0 +1,27 @@
+//! Browser entry point, built with `build.sh`.
+
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::prelude::*;
+
+use crate::{scan_grid, Grid, ScanOptions};
+
+/// Decode an image in any format the `image` crate reads and scan it into a
+/// level, one pixel per square.
+///
+/// `options_json` is a [`ScanOptions`] object like `{"mesh": "rect"}`, an
+/// empty string uses the defaults. The level is returned as a plain object
+/// shaped like the json the command line tool writes, errors as strings,
+/// including pixels that aren't in the palette.
+#[wasm_bindgen]
+pub fn parse_image_bytes(data: &[u8], options_json: &str) -> Result<JsValue, JsValue> {
+    let options: ScanOptions = if options_json.trim().is_empty() {
+        ScanOptions::default()
+    } else {
+        serde_json::from_str(options_json).map_err(|error| format!("invalid options: {error}"))?
+    };
+    let image = image::load_from_memory(data).map_err(|error| error.to_string())?;
+    let grid = Grid::try_from_image(&image).map_err(|error| error.to_string())?;
+
+    Ok(scan_grid(&grid, options).serialize(&Serializer::json_compatible())?)
+}
@@ -0,0 +1,95 @@
+use maze_lvl_maker::{parse_ascii, scan_grid, Lvl, LvlBuilder, Point, ScanOptions};
+
+/// Eight steps from start to end, with a dead end in the bottom left.
+const LEVEL: &str = "\
+#######
+#S..#E#
+#.#.#.#
+#.#...#
+#######
+";
+
+fn scan(text: &str) -> Lvl {
+    scan_grid(&parse_ascii(text).unwrap(), ScanOptions::default())
+}
+
+#[test]
+fn shortest_path_goes_around_the_walls() {
+    assert_eq!(scan(LEVEL).shortest_path_length(), Some(8));
+    assert_eq!(scan("S...E").shortest_path_length(), Some(4));
+    assert_eq!(scan("S#E").shortest_path_length(), None);
+}
+
+#[test]
+fn dead_ends_have_one_way_out() {
+    assert_eq!(scan(LEVEL).dead_ends(), [Point::new(1, 3)]);
+    // Start and end aren't empty squares, even with one neighbor
+    assert_eq!(scan("S.E").dead_ends(), []);
+    assert_eq!(
+        scan("S..\n.#.\n..E").dead_ends(),
+        [],
+        "a loop has no dead ends"
+    );
+}
+
+#[test]
+fn difficulty_weighs_path_dead_ends_and_walls() {
+    // 11 passable and 24 wall squares out of 35
+    let expected = 0.4 * 8.0 / 11.0 + 0.25 * 1.0 / 11.0 + 0.2 * 24.0 / 35.0;
+    let score = scan(LEVEL).difficulty_score();
+    assert!((score - expected).abs() < 1e-9, "{score} != {expected}");
+}
+
+#[test]
+fn unsolvable_levels_count_as_the_longest_path() {
+    let score = scan("S#E").difficulty_score();
+    assert!((score - (0.4 + 0.2 / 3.0)).abs() < 1e-9, "{score}");
+
+    for text in [LEVEL, "S#E", "S.E", "SE"] {
+        let score = scan(text).difficulty_score();
+        assert!((0.0..=1.0).contains(&score), "{text}: {score}");
+    }
+}
+
+/// A wall splits the level, start and end on the bigger left side.
+fn split_level() -> LvlBuilder {
+    LvlBuilder::new(6, 3)
+        .start(Point::new(0, 0))
+        .end(Point::new(2, 2))
+        .wall(Point::new(3, 0), Point::new(3, 2))
+}
+
+#[test]
+fn components_are_split_by_walls_largest_first() {
+    let components = split_level().build().unwrap().connected_components();
+    let sizes: Vec<_> = components.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [9, 6]);
+    assert!(components[0].contains(&Point::new(0, 0)));
+    assert!(components[1].contains(&Point::new(5, 2)));
+    assert!(components.iter().flatten().all(|point| point.x != 3));
+
+    assert_eq!(scan("S.E").connected_components().len(), 1);
+    assert_eq!(scan("S#\n#E").connected_components().len(), 2);
+}
+
+#[test]
+fn solvable_when_start_end_and_checkpoints_share_a_component() {
+    assert!(split_level().build().unwrap().is_solvable());
+    assert!(split_level()
+        .optional_checkpoint(Point::new(5, 1))
+        .build()
+        .unwrap()
+        .is_solvable());
+    assert!(!split_level()
+        .checkpoint(Point::new(5, 1))
+        .build()
+        .unwrap()
+        .is_solvable());
+    assert!(!split_level()
+        .end(Point::new(4, 2))
+        .build()
+        .unwrap()
+        .is_solvable());
+    assert!(!scan("S#E").is_solvable());
+    assert!(scan(LEVEL).is_solvable());
+}
@@ -0,0 +1,121 @@
+//! The squares redrawn from the walls and points of a level have to match the
+//! squares it was scanned from, whatever options merged the walls.
+
+use std::path::Path;
+
+use maze_lvl_maker::{parse_ascii, scan_grid, Grid, Mesh, ScanOptions};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#.###.###
+#...#...#
+#########
+";
+
+/// Every option set that changes how the walls are merged but not which
+/// squares they cover.
+fn options() -> Vec<ScanOptions> {
+    let mut options = vec![
+        ScanOptions::default(),
+        ScanOptions {
+            no_overlap: true,
+            ..ScanOptions::default()
+        },
+        ScanOptions {
+            diagonals: true,
+            ..ScanOptions::default()
+        },
+        ScanOptions {
+            optimize_walls: true,
+            ..ScanOptions::default()
+        },
+    ];
+    for mesh in [Mesh::Rect, Mesh::Polyline, Mesh::Contour, Mesh::Marching] {
+        options.push(ScanOptions {
+            mesh,
+            ..ScanOptions::default()
+        });
+    }
+    options
+}
+
+/// Compare cell by cell, naming the first square that differs.
+fn assert_same_squares(name: &str, grid: &Grid) {
+    let expected = grid.to_ascii();
+    for options in options() {
+        let actual = Grid::from_lvl(&scan_grid(grid, options)).to_ascii();
+        let mismatch =
+            expected
+                .lines()
+                .zip(actual.lines())
+                .enumerate()
+                .find_map(|(y, (expected, actual))| {
+                    expected
+                        .chars()
+                        .zip(actual.chars())
+                        .position(|(expected, actual)| expected != actual)
+                        .map(|x| (x, y))
+                });
+        assert_eq!(
+            mismatch, None,
+            "{name} with {options:?}\nexpected:\n{expected}\nactual:\n{actual}"
+        );
+        assert_eq!(actual, expected, "{name} with {options:?}");
+    }
+}
+
+#[test]
+fn ascii_fixture_is_redrawn_exactly() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    assert_eq!(grid.to_ascii(), LEVEL);
+    assert_same_squares("LEVEL", &grid);
+}
+
+#[test]
+fn golden_images_are_redrawn_exactly() {
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    for name in ["tiny", "maze", "rooms"] {
+        let image = image::open(golden.join(format!("{name}.png"))).unwrap();
+        assert_same_squares(name, &Grid::from_image(&image));
+    }
+}
+
+fn run(name: &str, args: &[&str]) -> std::process::Output {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-ascii-render-{name}"));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    std::process::Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(["--ascii", "level.txt", "--emit", "ascii"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn emit_ascii_lists_the_rows() {
+    let output = run("emit", &[]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rows: Vec<&str> = json["ascii"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| row.as_str().unwrap())
+        .collect();
+    assert_eq!(rows, LEVEL.lines().collect::<Vec<_>>());
+    assert!(json.get("walls").is_none());
+}
+
+#[test]
+fn max_width_fails_on_wider_levels() {
+    assert!(run("fits", &["--max-width", "9"]).status.success());
+    let output = run("wide", &["--max-width", "8"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("9 squares wide, more than --max-width 8"));
+}
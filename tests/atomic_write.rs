@@ -0,0 +1,95 @@
+use std::{fs, path::PathBuf, process::Command};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-atomic-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(["--ascii", "level.txt"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn temporary_file_is_renamed_over_the_outfile() {
+    let dir = dir("rename");
+    fs::write(dir.join("level.json"), "old").unwrap();
+
+    assert!(run(&dir, &["-o", "level.json"]).status.success());
+    assert_eq!(
+        fs::read(dir.join("level.json")).unwrap(),
+        run(&dir, &[]).stdout
+    );
+    assert!(!dir.join("level.json.tmp").exists());
+
+    assert!(run(&dir, &["-o", "level.json", "--compress"])
+        .status
+        .success());
+    assert!(dir.join("level.json.gz").exists());
+    assert!(!dir.join("level.json.gz.tmp").exists());
+}
+
+#[test]
+fn failed_write_keeps_the_old_outfile() {
+    let dir = dir("failed");
+    fs::write(dir.join("level.json"), "old").unwrap();
+    // The temporary file can't be created where a directory is
+    fs::create_dir(dir.join("level.json.tmp")).unwrap();
+
+    assert!(!run(&dir, &["-o", "level.json"]).status.success());
+    assert_eq!(fs::read_to_string(dir.join("level.json")).unwrap(), "old");
+}
+
+#[test]
+fn smaller_level_replaces_all_of_a_bigger_one() {
+    let dir = dir("shrink");
+    let big = format!("{}/tests/golden/maze.png", env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["--image", &big, "-o", "level.json", "--pretty"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let before = fs::metadata(dir.join("level.json")).unwrap().len();
+
+    assert!(run(&dir, &["-o", "level.json"]).status.success());
+    let written = fs::read(dir.join("level.json")).unwrap();
+    assert!((written.len() as u64) < before);
+    let lvl: serde_json::Value = serde_json::from_slice(&written).unwrap();
+    assert_eq!(lvl["width"], 9);
+}
+
+#[test]
+fn dash_is_stdout() {
+    let dir = dir("dash");
+
+    let output = run(&dir, &["-o", "-"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(output.stdout, run(&dir, &[]).stdout);
+    assert!(!dir.join("-").exists());
+
+    assert!(run(&dir, &["-o", "level.json"]).status.success());
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["render", "--lvl", "level.json", "-o", "-"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stdout.starts_with(b"<svg"));
+    assert!(!dir.join("-").exists());
+}
@@ -0,0 +1,116 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{
+    parse_ascii, scan_grid, Lvl, LvlBuilder, Point, ScanOptions, SquareType, Wall, Wrap,
+};
+
+const OPEN: &str = "\
+.....
+.S.#.
+.#.E.
+.....
+";
+
+fn wall(start: (u32, u32), end: (u32, u32)) -> Wall {
+    Wall {
+        start: Point::new(start.0, start.1),
+        end: Some(Point::new(end.0, end.1)),
+    }
+}
+
+fn scan(text: &str) -> Lvl {
+    scan_grid(&parse_ascii(text).unwrap(), ScanOptions::default())
+}
+
+#[test]
+fn open_edges_get_one_wall_each() {
+    let mut lvl = scan(OPEN);
+    let scanned = lvl.walls.len();
+    lvl.add_border_walls();
+
+    assert_eq!(
+        lvl.walls[scanned..],
+        [
+            wall((0, 0), (4, 0)),
+            wall((0, 3), (4, 3)),
+            wall((0, 1), (0, 2)),
+            wall((4, 1), (4, 2)),
+        ]
+    );
+    assert!(lvl.border_gaps().is_empty());
+}
+
+#[test]
+fn walls_split_around_the_start_and_existing_walls() {
+    let mut lvl = LvlBuilder::new(6, 3)
+        .start(Point::new(2, 0))
+        .end(Point::new(3, 1))
+        .wall(Point::new(5, 2), Point::new(5, 2))
+        .build()
+        .unwrap();
+    lvl.add_border_walls();
+
+    assert_eq!(
+        lvl.walls[1..],
+        [
+            wall((0, 0), (1, 0)),
+            wall((3, 0), (5, 0)),
+            wall((0, 2), (4, 2)),
+            Wall {
+                start: Point::new(0, 1),
+                end: None,
+            },
+            Wall {
+                start: Point::new(5, 1),
+                end: None,
+            },
+        ]
+    );
+    assert_eq!(lvl.cell_at(2, 0), SquareType::Start);
+    assert!(lvl.find_overlapping_walls().is_empty());
+
+    // A closed border has nothing left to add
+    let walls = lvl.walls.clone();
+    lvl.add_border_walls();
+    assert_eq!(lvl.walls, walls);
+}
+
+#[test]
+fn wrapping_edges_stay_open() {
+    let mut lvl = scan(OPEN);
+    lvl.set_wrap(Wrap::X);
+    let scanned = lvl.walls.len();
+    lvl.add_border_walls();
+    assert_eq!(
+        lvl.walls[scanned..],
+        [wall((0, 0), (4, 0)), wall((0, 3), (4, 3))]
+    );
+
+    let mut lvl = scan(OPEN);
+    lvl.set_wrap(Wrap::Both);
+    let scanned = lvl.walls.len();
+    lvl.add_border_walls();
+    assert_eq!(lvl.walls.len(), scanned);
+}
+
+#[test]
+fn flag_closes_the_border_before_it_is_checked() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-auto-border");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), OPEN).unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(["--ascii", "level.txt", "--require-closed-border"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    assert!(!run(&[]).status.success());
+    let output = run(&["--auto-border"]);
+    assert!(output.status.success(), "{output:?}");
+    let lvl: Lvl = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(lvl.walls.contains(&wall((0, 0), (4, 0))));
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "bevy")]
+
+use std::path::Path;
+
+use bevy::{
+    asset::{AssetPlugin, AssetServer, Assets, LoadState},
+    prelude::*,
+    reflect::{structs::GetField, TypePath},
+};
+use maze_lvl_maker::{Lvl, LvlBuilder, LvlPlugin, Point};
+
+#[test]
+fn loads_lvl_json_assets() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: assets.to_string_lossy().into_owned(),
+            ..AssetPlugin::default()
+        },
+        LvlPlugin,
+    ));
+    let handle: Handle<Lvl> = app
+        .world()
+        .resource::<AssetServer>()
+        .load("example.lvl.json");
+
+    for _ in 0..1000 {
+        app.update();
+        match app.world().resource::<AssetServer>().load_state(&handle) {
+            LoadState::Loaded => break,
+            LoadState::Failed(error) => panic!("{error}"),
+            _ => std::thread::sleep(std::time::Duration::from_millis(1)),
+        }
+    }
+
+    let expected: Lvl =
+        serde_json::from_str(&std::fs::read_to_string(assets.join("example.lvl.json")).unwrap())
+            .unwrap();
+    let lvl = app.world().resource::<Assets<Lvl>>().get(&handle).unwrap();
+    assert_eq!(*lvl, expected);
+}
+
+#[test]
+fn level_types_are_reflected() {
+    let lvl = LvlBuilder::new(4, 4)
+        .start(Point::new(2, 3))
+        .end(Point::new(0, 0))
+        .build()
+        .unwrap();
+    assert_eq!(lvl.get_field::<u32>("width"), Some(&lvl.width));
+    let start = lvl.get_field::<Point>("start").unwrap();
+    assert_eq!(start.get_field::<u32>("y"), Some(&3));
+    assert_eq!(Lvl::type_path(), "maze_lvl_maker::lvl::Lvl");
+}
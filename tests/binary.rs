@@ -0,0 +1,148 @@
+use maze_lvl_maker::{
+    parse_ascii, scan_edges, scan_grid, BinError, Lvl, LvlBuilder, Mesh, Point, ScanOptions, Wrap,
+    BIN_MAGIC, BIN_VERSION,
+};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+fn level() -> Lvl {
+    let mut lvl = LvlBuilder::new(6, 6)
+        .start(Point::new(1, 1))
+        .end(Point::new(4, 4))
+        .checkpoint(Point::new(1, 4))
+        .optional_checkpoint(Point::new(4, 1))
+        .wall(Point::new(0, 0), Point::new(5, 0))
+        .wall(Point::new(0, 1), Point::new(0, 5))
+        .wall(Point::new(3, 5), Point::new(1, 3))
+        .wall(Point::new(5, 3), Point::new(5, 3))
+        .dynamic_wall(Point::new(2, 2), Point::new(3, 2), 7)
+        .build()
+        .unwrap();
+    lvl.set_wrap(Wrap::Both);
+    lvl
+}
+
+#[test]
+fn round_trip() {
+    let lvl = level();
+    assert_eq!(Lvl::from_bin(&lvl.to_bin()).unwrap(), lvl);
+}
+
+#[test]
+fn round_trip_scanned_meshes() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    for mesh in [Mesh::Lines, Mesh::Rect] {
+        let lvl = scan_grid(
+            &grid,
+            ScanOptions {
+                mesh,
+                ..ScanOptions::default()
+            },
+        );
+        assert_eq!(Lvl::from_bin(&lvl.to_bin()).unwrap(), lvl, "{mesh:?}");
+    }
+
+    let edges = scan_edges(&grid).unwrap();
+    assert_eq!(Lvl::from_bin(&edges.to_bin()).unwrap(), edges);
+}
+
+#[test]
+fn wall_paths_and_polygons_are_left_out() {
+    let lvl = scan_grid(
+        &parse_ascii(LEVEL).unwrap(),
+        ScanOptions {
+            mesh: Mesh::Marching,
+            ..ScanOptions::default()
+        },
+    );
+    assert!(!lvl.collision.is_empty());
+    let read = Lvl::from_bin(&lvl.to_bin()).unwrap();
+    assert!(read.collision.is_empty());
+    assert_eq!(read.start, lvl.start);
+}
+
+#[test]
+fn header_and_record_layout() {
+    let lvl = level();
+    let bin = lvl.to_bin();
+    let u32_at = |offset: usize| u32::from_le_bytes(bin[offset..offset + 4].try_into().unwrap());
+
+    assert_eq!(bin[..4], BIN_MAGIC);
+    assert_eq!(u16::from_le_bytes([bin[4], bin[5]]), BIN_VERSION);
+    assert_eq!(bin[6..8], [3, 0]);
+    assert_eq!(
+        [
+            u32_at(8),
+            u32_at(12),
+            u32_at(16),
+            u32_at(20),
+            u32_at(24),
+            u32_at(28)
+        ],
+        [6, 6, 1, 1, 4, 4]
+    );
+
+    // Walls, single squares repeat their start
+    assert_eq!(u32_at(32), 4);
+    assert_eq!(
+        [u32_at(36), u32_at(40), u32_at(44), u32_at(48)],
+        [0, 0, 5, 0]
+    );
+    assert_eq!(
+        [u32_at(84), u32_at(88), u32_at(92), u32_at(96)],
+        [5, 3, 5, 3]
+    );
+    // Dynamic walls
+    assert_eq!(u32_at(100), 1);
+    assert_eq!(bin[120..124], [7, 0, 0, 0]);
+
+    let lists = 4 * 6;
+    let records = 4 * 16 + 20 + 8 + 8;
+    assert_eq!(bin.len(), 32 + lists + records);
+}
+
+#[test]
+fn rejects_other_files_and_versions() {
+    let mut bin = level().to_bin();
+    assert_eq!(Lvl::from_bin(b"{\"width\": 3}"), Err(BinError::Magic));
+
+    bin[4..6].copy_from_slice(&(BIN_VERSION + 1).to_le_bytes());
+    assert_eq!(Lvl::from_bin(&bin), Err(BinError::Version(BIN_VERSION + 1)));
+}
+
+#[test]
+fn rejects_damaged_files() {
+    let bin = level().to_bin();
+    for length in [0, 3, 20, 40, bin.len() - 1] {
+        assert_eq!(
+            Lvl::from_bin(&bin[..length]),
+            Err(BinError::Truncated),
+            "{length}"
+        );
+    }
+
+    let mut longer = bin.clone();
+    longer.extend_from_slice(&[0, 0]);
+    assert_eq!(Lvl::from_bin(&longer), Err(BinError::TrailingBytes(2)));
+
+    let mut wrap = bin.clone();
+    wrap[6] = 9;
+    assert_eq!(
+        Lvl::from_bin(&wrap),
+        Err(BinError::InvalidTag {
+            offset: 6,
+            value: 9
+        })
+    );
+
+    // A count far beyond the end of the file
+    let mut count = bin;
+    count[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(Lvl::from_bin(&count), Err(BinError::Truncated));
+}
@@ -0,0 +1,80 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{
+    parse_ascii, parse_occupancy, scan_image, Grid, OccupancyError, ScanOptions, SquareType,
+};
+
+/// Only the walls of `grid`, everything else empty.
+fn walls_only(grid: &Grid) -> Grid {
+    let mut text = String::new();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            text.push(if grid.get(x, y) == SquareType::Wall {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        text.push('\n');
+    }
+    parse_ascii(&text).unwrap()
+}
+
+#[test]
+fn bits_are_packed_in_reading_order_from_the_top_bit() {
+    // 101 010 100 and 7 padding bits
+    let grid = parse_ascii("#S#\n.#.\n#.E\n").unwrap();
+    assert_eq!(grid.to_occupancy(), "qgA=");
+}
+
+#[test]
+fn golden_levels_round_trip_through_the_bitset() {
+    for name in ["tiny", "maze", "rooms"] {
+        let path = format!("{}/tests/golden/{name}.png", env!("CARGO_MANIFEST_DIR"));
+        let lvl = scan_image(&image::open(path).unwrap(), ScanOptions::default());
+        let grid = Grid::from_lvl(&lvl);
+
+        let decoded = parse_occupancy(&grid.to_occupancy(), lvl.width, lvl.height).unwrap();
+        assert_eq!(decoded, walls_only(&grid), "{name}");
+    }
+}
+
+#[test]
+fn bitsets_of_the_wrong_size_are_refused() {
+    // The size comes from the level, any that needs two bytes reads
+    assert!(parse_occupancy("qgA=", 4, 4).is_ok());
+    assert!(matches!(
+        parse_occupancy("qgA=", 5, 5),
+        Err(OccupancyError::Length {
+            expected: 4,
+            actual: 2
+        })
+    ));
+    assert!(matches!(
+        parse_occupancy("not base64!", 3, 3),
+        Err(OccupancyError::Base64(_))
+    ));
+}
+
+#[test]
+fn emit_bitset_replaces_the_walls() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-bitset");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), "#S#\n.#.\n#.E\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["--ascii", "level.txt", "--emit", "bitset"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(json["occupancy"], "qgA=");
+    assert_eq!(json["bit_order"], "msb_first");
+    assert_eq!(json["width"], 3);
+    assert_eq!(json["height"], 3);
+    assert_eq!(json["start"], serde_json::json!({"x": 1, "y": 0}));
+    assert!(json.get("walls").is_none());
+}
@@ -0,0 +1,79 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use maze_lvl_maker::{LvlBuilder, Point, ValidationWarning};
+
+/// Start inside the level, end on the right edge.
+const INSIDE: &str = "\
+#####
+#S..E
+#####
+";
+
+fn level(name: &str, text: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-boundary");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.txt"));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn points_off_every_edge_are_reported() {
+    let lvl = LvlBuilder::new(5, 4)
+        .start(Point::new(1, 1))
+        .end(Point::new(3, 2))
+        .build()
+        .unwrap();
+    assert_eq!(
+        lvl.validate_boundary_start_end(),
+        [
+            ValidationWarning::StartNotOnBoundary(Point::new(1, 1)),
+            ValidationWarning::EndNotOnBoundary(Point::new(3, 2)),
+        ]
+    );
+
+    for (start, end) in [((0, 2), (4, 1)), ((2, 0), (1, 3))] {
+        let lvl = LvlBuilder::new(5, 4)
+            .start(Point::new(start.0, start.1))
+            .end(Point::new(end.0, end.1))
+            .build()
+            .unwrap();
+        assert!(lvl.validate_boundary_start_end().is_empty(), "{start:?}");
+    }
+}
+
+#[test]
+fn only_strict_boundary_fails() {
+    let path = level("inside", INSIDE);
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .env("RUST_LOG", "warn")
+            .arg("--ascii")
+            .arg(&path)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&[]);
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("the start at (1, 1) isn't on the edge"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("the end"), "{stderr}");
+
+    let output = run(&["--strict-boundary"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let path = level("edge", "#####\nS...E\n#####\n");
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .arg("--ascii")
+        .arg(&path)
+        .arg("--strict-boundary")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
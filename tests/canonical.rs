@@ -0,0 +1,119 @@
+use std::{fs, path::Path, process::Command};
+
+use image::Rgba;
+use maze_lvl_maker::{Lvl, LvlBuilder, Point, SquareType, Wall};
+
+fn canonical(dir: &Path, image: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .arg("--image")
+        .arg(image)
+        .arg("--canonical")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Lines of `after` that aren't in the prefix or suffix it shares with
+/// `before`, an upper bound of what a line diff shows as added.
+fn changed_lines(before: &str, after: &str) -> usize {
+    let (before, after): (Vec<_>, Vec<_>) = (before.lines().collect(), after.lines().collect());
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    after.len() - prefix - suffix
+}
+
+#[test]
+fn walls_and_checkpoints_are_sorted_by_position() {
+    let wall = |start: (u32, u32), end: (u32, u32)| Wall {
+        start: Point::new(start.0, start.1),
+        end: Some(Point::new(end.0, end.1)),
+    };
+    let mut lvl: Lvl = LvlBuilder::new(8, 8)
+        .start(Point::new(0, 0))
+        .end(Point::new(7, 7))
+        .wall(Point::new(1, 5), Point::new(6, 5))
+        .wall(Point::new(2, 1), Point::new(2, 3))
+        .wall(Point::new(2, 1), Point::new(5, 1))
+        .wall(Point::new(2, 1), Point::new(3, 1))
+        .checkpoint(Point::new(4, 6))
+        .checkpoint(Point::new(6, 2))
+        .checkpoint(Point::new(1, 2))
+        .build()
+        .unwrap();
+    lvl.canonicalize();
+
+    assert_eq!(
+        lvl.walls,
+        [
+            wall((2, 1), (3, 1)),
+            wall((2, 1), (5, 1)),
+            wall((2, 1), (2, 3)),
+            wall((1, 5), (6, 5)),
+        ]
+    );
+    assert_eq!(
+        lvl.checkpoints,
+        [Point::new(1, 2), Point::new(6, 2), Point::new(4, 6)]
+    );
+}
+
+#[test]
+fn runs_on_the_same_image_are_identical() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-canonical-identical");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let image = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/rooms.png");
+
+    let json = canonical(&dir, &image);
+    assert_eq!(canonical(&dir, &image), json);
+    assert!(json.ends_with("}\n"));
+    assert!(json.starts_with("{\n  \"checkpoints\""));
+}
+
+#[test]
+fn one_flipped_pixel_changes_a_few_lines() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-canonical-flip");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/maze.png");
+    let mut image = image::open(&golden).unwrap().to_rgba8();
+    // Empty square in the middle of a corridor
+    let (x, y) = (3, 21);
+    assert_eq!(image.get_pixel(x, y).0, SquareType::Empty.to_rgba());
+    image.put_pixel(x, y, Rgba(SquareType::Wall.to_rgba()));
+    let flipped = dir.join("flipped.png");
+    image.save(&flipped).unwrap();
+
+    let before = canonical(&dir, &golden);
+    let after = canonical(&dir, &flipped);
+    assert_ne!(before, after);
+    let changed = changed_lines(&before, &after);
+    assert!(
+        changed <= 20,
+        "{changed} of {} lines",
+        after.lines().count()
+    );
+}
+
+#[test]
+fn canonical_is_json_only() {
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .arg("--image")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/tiny.png"))
+        .args(["--canonical", "--format", "bin"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--canonical only applies to json"));
+}
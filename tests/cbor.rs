@@ -0,0 +1,35 @@
+#![cfg(feature = "cbor")]
+
+use maze_lvl_maker::{parse_ascii, scan_grid, ScanOptions};
+
+/// Has a single square wall, so the null `end` is part of the fixture.
+const LEVEL: &str = "\
+#######
+#S..#C#
+#.#...#
+#...#E#
+#######
+";
+
+/// Written by `maze-lvl-maker --ascii` of `LEVEL` with `--format cbor`.
+/// Regenerate it when the output format changes on purpose.
+const GOLDEN: &[u8] = include_bytes!("fixtures/level.cbor");
+
+fn encoded() -> Vec<u8> {
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&serde_json::to_value(&lvl).unwrap(), &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn cbor_matches_golden_fixture() {
+    assert_eq!(encoded(), GOLDEN);
+}
+
+#[test]
+fn cbor_field_names_match_json() {
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    let from_cbor: serde_json::Value = ciborium::from_reader(GOLDEN).unwrap();
+    assert_eq!(from_cbor, serde_json::to_value(&lvl).unwrap());
+}
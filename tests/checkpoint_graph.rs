@@ -0,0 +1,84 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{parse_ascii, scan_grid, Lvl, Point, ScanOptions};
+
+/// The checkpoint in the right room can't be reached from anywhere else.
+const LEVEL: &str = "\
+#########
+#S..C#..#
+#.####C.#
+#...E#..#
+#########
+";
+
+fn lvl() -> Lvl {
+    scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default())
+}
+
+#[test]
+fn edges_are_shortest_path_lengths() {
+    let lvl = lvl();
+    assert_eq!(
+        lvl.checkpoint_graph_nodes(),
+        [
+            Point::new(1, 1),
+            Point::new(4, 3),
+            Point::new(4, 1),
+            Point::new(6, 2),
+        ]
+    );
+    assert_eq!(
+        lvl.checkpoint_graph(),
+        [
+            vec![(1, 5), (2, 3)],
+            vec![(0, 5), (2, 8)],
+            vec![(0, 3), (1, 8)],
+            vec![],
+        ]
+    );
+}
+
+#[test]
+fn edges_go_both_ways() {
+    let graph = lvl().checkpoint_graph();
+    for (from, edges) in graph.iter().enumerate() {
+        for &(to, length) in edges {
+            assert!(graph[to].contains(&(from, length)), "{from} -> {to}");
+        }
+    }
+}
+
+#[test]
+fn unreachable_checkpoints_have_no_edges() {
+    let graph = lvl().checkpoint_graph();
+    assert!(graph[3].is_empty());
+    assert!(graph.iter().flatten().all(|(to, _)| *to != 3));
+}
+
+#[test]
+fn checkpoint_graph_flag() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-checkpoint-graph");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["--ascii", "level.txt", "--checkpoint-graph"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(
+        json["checkpoint_graph"],
+        serde_json::json!({
+            "nodes": [
+                { "x": 1, "y": 1 },
+                { "x": 4, "y": 3 },
+                { "x": 4, "y": 1 },
+                { "x": 6, "y": 2 },
+            ],
+            "adjacency": [[[1, 5], [2, 3]], [[0, 5], [2, 8]], [[0, 3], [1, 8]], []],
+        })
+    );
+}
@@ -0,0 +1,181 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{
+    scan_image, Chunk, Grid, Lvl, LvlBuilder, Mesh, Point, Rect, ScanOptions, Wall, Wrap,
+};
+
+fn golden(name: &str) -> Lvl {
+    golden_with(name, ScanOptions::default())
+}
+
+fn golden_with(name: &str, options: ScanOptions) -> Lvl {
+    let path = format!("{}/tests/golden/{name}.png", env!("CARGO_MANIFEST_DIR"));
+    scan_image(&image::open(path).unwrap(), options)
+}
+
+/// The level with its walls replaced by those of every chunk.
+fn reassembled(lvl: &Lvl, chunks: &[Chunk]) -> Lvl {
+    let mut joined = lvl.clone();
+    joined.walls = chunks
+        .iter()
+        .flat_map(|chunk| chunk.walls.clone())
+        .collect();
+    joined.dynamic_walls = chunks
+        .iter()
+        .flat_map(|chunk| chunk.dynamic_walls.clone())
+        .collect();
+    joined.rects = chunks
+        .iter()
+        .flat_map(|chunk| chunk.rects.clone())
+        .collect();
+    joined.wall_paths = chunks
+        .iter()
+        .flat_map(|chunk| chunk.wall_paths.clone())
+        .collect();
+    joined
+}
+
+/// Squares of the walls and dynamic walls of a chunk. Wrapping walls are
+/// split at the seam before they're chunked, so none of them wrap.
+fn squares(chunk: &Chunk) -> Vec<Point> {
+    chunk
+        .walls
+        .iter()
+        .chain(
+            chunk
+                .dynamic_walls
+                .iter()
+                .map(|dynamic_wall| &dynamic_wall.wall),
+        )
+        .inspect(|wall| assert!(!wall.wraps(), "{wall:?}"))
+        .flat_map(|wall| wall.squares())
+        .collect()
+}
+
+#[test]
+fn chunks_cover_the_same_squares_as_the_whole_level() {
+    let mut levels = vec![golden("maze"), golden("rooms"), golden("tiny")];
+    for mesh in [Mesh::Rect, Mesh::Polyline] {
+        levels.push(golden_with(
+            "maze",
+            ScanOptions {
+                mesh,
+                ..ScanOptions::default()
+            },
+        ));
+    }
+    levels.push(golden_with(
+        "rooms",
+        ScanOptions {
+            diagonals: true,
+            ..ScanOptions::default()
+        },
+    ));
+    let mut wrapped = golden("maze");
+    wrapped.set_wrap(Wrap::Both);
+    levels.push(wrapped);
+
+    for lvl in levels {
+        for size in [1, 3, 4, 7, 64] {
+            let chunks = lvl.chunks(size);
+            assert_eq!(
+                Grid::from_lvl(&reassembled(&lvl, &chunks)),
+                Grid::from_lvl(&lvl),
+                "{size}"
+            );
+        }
+    }
+}
+
+#[test]
+fn every_wall_lies_inside_its_chunk() {
+    let lvl = golden("maze");
+    let size = 4;
+    let chunks = lvl.chunks(size);
+    assert_eq!(
+        chunks.len() as u32,
+        lvl.width.div_ceil(size) * lvl.height.div_ceil(size)
+    );
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let columns = lvl.width.div_ceil(size);
+        assert_eq!(chunk.cy * columns + chunk.cx, index as u32);
+        let rect = Rect {
+            x: chunk.cx * size,
+            y: chunk.cy * size,
+            width: size,
+            height: size,
+        };
+        for square in squares(chunk) {
+            assert!(rect.contains(square.x, square.y), "{chunk:?} {square:?}");
+        }
+    }
+}
+
+#[test]
+fn walls_on_a_boundary_belong_to_the_chunk_of_their_squares() {
+    let lvl = LvlBuilder::new(8, 8)
+        .start(Point::new(0, 0))
+        .end(Point::new(7, 7))
+        // Last column of the first chunk
+        .wall(Point::new(3, 0), Point::new(3, 2))
+        // Crosses from the first chunk into the second
+        .wall(Point::new(2, 5), Point::new(6, 5))
+        .dynamic_wall(Point::new(1, 7), Point::new(4, 7), 3)
+        .build()
+        .unwrap();
+    let chunks = lvl.chunks(4);
+    let wall = |start: (u32, u32), end: (u32, u32)| Wall {
+        start: Point::new(start.0, start.1),
+        end: Some(Point::new(end.0, end.1)),
+    };
+
+    assert_eq!(chunks[0].walls, [wall((3, 0), (3, 2))]);
+    assert!(chunks[1].walls.is_empty());
+    assert_eq!(chunks[2].walls, [wall((2, 5), (3, 5))]);
+    assert_eq!(chunks[3].walls, [wall((4, 5), (6, 5))]);
+    assert_eq!(chunks[2].dynamic_walls[0].wall, wall((1, 7), (3, 7)));
+    assert_eq!(
+        chunks[3].dynamic_walls[0].wall,
+        Wall {
+            start: Point::new(4, 7),
+            end: None,
+        }
+    );
+    assert_eq!(chunks[3].dynamic_walls[0].period, 3);
+}
+
+#[test]
+fn flag_moves_the_walls_into_chunks_and_annotates_the_points() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-chunks");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let image = format!("{}/tests/golden/maze.png", env!("CARGO_MANIFEST_DIR"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["--image", &image, "--chunk-size", "8", "--no-orientation"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert!(json.get("walls").is_none());
+    assert_eq!(json["chunk_size"], 8);
+    let lvl = golden("maze");
+    for (key, point) in [("start", lvl.start), ("end", lvl.end)] {
+        assert_eq!(json[key]["cx"], point.x / 8, "{key}");
+        assert_eq!(json[key]["cy"], point.y / 8, "{key}");
+    }
+    let chunks = json["chunks"].as_array().unwrap();
+    assert_eq!(chunks.len(), lvl.chunks(8).len());
+    assert!(chunks
+        .iter()
+        .flat_map(|chunk| chunk["walls"].as_array().unwrap())
+        .all(|wall| wall.get("orientation").is_none()));
+
+    // Still reads back as the level without its walls
+    let read: Lvl = serde_json::from_value(json).unwrap();
+    assert_eq!((read.start, read.end), (lvl.start, lvl.end));
+    assert!(read.walls.is_empty());
+}
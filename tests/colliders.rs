@@ -0,0 +1,65 @@
+use maze_lvl_maker::{Collider, LvlBuilder, Point, Rect, Wall, Wrap};
+
+#[test]
+fn single_square_is_a_unit_box() {
+    let wall = Wall {
+        start: Point::new(2, 3),
+        end: None,
+    };
+    let collider = Collider {
+        cx: 2.5,
+        cy: 3.5,
+        hx: 0.5,
+        hy: 0.5,
+    };
+    assert_eq!(wall.collider(0.0), collider);
+}
+
+#[test]
+fn rect_padding_shrinks_every_side() {
+    let rect = Rect {
+        x: 0,
+        y: 1,
+        width: 3,
+        height: 2,
+    };
+    let collider = Collider {
+        cx: 1.5,
+        cy: 2.0,
+        hx: 1.4,
+        hy: 0.9,
+    };
+    assert_eq!(rect.collider(0.1), collider);
+}
+
+#[test]
+fn padding_never_makes_negative_extents() {
+    let wall = Wall {
+        start: Point::new(0, 0),
+        end: Some(Point::new(0, 4)),
+    };
+    let collider = wall.collider(0.75);
+    assert_eq!((collider.hx, collider.hy), (0.0, 1.75));
+}
+
+#[test]
+fn diagonal_and_wrapping_walls_are_split() {
+    let mut lvl = LvlBuilder::new(6, 6)
+        .start(Point::new(0, 5))
+        .end(Point::new(5, 5))
+        .wall(Point::new(1, 1), Point::new(2, 2))
+        .build()
+        .unwrap();
+    lvl.wrap = Wrap::X;
+    lvl.walls.push(Wall {
+        start: Point::new(4, 0),
+        end: Some(Point::new(1, 0)),
+    });
+
+    let centers: Vec<_> = lvl
+        .colliders(0.0)
+        .iter()
+        .map(|collider| (collider.cx, collider.cy))
+        .collect();
+    assert_eq!(centers, [(1.5, 1.5), (2.5, 2.5), (5.0, 0.5), (1.0, 0.5)]);
+}
@@ -0,0 +1,101 @@
+use std::{fs, io::Read, path::PathBuf, process::Command};
+
+use flate2::read::GzDecoder;
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-compress-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(["--ascii", "level.txt"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+fn gunzip(bytes: &[u8]) -> Vec<u8> {
+    let mut read = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut read).unwrap();
+    read
+}
+
+#[test]
+fn compression_follows_the_extension() {
+    let dir = dir("extension");
+    let plain = run(&dir, &["-p"]);
+
+    run(&dir, &["-p", "-o", "level.json.gz"]);
+    assert_eq!(gunzip(&fs::read(dir.join("level.json.gz")).unwrap()), plain);
+
+    run(&dir, &["-p", "-o", "level.json.zst"]);
+    let zstd = fs::read(dir.join("level.json.zst")).unwrap();
+    assert_eq!(zstd::decode_all(zstd.as_slice()).unwrap(), plain);
+}
+
+#[test]
+fn flag_overrides_the_extension() {
+    let dir = dir("flag");
+    let plain = run(&dir, &[]);
+
+    run(&dir, &["--compress", "none", "-o", "level.json.gz"]);
+    assert_eq!(fs::read(dir.join("level.json.gz")).unwrap(), plain);
+
+    run(&dir, &["--compress", "zstd", "-o", "level.json"]);
+    assert!(!dir.join("level.json").exists());
+    let zstd = fs::read(dir.join("level.json.zst")).unwrap();
+    assert_eq!(zstd::decode_all(zstd.as_slice()).unwrap(), plain);
+
+    // Alone it still means gzip
+    assert_eq!(gunzip(&run(&dir, &["--compress"])), plain);
+}
+
+#[test]
+fn formats_are_compressed_underneath() {
+    let dir = dir("formats");
+    for format in ["tmx", "godot", "bin"] {
+        let plain = run(&dir, &["--format", format]);
+        let zstd = run(
+            &dir,
+            &[
+                "--format",
+                format,
+                "--compress",
+                "zstd",
+                "--compress-level",
+                "3",
+            ],
+        );
+        assert_eq!(
+            zstd::decode_all(zstd.as_slice()).unwrap(),
+            plain,
+            "{format}"
+        );
+    }
+    // The format is still inferred from the extension in front
+    run(&dir, &["-o", "level.tmx.zst"]);
+    let tmx = fs::read(dir.join("level.tmx.zst")).unwrap();
+    assert_eq!(
+        zstd::decode_all(tmx.as_slice()).unwrap(),
+        run(&dir, &["--format", "tmx"])
+    );
+}
@@ -0,0 +1,99 @@
+use std::{fs, path::PathBuf, process::Command};
+
+const LEVEL: &str = "\
+#####
+#S.E#
+#####
+";
+
+/// A working directory with the level and an empty home directory in it.
+fn dirs(name: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-config-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    let home = dir.join("home");
+    fs::create_dir_all(&home).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    (dir, home)
+}
+
+fn run(dir: &PathBuf, home: &PathBuf, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .env("HOME", home)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &std::process::Output) -> String {
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn config_sets_defaults() {
+    let (dir, home) = dirs("defaults");
+    let plain = stdout(&run(&dir, &home, &["--ascii", "level.txt"]));
+    fs::write(
+        dir.join(".maze-lvl-maker.toml"),
+        "pretty = true\nascii = \"level.txt\"\n",
+    )
+    .unwrap();
+
+    let configured = stdout(&run(&dir, &home, &[]));
+    assert!(configured.starts_with("{\n"));
+    assert_ne!(configured, plain);
+}
+
+#[test]
+fn current_directory_wins_over_home() {
+    let (dir, home) = dirs("home");
+    fs::write(home.join(".maze-lvl-maker.toml"), "emit = \"grid\"\n").unwrap();
+    assert!(stdout(&run(&dir, &home, &["--ascii", "level.txt"])).contains("\"grid\""));
+
+    fs::write(dir.join(".maze-lvl-maker.toml"), "emit = \"rle\"\n").unwrap();
+    assert!(stdout(&run(&dir, &home, &["--ascii", "level.txt"])).contains("\"rows_rle\""));
+}
+
+#[test]
+fn command_line_takes_precedence() {
+    let (dir, home) = dirs("precedence");
+    fs::write(
+        dir.join(".maze-lvl-maker.toml"),
+        "emit = \"grid\"\ncsv = \"missing.csv\"\n",
+    )
+    .unwrap();
+
+    // The input group only takes one, so the config's csv is left out too
+    let output = stdout(&run(
+        &dir,
+        &home,
+        &["--ascii", "level.txt", "--emit", "rle"],
+    ));
+    assert!(output.contains("\"rows_rle\"") && !output.contains("\"grid\""));
+}
+
+#[test]
+fn unknown_keys_warn() {
+    let (dir, home) = dirs("unknown");
+    fs::write(dir.join(".maze-lvl-maker.toml"), "tolerance = 3\n").unwrap();
+
+    let output = run(&dir, &home, &["--ascii", "level.txt"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Warning: unknown key `tolerance` in .maze-lvl-maker.toml\n"
+    );
+}
+
+#[test]
+fn invalid_config_is_an_error() {
+    let (dir, home) = dirs("invalid");
+    fs::write(dir.join(".maze-lvl-maker.toml"), "pretty = \n").unwrap();
+
+    let output = run(&dir, &home, &["--ascii", "level.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Error: invalid .maze-lvl-maker.toml"));
+}
@@ -0,0 +1,60 @@
+use maze_lvl_maker::{contours, parse_ascii, Vertex};
+
+/// Shoelace area, negative for rings that run counter-clockwise on screen.
+fn signed_area(ring: &[Vertex]) -> f64 {
+    let next = ring.iter().cycle().skip(1);
+    ring.iter()
+        .zip(next)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f64>()
+        / 2.0
+}
+
+#[test]
+fn donut_has_one_hole() {
+    let grid = parse_ascii("#####\n#...#\n#...#\n#####").unwrap();
+    let polygons = contours(&grid);
+
+    assert_eq!(polygons.len(), 1);
+    let [polygon] = &polygons[..] else {
+        unreachable!()
+    };
+    assert_eq!(polygon.outer.len(), 4);
+    assert_eq!(polygon.holes.len(), 1);
+    assert_eq!(polygon.holes[0].len(), 4);
+    assert_eq!(signed_area(&polygon.outer), -20.0);
+    assert_eq!(signed_area(&polygon.holes[0]), 6.0);
+    assert!(polygon.contains(0, 0));
+    assert!(!polygon.contains(2, 1));
+}
+
+#[test]
+fn diagonal_squares_are_separate_polygons() {
+    let grid = parse_ascii("#.\n.#").unwrap();
+    let polygons = contours(&grid);
+
+    assert_eq!(polygons.len(), 2);
+    for polygon in &polygons {
+        assert_eq!(polygon.outer.len(), 4);
+        assert!(polygon.holes.is_empty());
+        assert_eq!(signed_area(&polygon.outer), -1.0);
+    }
+    assert!(polygons[0].contains(0, 0) && !polygons[0].contains(1, 1));
+    assert!(polygons[1].contains(1, 1) && !polygons[1].contains(0, 0));
+}
+
+#[test]
+fn single_square_is_its_corners() {
+    let polygons = contours(&parse_ascii("...\n.#.\n...").unwrap());
+
+    assert_eq!(polygons.len(), 1);
+    let mut corners: Vec<_> = polygons[0].outer.iter().map(|v| (v.x, v.y)).collect();
+    corners.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(corners, [(1.0, 1.0), (1.0, 2.0), (2.0, 1.0), (2.0, 2.0)]);
+    assert!(polygons[0].holes.is_empty());
+}
+
+#[test]
+fn no_walls_no_polygons() {
+    assert!(contours(&parse_ascii("S..\n..E").unwrap()).is_empty());
+}
@@ -0,0 +1,148 @@
+use std::{fs, path::Path, process::Command};
+
+use maze_lvl_maker::parse_ascii;
+use serde_json::Value;
+
+const LEVEL: &str = "\
+############
+#..........#
+#.#######..#
+#.#S....#..#
+#.#.###.#..#
+#.#...#.#..#
+#.#####E#..#
+#..........#
+############";
+
+fn run(dir: &Path, args: &[&str]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    parse_ascii(LEVEL)
+        .unwrap()
+        .to_image(1)
+        .save(dir.join("level.png"))
+        .unwrap();
+    dir
+}
+
+/// `point` moved by the crop origin, `null` for walls without an end.
+fn shifted(point: &Value) -> Value {
+    if point.is_null() {
+        return Value::Null;
+    }
+    serde_json::json!({
+        "x": point["x"].as_u64().unwrap() + 2,
+        "y": point["y"].as_u64().unwrap() + 1,
+    })
+}
+
+#[test]
+fn origin_offset_keeps_absolute_coordinates() {
+    let dir = fixture("maze-lvl-maker-crop-origin");
+    let crop = ["--image", "level.png", "--crop", "2,1,7,6"];
+    let plain = run(&dir, &crop);
+    let offset = run(&dir, &[&crop[..], &["--crop-origin-offset"]].concat());
+
+    assert_eq!((&plain["width"], &plain["height"]), (&7.into(), &6.into()));
+    // Grown to reach the far corner of the crop
+    assert_eq!(
+        (&offset["width"], &offset["height"]),
+        (&9.into(), &7.into())
+    );
+    assert_eq!(offset["start"], serde_json::json!({ "x": 3, "y": 3 }));
+    assert_eq!(offset["end"], serde_json::json!({ "x": 7, "y": 6 }));
+    assert_eq!(offset["start"], shifted(&plain["start"]));
+    assert_eq!(offset["end"], shifted(&plain["end"]));
+
+    let walls = |lvl: &Value| lvl["walls"].as_array().unwrap().clone();
+    let shifted_walls: Vec<Value> = walls(&plain)
+        .into_iter()
+        .map(|mut wall| {
+            wall["start"] = shifted(&wall["start"]);
+            wall["end"] = shifted(&wall["end"]);
+            wall
+        })
+        .collect();
+    assert_eq!(walls(&offset), shifted_walls);
+    // The wall square at (2, 2) of the image, not of the crop
+    assert!(walls(&offset)
+        .iter()
+        .any(|wall| wall["start"] == serde_json::json!({ "x": 2, "y": 2 })));
+}
+
+#[test]
+fn origin_offset_works_with_the_binary_formats() {
+    let dir = fixture("maze-lvl-maker-crop-origin-bin");
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args([
+            "--image",
+            "level.png",
+            "--crop",
+            "2,1,7,6",
+            "--crop-origin-offset",
+        ])
+        .args(["--format", "bin", "--outfile", "level.bin"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+#[test]
+fn crop_needs_an_image() {
+    let dir = fixture("maze-lvl-maker-crop-ascii");
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["--ascii", "level.txt", "--crop", "2,1,7,6"])
+        .output()
+        .unwrap();
+    // Rejected by clap as a usage error
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("'--ascii <ASCII>' cannot be used with '--crop <CROP>'"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn origin_offset_conflicts_with_moving_the_level() {
+    let dir = fixture("maze-lvl-maker-crop-origin-conflicts");
+    for flag in [
+        &["--normalize"][..],
+        &["--tile", "2x1"],
+        &["--mirror", "horizontal"],
+        &["--rotate", "90"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args([
+                "--image",
+                "level.png",
+                "--crop",
+                "2,1,7,6",
+                "--crop-origin-offset",
+            ])
+            .args(flag)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{flag:?}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains(&format!("cannot be used with '{}", flag[0])),
+            "{stderr}"
+        );
+    }
+}
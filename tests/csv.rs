@@ -0,0 +1,76 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{parse_csv, CsvError, Lvl, Point, SquareType};
+
+#[test]
+fn fields_are_square_names_in_any_case() {
+    let grid = parse_csv("wall,Start, END\nCheckpoint,empty,WALL\n").unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid.get(0, 0), SquareType::Wall);
+    assert_eq!(grid.get(1, 0), SquareType::Start);
+    assert_eq!(grid.get(2, 0), SquareType::End);
+    assert_eq!(grid.get(0, 1), SquareType::Checkpoint);
+    assert_eq!(grid.get(1, 1), SquareType::Empty);
+    assert_eq!(grid.get(2, 1), SquareType::Wall);
+}
+
+#[test]
+fn empty_fields_are_empty_squares() {
+    let grid = parse_csv("Wall,,End\n, ,Start\n").unwrap();
+    assert_eq!(grid.get(1, 0), SquareType::Empty);
+    assert_eq!(grid.get(0, 1), SquareType::Empty);
+    assert_eq!(grid.get(1, 1), SquareType::Empty);
+    assert_eq!(grid.get(2, 0), SquareType::End);
+}
+
+#[test]
+fn short_rows_are_padded_with_empty_squares() {
+    let grid = parse_csv("Wall,Wall,Wall,Wall\nStart\nWall,End\n").unwrap();
+    assert_eq!((grid.width(), grid.height()), (4, 3));
+    assert_eq!(grid.get(0, 1), SquareType::Start);
+    for x in 1..4 {
+        assert_eq!(grid.get(x, 1), SquareType::Empty);
+    }
+    assert_eq!(grid.get(1, 2), SquareType::End);
+    assert_eq!(grid.get(3, 2), SquareType::Empty);
+}
+
+#[test]
+fn unknown_squares_and_long_rows_are_errors() {
+    let error = parse_csv("Wall,Start\nEnd,Lava\n").unwrap_err();
+    assert!(
+        matches!(
+            &error,
+            CsvError::UnknownSquare { field, row: 2, column: 2 } if field == "Lava"
+        ),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "unknown square \"Lava\" at row 2, column 2"
+    );
+
+    let error = parse_csv("Start,End\nWall,Wall,Wall\n").unwrap_err();
+    assert!(
+        matches!(error, CsvError::RowTooLong { row: 2, width: 2 }),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn csv_flag_reads_the_level() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-csv");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.csv"), "Wall,Wall,Wall\nStart,,End\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["--csv", "level.csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let lvl: Lvl = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!((lvl.start, lvl.end), (Point::new(0, 1), Point::new(2, 1)));
+    assert_eq!(lvl.walls.len(), 1);
+}
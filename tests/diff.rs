@@ -0,0 +1,124 @@
+use std::{fs, path::Path, process::Command};
+
+use maze_lvl_maker::{scan_image, Change, Lvl, LvlBuilder, Point, ScanOptions, Wall};
+
+fn level() -> LvlBuilder {
+    LvlBuilder::new(6, 6)
+        .start(Point::new(1, 1))
+        .end(Point::new(4, 4))
+        .checkpoint(Point::new(1, 4))
+        .wall(Point::new(0, 0), Point::new(5, 0))
+        .wall(Point::new(0, 1), Point::new(0, 5))
+        .wall(Point::new(3, 2), Point::new(3, 3))
+}
+
+fn wall(start: (u32, u32), end: (u32, u32)) -> Wall {
+    Wall {
+        start: Point::new(start.0, start.1),
+        end: Some(Point::new(end.0, end.1)),
+    }
+}
+
+#[test]
+fn identical_levels() {
+    let lvl = level().build().unwrap();
+    let mut reordered = lvl.clone();
+    reordered.walls.reverse();
+
+    let diff = lvl.diff(&reordered);
+    assert!(diff.is_empty());
+    assert_eq!(serde_json::to_string(&diff).unwrap(), "{}");
+}
+
+#[test]
+fn walls_and_points() {
+    let before = level().build().unwrap();
+    let mut after = level()
+        .start(Point::new(2, 1))
+        .checkpoint(Point::new(4, 1))
+        .wall(Point::new(5, 1), Point::new(5, 5))
+        .build()
+        .unwrap();
+    after.walls.retain(|wall| wall.start != Point::new(0, 1));
+    after.walls[1] = wall((3, 2), (3, 4));
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.added_walls, [wall((5, 1), (5, 5))]);
+    assert_eq!(diff.removed_walls, [wall((0, 1), (0, 5))]);
+    assert_eq!(
+        diff.modified_walls,
+        [Change {
+            from: wall((3, 2), (3, 3)),
+            to: wall((3, 2), (3, 4)),
+        }]
+    );
+    assert_eq!(
+        diff.moved_start,
+        Some(Change {
+            from: Point::new(1, 1),
+            to: Point::new(2, 1),
+        })
+    );
+    assert_eq!(diff.moved_end, None);
+    assert_eq!(diff.added_checkpoints, [Point::new(4, 1)]);
+    assert!(diff.removed_checkpoints.is_empty());
+    assert!(diff.resized.is_none());
+
+    let json = serde_json::to_value(&diff).unwrap();
+    assert_eq!(
+        json["moved_start"]["to"],
+        serde_json::json!({"x": 2, "y": 1})
+    );
+    assert!(json.get("moved_end").is_none());
+}
+
+#[test]
+fn golden_levels_read_back() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    for name in ["maze", "rooms", "tiny"] {
+        let json = fs::read_to_string(directory.join(name).with_extension("json")).unwrap();
+        let read: Lvl = serde_json::from_str(&json).unwrap();
+        let image = image::open(directory.join(name).with_extension("png")).unwrap();
+        assert_eq!(read, scan_image(&image, ScanOptions::default()), "{name}");
+    }
+}
+
+#[test]
+fn exit_code_tells_whether_levels_differ() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-diff");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("before.txt"), "#######\n#S..CE#\n#######\n").unwrap();
+    fs::write(dir.join("after.txt"), "#######\n#S.C.E#\n#######\n").unwrap();
+    fs::write(dir.join("broken.json"), "{\"width\":").unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    run(&["--ascii", "before.txt", "-o", "before.json.gz"]);
+
+    let same = run(&["--ascii", "before.txt", "--diff", "before.json.gz"]);
+    assert_eq!(same.status.code(), Some(0));
+    assert_eq!(same.stdout, b"{}");
+
+    let changed = run(&["--ascii", "after.txt", "--diff", "before.json.gz"]);
+    assert_eq!(changed.status.code(), Some(1));
+    assert!(changed.stderr.is_empty());
+    let diff: serde_json::Value = serde_json::from_slice(&changed.stdout).unwrap();
+    assert_eq!(
+        diff,
+        serde_json::json!({
+            "added_checkpoints": [{"x": 3, "y": 1}],
+            "removed_checkpoints": [{"x": 4, "y": 1}],
+        })
+    );
+
+    let broken = run(&["--ascii", "after.txt", "--diff", "broken.json"]);
+    assert_eq!(broken.status.code(), Some(2));
+    assert!(String::from_utf8(broken.stderr)
+        .unwrap()
+        .contains("broken.json"));
+}
@@ -0,0 +1,92 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use image::{Rgba, RgbaImage};
+
+fn level(name: &str, ascii: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("maze-lvl-maker-dry-run-{name}.txt"));
+    fs::write(&path, ascii).unwrap();
+    path
+}
+
+fn dry_run(path: &PathBuf, extra: &[&str]) -> std::process::Output {
+    let input = if path.extension().is_some_and(|extension| extension == "png") {
+        "--image"
+    } else {
+        "--ascii"
+    };
+    Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .arg(input)
+        .arg(path)
+        .arg("--dry-run")
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn prints_a_summary_instead_of_the_level() {
+    let path = level(
+        "solvable",
+        "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+",
+    );
+    let outfile = std::env::temp_dir().join("maze-lvl-maker-dry-run-solvable.json");
+    let _ = fs::remove_file(&outfile);
+
+    let output = dry_run(
+        &path,
+        &["--check-solvable", "-o", outfile.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!outfile.exists());
+    let summary = String::from_utf8(output.stderr).unwrap();
+    for line in [
+        "image: 9x5",
+        "walls: 7",
+        "wall length: 37",
+        "horizontal walls: 2",
+        "vertical walls: 5",
+        "checkpoints: 1",
+        "solvable: yes",
+    ] {
+        assert!(summary.lines().any(|summary| summary == line), "{summary}");
+    }
+}
+
+#[test]
+fn fails_when_unsolvable() {
+    let path = level("unsolvable", "#####\n#S#E#\n#####\n");
+
+    let output = dry_run(&path, &["--check-solvable"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("solvable: no"));
+
+    // Not validated without the check
+    let output = dry_run(&path, &[]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn shows_warnings() {
+    // Start, a dynamic wall and the end in a corridor
+    let mut image = RgbaImage::from_pixel(5, 3, Rgba([0, 0, 0, 255]));
+    image.put_pixel(1, 1, Rgba([0, 255, 0, 255]));
+    image.put_pixel(2, 1, Rgba([0, 0, 0, 10]));
+    image.put_pixel(3, 1, Rgba([255, 0, 0, 255]));
+    let path = std::env::temp_dir().join("maze-lvl-maker-dry-run-dynamic.png");
+    image.save(&path).unwrap();
+
+    let output = dry_run(&path, &[]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("dynamic wall"));
+}
@@ -0,0 +1,114 @@
+use std::process::Command;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use maze_lvl_maker::{scan_grid, Grid, Lvl, Point, ScanOptions, SquareType};
+
+/// The only route to the end goes through the `%`.
+const BLOCKED: &[&str] = &[
+    "#######", //
+    "#S.%.E#", "#######",
+];
+
+/// Same, with a way around the `%`.
+const DETOUR: &[&str] = &[
+    "#######", //
+    "#S.%.E#", "#.###.#", "#.....#", "#######",
+];
+
+/// The end can't be reached even with the `%` open.
+const WALLED_IN: &[&str] = &[
+    "#######", //
+    "#S.%#E#", "#######",
+];
+
+/// Image of `rows` with every `%` as translucent black of `alpha`.
+fn image(rows: &[&str], alpha: u8) -> DynamicImage {
+    let mut image = RgbaImage::new(rows[0].len() as u32, rows.len() as u32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, square) in row.chars().enumerate() {
+            let color = match square {
+                '%' => [0, 0, 0, alpha],
+                '#' => SquareType::Wall.to_rgba(),
+                'S' => SquareType::Start.to_rgba(),
+                'E' => SquareType::End.to_rgba(),
+                _ => SquareType::Empty.to_rgba(),
+            };
+            image.put_pixel(x as u32, y as u32, Rgba(color));
+        }
+    }
+    DynamicImage::ImageRgba8(image)
+}
+
+fn lvl(rows: &[&str], alpha: u8) -> Lvl {
+    scan_grid(
+        &Grid::from_image(&image(rows, alpha)),
+        ScanOptions::default(),
+    )
+}
+
+#[test]
+fn partial_alpha_black_is_a_dynamic_wall() {
+    for alpha in [1, 10, 128, 254] {
+        assert_eq!(
+            SquareType::from_rgba([0, 0, 0, alpha]),
+            Some(SquareType::DynamicWall(alpha))
+        );
+        let grid = Grid::from_image(&image(BLOCKED, alpha));
+        assert_eq!(grid.get(3, 1), SquareType::DynamicWall(alpha));
+
+        let lvl = lvl(BLOCKED, alpha);
+        assert_eq!(lvl.dynamic_walls.len(), 1);
+        assert_eq!(lvl.dynamic_walls[0].wall.start, Point::new(3, 1));
+        assert_eq!(lvl.dynamic_walls[0].period, alpha);
+    }
+    // Transparent and opaque black are both solid walls
+    for alpha in [0, 255] {
+        assert_eq!(
+            SquareType::from_rgba([0, 0, 0, alpha]),
+            Some(SquareType::Wall)
+        );
+        assert!(lvl(BLOCKED, alpha).dynamic_walls.is_empty());
+    }
+    // Only black reads the alpha
+    assert_eq!(
+        SquareType::from_rgba([255, 0, 0, 100]),
+        Some(SquareType::End)
+    );
+    assert_eq!(
+        SquareType::from_rgba([255, 255, 255, 100]),
+        Some(SquareType::Empty)
+    );
+}
+
+#[test]
+fn needs_dynamic_walls_only_when_every_route_goes_through_one() {
+    assert!(lvl(BLOCKED, 10).needs_dynamic_walls());
+    assert!(!lvl(DETOUR, 10).needs_dynamic_walls());
+    // Unsolvable either way
+    assert!(!lvl(WALLED_IN, 10).needs_dynamic_walls());
+    // No dynamic walls to go through
+    assert!(!lvl(BLOCKED, 255).needs_dynamic_walls());
+}
+
+#[test]
+fn warns_when_the_level_needs_dynamic_walls() {
+    let dir = std::env::temp_dir();
+    let run = |name: &str, rows: &[&str]| {
+        let path = dir.join(format!("maze-lvl-maker-dynamic-{name}.png"));
+        image(rows, 10).save(&path).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .env("RUST_LOG", "warn")
+            .arg("--image")
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    let warning = "Every route to the end goes through a dynamic wall";
+    let stderr = run("blocked", BLOCKED);
+    assert!(stderr.contains(warning), "{stderr}");
+    let stderr = run("detour", DETOUR);
+    assert!(!stderr.contains(warning), "{stderr}");
+}
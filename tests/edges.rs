@@ -0,0 +1,60 @@
+use maze_lvl_maker::{parse_ascii, scan_edges, EdgeModelError, EdgeWall, Point, Side};
+
+#[test]
+fn walls_between_cells() {
+    let grid = parse_ascii(
+        "\
+#####
+#S..#
+###.#
+#E..#
+#####
+",
+    )
+    .unwrap();
+    let lvl = scan_edges(&grid).unwrap();
+
+    assert_eq!((lvl.width, lvl.height), (2, 2));
+    assert_eq!((lvl.start, lvl.end), (Point::new(0, 0), Point::new(0, 1)));
+    assert!(lvl.walls.is_empty());
+
+    let wall = |x, y, side| EdgeWall { x, y, side };
+    assert_eq!(
+        lvl.edge_walls,
+        [
+            wall(0, 0, Side::Top),
+            wall(0, 0, Side::Left),
+            wall(0, 0, Side::Bottom),
+            wall(1, 0, Side::Top),
+            wall(1, 0, Side::Right),
+            wall(0, 1, Side::Left),
+            wall(0, 1, Side::Bottom),
+            wall(1, 1, Side::Right),
+            wall(1, 1, Side::Bottom),
+        ]
+    );
+}
+
+#[test]
+fn images_that_dont_fit_the_convention() {
+    let even = parse_ascii("####\n#SE#\n####\n").unwrap();
+    assert_eq!(
+        scan_edges(&even),
+        Err(EdgeModelError::Size {
+            width: 4,
+            height: 3
+        })
+    );
+
+    let open = parse_ascii("#####\n#S.E.\n#####\n").unwrap();
+    assert_eq!(
+        scan_edges(&open),
+        Err(EdgeModelError::OpenBorder(Point::new(4, 1)))
+    );
+
+    let on_line = parse_ascii("#####\n#.SE#\n#####\n").unwrap();
+    assert_eq!(
+        scan_edges(&on_line),
+        Err(EdgeModelError::PointOnWallLine(Point::new(2, 1)))
+    );
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, CStr, CString};
+
+use image::{Rgba, RgbaImage};
+use maze_lvl_maker::{
+    maze_lvl_free_string, maze_lvl_last_error, maze_lvl_parse_image, MAZE_LVL_ERR_BUFFER_TOO_SMALL,
+    MAZE_LVL_ERR_IMAGE, MAZE_LVL_OK,
+};
+
+fn level_png(name: &str) -> CString {
+    let mut image = RgbaImage::from_pixel(4, 3, Rgba([255, 255, 255, 255]));
+    for x in 0..4 {
+        image.put_pixel(x, 0, Rgba([0, 0, 0, 255]));
+    }
+    image.put_pixel(0, 2, Rgba([0, 255, 0, 255]));
+    image.put_pixel(3, 2, Rgba([255, 0, 0, 255]));
+
+    let path = std::env::temp_dir().join(name);
+    image.save(&path).unwrap();
+    CString::new(path.to_str().unwrap()).unwrap()
+}
+
+fn last_error() -> String {
+    let error = maze_lvl_last_error();
+    let message = unsafe { CStr::from_ptr(error) }
+        .to_str()
+        .unwrap()
+        .to_owned();
+    unsafe { maze_lvl_free_string(error) };
+    message
+}
+
+#[test]
+fn writes_null_terminated_json() {
+    let path = level_png("maze_lvl_ffi_json.png");
+    let mut buffer = vec![1 as c_char; 1024];
+    let mut len = buffer.len();
+
+    let code = unsafe { maze_lvl_parse_image(path.as_ptr(), buffer.as_mut_ptr(), &mut len) };
+    assert_eq!(code, MAZE_LVL_OK);
+    let json = unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_str().unwrap();
+    assert_eq!(json.len(), len);
+    let lvl: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(lvl["walls"].as_array().unwrap().len(), 1);
+    assert_eq!(lvl["end"], serde_json::json!({"x": 3, "y": 2}));
+}
+
+#[test]
+fn small_buffer_reports_needed_size() {
+    let path = level_png("maze_lvl_ffi_small.png");
+    let mut buffer = vec![0 as c_char; 8];
+    let mut len = buffer.len();
+
+    let code = unsafe { maze_lvl_parse_image(path.as_ptr(), buffer.as_mut_ptr(), &mut len) };
+    assert_eq!(code, MAZE_LVL_ERR_BUFFER_TOO_SMALL);
+    assert!(len > 8);
+    assert!(buffer.iter().all(|byte| *byte == 0));
+
+    buffer.resize(len, 0);
+    let code = unsafe { maze_lvl_parse_image(path.as_ptr(), buffer.as_mut_ptr(), &mut len) };
+    assert_eq!(code, MAZE_LVL_OK);
+    assert_eq!(len + 1, buffer.len());
+}
+
+#[test]
+fn missing_image_sets_last_error() {
+    let path = CString::new("/nonexistent/maze.png").unwrap();
+    let mut buffer = vec![0 as c_char; 64];
+    let mut len = buffer.len();
+
+    let code = unsafe { maze_lvl_parse_image(path.as_ptr(), buffer.as_mut_ptr(), &mut len) };
+    assert_eq!(code, MAZE_LVL_ERR_IMAGE);
+    assert!(last_error().starts_with("/nonexistent/maze.png: "));
+}
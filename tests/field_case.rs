@@ -0,0 +1,77 @@
+use std::{fs, path::PathBuf, process::Command};
+
+const LEVEL: &str = "\
+#######
+#S..#C#
+#.#...#
+#...#E#
+#######
+";
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-field-case-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(["--ascii", "level.txt"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn json(dir: &PathBuf, args: &[&str]) -> serde_json::Value {
+    let output = run(dir, args);
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn multi_word_keys_are_renamed_everywhere() {
+    let dir = dir("rename");
+
+    let camel = json(&dir, &["--field-case", "camel", "--largest-open-area"]);
+    assert_eq!(camel["formatVersion"], 2);
+    assert!(camel["largestOpenArea"].is_u64());
+    assert_eq!(camel["start"], serde_json::json!({"x": 1, "y": 1}));
+    assert!(camel.get("format_version").is_none());
+
+    let pascal = json(&dir, &["--field-case", "pascal", "--embed-solution"]);
+    assert_eq!(pascal["FormatVersion"], 2);
+    assert_eq!(pascal["Start"], serde_json::json!({"X": 1, "Y": 1}));
+    assert_eq!(pascal["Walls"][0]["Orientation"], "horizontal");
+    assert_eq!(pascal["Solution"][0], serde_json::json!({"X": 1, "Y": 1}));
+
+    // The default is what the level has always been written in
+    let snake = json(&dir, &[]);
+    assert_eq!(json(&dir, &["--field-case", "snake"]), snake);
+    assert_eq!(snake["format_version"], 2);
+}
+
+#[test]
+fn levels_are_read_back_in_any_case() {
+    let dir = dir("read");
+
+    for case in ["snake", "camel", "pascal"] {
+        let outfile = format!("{case}.json");
+        let output = run(&dir, &["--field-case", case, "-o", &outfile]);
+        assert!(output.status.success(), "{output:?}");
+
+        // No differences to the level it was written from
+        let output = run(&dir, &["--diff", &outfile]);
+        assert!(output.status.success(), "{case} {output:?}");
+    }
+}
+
+#[test]
+fn formats_written_from_the_level_are_refused() {
+    let dir = dir("refused");
+    let output = run(&dir, &["--field-case", "camel", "--format", "bin"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--field-case"));
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeSet;
+
+use maze_lvl_maker::{parse_ascii, scan_grid, LvlBuilder, Mesh, Point, ScanOptions, Wall, Wrap};
+
+const LEVEL: &str = "\
+#######
+#S..#C#
+#.#...#
+#...#E#
+#######
+";
+
+/// Written by `maze-lvl-maker --ascii` of `LEVEL` with `-o level.tres`.
+/// Regenerate it when the output format changes on purpose.
+const GOLDEN: &str = include_str!("fixtures/level.tres");
+
+fn metadata<'a>(tres: &'a str, name: &str) -> &'a str {
+    tres.lines()
+        .find_map(|line| line.strip_prefix(&format!("metadata/{name} = ")))
+        .unwrap()
+}
+
+#[test]
+fn godot_matches_golden_fixture() {
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    assert_eq!(lvl.to_godot(), GOLDEN);
+    assert!(!GOLDEN.contains("null"));
+}
+
+#[test]
+fn typed_values() {
+    let lvl = LvlBuilder::new(6, 6)
+        .start(Point::new(1, 1))
+        .end(Point::new(4, 4))
+        .checkpoint(Point::new(1, 4))
+        .checkpoint(Point::new(2, 4))
+        .optional_checkpoint(Point::new(4, 1))
+        .wall(Point::new(5, 5), Point::new(5, 5))
+        .dynamic_wall(Point::new(2, 2), Point::new(3, 2), 7)
+        .build()
+        .unwrap();
+    let tres = lvl.to_godot();
+
+    assert_eq!(metadata(&tres, "start"), "Vector2i(1, 1)");
+    assert_eq!(
+        metadata(&tres, "checkpoints"),
+        "PackedVector2Array(1, 4, 2, 4)"
+    );
+    assert_eq!(
+        metadata(&tres, "optional_checkpoints"),
+        "PackedVector2Array(4, 1)"
+    );
+    // A single square is a 1 by 1 rectangle rather than a null end
+    assert_eq!(
+        metadata(&tres, "walls"),
+        "Array[Rect2]([Rect2(5, 5, 1, 1)])"
+    );
+    assert_eq!(
+        metadata(&tres, "dynamic_walls"),
+        r#"Array[Dictionary]([{"rect": Rect2(2, 2, 2, 1), "period": 7}])"#
+    );
+}
+
+#[test]
+fn seam_and_diagonal_walls_are_split() {
+    let mut lvl = LvlBuilder::new(6, 4)
+        .start(Point::new(1, 1))
+        .end(Point::new(2, 1))
+        .wall(Point::new(3, 0), Point::new(1, 2))
+        .build()
+        .unwrap();
+    lvl.set_wrap(Wrap::X);
+    lvl.walls.push(Wall {
+        start: Point::new(4, 3),
+        end: Some(Point::new(1, 3)),
+    });
+    let tres = lvl.to_godot();
+
+    assert_eq!(metadata(&tres, "wrap"), "\"x\"");
+    assert_eq!(
+        metadata(&tres, "walls"),
+        "Array[Rect2]([Rect2(3, 0, 1, 1), Rect2(2, 1, 1, 1), Rect2(1, 2, 1, 1), \
+         Rect2(4, 3, 2, 1), Rect2(0, 3, 2, 1)])"
+    );
+}
+
+#[test]
+fn every_mesh_covers_the_same_squares() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    let squares = |mesh| {
+        let lvl = scan_grid(
+            &grid,
+            ScanOptions {
+                mesh,
+                ..ScanOptions::default()
+            },
+        );
+        let tres = lvl.to_godot();
+        let mut squares = BTreeSet::new();
+        for rect in metadata(&tres, "walls").split("Rect2(").skip(1) {
+            let numbers: Vec<u32> = rect
+                .trim_end_matches([')', ']', ',', ' '])
+                .split(", ")
+                .map(|number| number.parse().unwrap())
+                .collect();
+            let [x, y, width, height] = numbers[..] else {
+                unreachable!()
+            };
+            for y in y..y + height {
+                squares.extend((x..x + width).map(|x| (x, y)));
+            }
+        }
+        squares
+    };
+
+    let walls: BTreeSet<(u32, u32)> = (0..)
+        .zip(LEVEL.lines())
+        .flat_map(|(y, line)| {
+            (0..)
+                .zip(line.chars())
+                .filter(|(_, square)| *square == '#')
+                .map(move |(x, _)| (x, y))
+        })
+        .collect();
+    for mesh in [Mesh::Lines, Mesh::Rect, Mesh::Polyline] {
+        assert_eq!(squares(mesh), walls, "{mesh:?}");
+    }
+}
@@ -0,0 +1,61 @@
+//! Every `tests/golden/<name>.png` is scanned with the default options and
+//! compared byte for byte with `<name>.json`, the pretty json the command
+//! line tool writes with `--pretty`. Run with `BLESS=1` to overwrite the json
+//! with the current output after an intended change.
+
+use std::{fs, path::Path};
+
+use maze_lvl_maker::{scan_image, ScanOptions};
+
+/// Lines that differ, with their line numbers.
+fn diff(expected: &str, actual: &str) -> String {
+    let (expected, actual): (Vec<&str>, Vec<&str>) =
+        (expected.lines().collect(), actual.lines().collect());
+    (0..expected.len().max(actual.len()))
+        .filter(|line| expected.get(*line) != actual.get(*line))
+        .map(|line| {
+            format!(
+                "{:>5} - {}\n{:>5} + {}\n",
+                line + 1,
+                expected.get(line).unwrap_or(&""),
+                line + 1,
+                actual.get(line).unwrap_or(&"")
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn outputs_match_golden_files() {
+    let bless = std::env::var_os("BLESS").is_some_and(|bless| bless == "1");
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut images: Vec<_> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "png"))
+        .collect();
+    images.sort();
+    assert!(!images.is_empty(), "no images in {}", directory.display());
+
+    let mut failures = Vec::new();
+    for image in images {
+        let lvl = scan_image(&image::open(&image).unwrap(), ScanOptions::default());
+        let actual = serde_json::to_string_pretty(&lvl).unwrap();
+        let golden = image.with_extension("json");
+        if bless {
+            fs::write(&golden, &actual).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&golden).unwrap_or_default();
+        if actual != expected {
+            failures.push(format!(
+                "{} differs, rerun with BLESS=1 if that's intended:\n{}",
+                golden.display(),
+                diff(&expected, &actual)
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,104 @@
+use maze_lvl_maker::{parse_ascii, CorridorGraph, Grid, NodeKind, Point};
+
+fn graph(level: &str) -> (Grid, CorridorGraph) {
+    let grid = parse_ascii(level).unwrap();
+    let graph = CorridorGraph::from_grid(&grid);
+    (grid, graph)
+}
+
+fn passable_squares(grid: &Grid) -> u32 {
+    let squares = (0..grid.height()).flat_map(|y| (0..grid.width()).map(move |x| Point::new(x, y)));
+    squares.filter(|point| grid.is_passable(*point)).count() as u32
+}
+
+/// Every passable square is a node or inside exactly one edge.
+fn assert_accounts_for_every_square(grid: &Grid, graph: &CorridorGraph) {
+    let lengths: u32 = graph.edges.iter().map(|edge| edge.length).sum();
+    assert_eq!(graph.nodes.len() as u32 + lengths, passable_squares(grid));
+}
+
+#[test]
+fn known_maze() {
+    let (grid, graph) = graph(
+        "\
+#########
+#S....#.#
+#.###.#.#
+#.#E..#C#
+#.#####.#
+#.......#
+#########
+",
+    );
+    assert_accounts_for_every_square(&grid, &graph);
+
+    let kinds: Vec<NodeKind> = graph.nodes.iter().map(|node| node.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            NodeKind::Start,
+            NodeKind::DeadEnd,
+            NodeKind::End,
+            NodeKind::Checkpoint,
+        ]
+    );
+    // Start branches twice, one way to the end and one round to the
+    // checkpoint, which leads on to the dead end above it
+    assert_eq!(graph.edges.len(), 3);
+}
+
+#[test]
+fn loops_back_to_the_same_node() {
+    let (grid, graph) = graph(
+        "\
+#######
+#.....#
+#.###.#
+#.....#
+###.###
+###S###
+#######
+",
+    );
+    assert_accounts_for_every_square(&grid, &graph);
+
+    let junction = graph
+        .nodes
+        .iter()
+        .find(|node| node.kind == NodeKind::Junction)
+        .unwrap();
+    let cycles: Vec<_> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.a == junction.id && edge.b == junction.id)
+        .collect();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0].length, 11);
+}
+
+#[test]
+fn loops_without_nodes_get_one() {
+    let (grid, graph) = graph("####\n#..#\n#..#\n####\n");
+    assert_accounts_for_every_square(&grid, &graph);
+    assert_eq!(graph.nodes.len(), 1);
+    assert_eq!(graph.nodes[0].kind, NodeKind::Loop);
+    assert_eq!((graph.edges[0].a, graph.edges[0].b), (0, 0));
+}
+
+#[test]
+fn neighboring_junctions_have_zero_length_edges() {
+    let (grid, graph) = graph(
+        "\
+#######
+#.....#
+#.....#
+#.....#
+#######
+",
+    );
+    assert_accounts_for_every_square(&grid, &graph);
+    assert!(graph
+        .edges
+        .iter()
+        .any(|edge| edge.length == 0 && edge.a != edge.b));
+}
@@ -0,0 +1,202 @@
+use std::{io::Cursor, process::Command};
+
+use image::{ImageFormat, Rgba};
+use maze_lvl_maker::{
+    parse_ascii, scan_grid, Grid, LvlBuilder, Point, ScanOptions, SquareType, UnknownColor,
+};
+
+const LEVEL: &str = "\
+##########
+#S.#...#C#
+#..#.#.#.#
+#....#...#
+####.###.#
+#E.......#
+##########
+";
+
+/// Back to the text format, so the codes are checked against the parser.
+fn to_ascii(codes: &[Vec<u8>]) -> String {
+    codes
+        .iter()
+        .map(|row| {
+            let mut line: String = row
+                .iter()
+                .map(|code| match code {
+                    0 => '.',
+                    1 => '#',
+                    2 => 'S',
+                    3 => 'E',
+                    4 => 'C',
+                    other => panic!("unexpected code {other}"),
+                })
+                .collect();
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+#[test]
+fn grid_codes_rebuild_the_same_walls() {
+    for no_overlap in [false, true] {
+        let options = ScanOptions {
+            no_overlap,
+            ..ScanOptions::default()
+        };
+        let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), options);
+
+        let codes = Grid::from_lvl(&lvl).codes();
+        assert_eq!(codes.len(), lvl.height as usize);
+        assert!(codes.iter().all(|row| row.len() == lvl.width as usize));
+
+        let rebuilt = scan_grid(&parse_ascii(&to_ascii(&codes)).unwrap(), options);
+        assert_eq!(rebuilt.walls, lvl.walls);
+        assert_eq!(rebuilt.start, lvl.start);
+        assert_eq!(rebuilt.end, lvl.end);
+        assert_eq!(rebuilt.checkpoints, lvl.checkpoints);
+    }
+}
+
+#[test]
+fn grid_codes_match_the_input() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    assert_eq!(to_ascii(&grid.codes()), LEVEL);
+}
+
+#[test]
+fn row_runs_sum_to_the_width() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    let runs = grid.row_runs();
+    assert_eq!(runs.len(), grid.height() as usize);
+    for row in &runs {
+        assert_eq!(row.iter().sum::<u32>(), grid.width());
+    }
+
+    // Rows start with open squares, so a leading wall gets an empty run
+    assert_eq!(runs[0], [0, 10]);
+    assert_eq!(runs[1], [0, 1, 2, 1, 3, 1, 1, 1]);
+}
+
+#[test]
+fn row_runs_expand_to_the_walls() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    for (y, row) in grid.row_runs().iter().enumerate() {
+        let expanded: Vec<bool> = row
+            .iter()
+            .enumerate()
+            .flat_map(|(index, run)| std::iter::repeat_n(index % 2 == 1, *run as usize))
+            .collect();
+        for (x, wall) in expanded.into_iter().enumerate() {
+            assert_eq!(
+                grid.get(x as u32, y as u32) == SquareType::Wall,
+                wall,
+                "square {x},{y}"
+            );
+        }
+    }
+}
+
+#[test]
+fn hollow_keeps_the_shell_of_solid_walls() {
+    let grid = parse_ascii(
+        "\
+#######
+#######
+#######
+###.###
+#S###E#
+#######
+",
+    )
+    .unwrap();
+    let expected = parse_ascii(
+        "\
+#######
+#.....#
+#.###.#
+###.###
+#S###E#
+#######
+",
+    )
+    .unwrap();
+
+    assert_eq!(grid.hollow(), expected);
+}
+
+#[test]
+fn special_points_are_the_squares_that_arent_walls_or_empty() {
+    let lvl = LvlBuilder::new(4, 3)
+        .start(Point::new(1, 0))
+        .end(Point::new(3, 2))
+        .checkpoint(Point::new(1, 1))
+        .checkpoint(Point::new(2, 2))
+        .optional_checkpoint(Point::new(3, 0))
+        .wall(Point::new(2, 0), Point::new(2, 1))
+        .build()
+        .unwrap();
+
+    let points: Vec<_> = lvl.all_special_points().collect();
+    assert_eq!(points.len(), lvl.special_points_count());
+    for (point, kind) in &points {
+        assert_eq!(lvl.cell_at(point.x, point.y), *kind, "{point:?}");
+    }
+    let grid = Grid::from_lvl(&lvl);
+    let squares = (0..grid.height())
+        .flat_map(|y| (0..grid.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !matches!(grid.get(x, y), SquareType::Wall | SquareType::Empty))
+        .count();
+    assert_eq!(points.len(), squares);
+}
+
+/// PNG bytes of `LEVEL` with an anti-aliased gray pixel on a wall edge.
+fn off_palette_png() -> Vec<u8> {
+    let mut image = parse_ascii(LEVEL).unwrap().to_image(1);
+    image.put_pixel(3, 2, Rgba([128, 128, 128, 255]));
+    image.put_pixel(5, 4, Rgba([200, 200, 200, 255]));
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+    png
+}
+
+#[test]
+fn try_from_image_reports_the_first_pixel_outside_the_palette() {
+    let image = image::load_from_memory(&off_palette_png()).unwrap();
+    let error = Grid::try_from_image(&image).unwrap_err();
+    assert_eq!(
+        error,
+        UnknownColor {
+            x: 3,
+            y: 2,
+            rgba: [128, 128, 128, 255],
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "the pixel at (3, 2) is rgba(128, 128, 128, 255), which isn't in the palette"
+    );
+
+    let image = parse_ascii(LEVEL).unwrap().to_image(1).into();
+    assert_eq!(
+        Grid::try_from_image(&image).unwrap(),
+        Grid::from_image(&image)
+    );
+}
+
+#[test]
+fn off_palette_images_are_an_error() {
+    let path = std::env::temp_dir().join("maze-lvl-maker-off-palette.png");
+    std::fs::write(&path, off_palette_png()).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .arg("--image")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("UnknownColor { x: 3, y: 2"), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+}
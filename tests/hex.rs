@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use maze_lvl_maker::{
+    parse_ascii, scan_hex, Axial, Grid, HexAxis, HexLayout, HexLvl, Point, SquareType,
+};
+
+/// Draw one character per cell as `cell_size` blocks in the odd-r layout,
+/// padding the half cell on the right of even rows with walls.
+fn draw(cells: &str, cell_size: u32) -> Grid {
+    let cells = parse_ascii(cells).unwrap();
+    let mut pixels = Grid::new(
+        cells.width() * cell_size + cell_size / 2,
+        cells.height() * cell_size,
+    );
+    for y in 0..pixels.height() {
+        for x in 0..pixels.width() {
+            let row = y / cell_size;
+            let shift = if row % 2 == 1 { cell_size / 2 } else { 0 };
+            let col = x.checked_sub(shift).map(|x| x / cell_size);
+            let square = match col {
+                Some(col) if col < cells.width() => cells.get(col, row),
+                _ => SquareType::Wall,
+            };
+            pixels.set(x, y, square);
+        }
+    }
+
+    pixels
+}
+
+fn open_level(width: u32, height: u32) -> HexLvl {
+    HexLvl {
+        grid: HexLayout::Offset,
+        width,
+        height,
+        walls: Vec::new(),
+        start: Point::new(0, 0),
+        end: Point::new(0, 0),
+        checkpoints: Vec::new(),
+        optional_checkpoints: Vec::new(),
+    }
+}
+
+#[test]
+fn offset_and_axial_round_trip() {
+    for y in 0..12 {
+        for x in 0..12 {
+            let point = Point::new(x, y);
+            assert_eq!(Axial::from(point).to_offset(), Some(point));
+        }
+    }
+
+    let axial = |x, y| {
+        let Axial { q, r } = Axial::from(Point::new(x, y));
+        (q, r)
+    };
+    assert_eq!(axial(0, 1), (0, 1));
+    assert_eq!(axial(1, 1), (1, 1));
+    assert_eq!(axial(0, 2), (-1, 2));
+    assert_eq!(axial(3, 3), (2, 3));
+    assert_eq!(axial(4, 4), (2, 4));
+    assert_eq!(Axial { q: -2, r: 2 }.to_offset(), None);
+    assert_eq!(Axial { q: 0, r: -1 }.to_offset(), None);
+}
+
+#[test]
+fn neighbors_depend_on_the_row_parity() {
+    let lvl = open_level(6, 6);
+    let neighbors = |x, y| lvl.neighbors(Point::new(x, y)).collect::<HashSet<_>>();
+    let points = |list: &[(u32, u32)]| {
+        list.iter()
+            .map(|&(x, y)| Point::new(x, y))
+            .collect::<HashSet<_>>()
+    };
+
+    assert_eq!(
+        neighbors(2, 2),
+        points(&[(1, 2), (3, 2), (1, 1), (2, 1), (1, 3), (2, 3)])
+    );
+    assert_eq!(
+        neighbors(2, 1),
+        points(&[(1, 1), (3, 1), (2, 0), (3, 0), (2, 2), (3, 2)])
+    );
+    // Corners lose the neighbors outside of the level
+    assert_eq!(neighbors(0, 0), points(&[(1, 0), (0, 1)]));
+    assert_eq!(neighbors(5, 1), points(&[(4, 1), (5, 0), (5, 2)]));
+}
+
+#[test]
+fn axial_distance_matches_the_shortest_path() {
+    let mut lvl = open_level(7, 7);
+    lvl.start = Point::new(3, 3);
+    for y in 0..lvl.height {
+        for x in 0..lvl.width {
+            lvl.end = Point::new(x, y);
+            let distance = Axial::from(lvl.start).distance(Axial::from(lvl.end));
+            assert_eq!(lvl.shortest_path_length(), Some(distance), "to {x},{y}");
+        }
+    }
+
+    let origin = Axial { q: 0, r: 0 };
+    for axis in HexAxis::ALL {
+        assert_eq!(origin.distance(origin.step(axis, true)), 1);
+        assert_eq!(origin.step(axis, true).step(axis, false), origin);
+    }
+}
+
+#[test]
+fn scans_runs_along_the_three_axes() {
+    const CELLS: &str = "\
+#####
+#S..#
+#.#E#
+#####
+";
+    let cells = parse_ascii(CELLS).unwrap();
+    for cell_size in [2, 4, 5] {
+        let lvl = scan_hex(&draw(CELLS, cell_size), cell_size);
+
+        assert_eq!((lvl.width, lvl.height), (5, 4));
+        assert_eq!((lvl.start, lvl.end), (Point::new(1, 1), Point::new(3, 2)));
+        assert_eq!(lvl.shortest_path_length(), Some(2));
+        assert!(lvl.is_solvable());
+
+        let covered: HashSet<Point> = lvl.walls.iter().flat_map(|wall| wall.cells()).collect();
+        let walls: HashSet<Point> = (0..cells.height())
+            .flat_map(|y| (0..cells.width()).map(move |x| Point::new(x, y)))
+            .filter(|point| !cells.is_passable(*point))
+            .collect();
+        assert_eq!(covered, walls, "cell size {cell_size}");
+        assert!(lvl
+            .walls
+            .iter()
+            .any(|wall| wall.axis == HexAxis::Sw && wall.length == 3));
+    }
+}
@@ -0,0 +1,67 @@
+use maze_lvl_maker::{parse_ascii, scan_grid, Lvl, Rect, ScanOptions, Wall, Wrap};
+
+const LEVEL: &str = "\
+##########
+#S.#...#C#
+#..#.#.#.#
+#....#...#
+####.###.#
+#E.#.....#
+##.#######
+";
+
+fn brute_force(lvl: &Lvl, matches: impl Fn(&Wall) -> bool) -> Vec<&Wall> {
+    lvl.walls.iter().filter(|wall| matches(wall)).collect()
+}
+
+fn assert_matches_brute_force(lvl: &Lvl) {
+    let index = lvl.build_index();
+    for y in 0..lvl.height {
+        for x in 0..lvl.width {
+            assert_eq!(
+                index.query_point(x, y),
+                brute_force(lvl, |wall| wall.contains(x, y)),
+                "point {x},{y}"
+            );
+
+            let rect = Rect {
+                x,
+                y,
+                width: 3.min(lvl.width - x),
+                height: 2.min(lvl.height - y),
+            };
+            let in_rect = |wall: &Wall| {
+                (rect.y..rect.y + rect.height)
+                    .any(|y| (rect.x..rect.x + rect.width).any(|x| wall.contains(x, y)))
+            };
+            assert_eq!(
+                index.query_rect(rect),
+                brute_force(lvl, in_rect),
+                "{rect:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn queries_match_brute_force() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    for options in [
+        ScanOptions::default(),
+        ScanOptions {
+            diagonals: true,
+            ..ScanOptions::default()
+        },
+    ] {
+        assert_matches_brute_force(&scan_grid(&grid, options));
+    }
+}
+
+#[test]
+fn wrapping_walls_are_found_on_both_edges() {
+    let mut lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    lvl.set_wrap(Wrap::Y);
+    assert!(lvl.walls.iter().any(|wall| wall.wraps()));
+
+    assert_matches_brute_force(&lvl);
+}
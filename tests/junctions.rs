@@ -0,0 +1,62 @@
+use maze_lvl_maker::{parse_ascii, scan_grid, Junction, JunctionKind, Mesh, ScanOptions};
+
+// Corners at the four outer corners, T junctions where the inner walls
+// meet the border and a cross in the middle. The inner wall stops short of
+// the bottom border, so its dangling end and the straight runs aren't
+// junctions.
+const LEVEL: &str = "\
+#######
+#S.#..#
+#..#..#
+#######
+#..#.E#
+#.....#
+#######
+";
+
+#[test]
+fn every_junction_kind_is_classified() {
+    let junctions = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default()).junctions();
+    let at = |x, y| {
+        junctions
+            .iter()
+            .find(|junction| (junction.x, junction.y) == (x, y))
+            .map(|junction| junction.kind)
+    };
+
+    for corner in [(0, 0), (6, 0), (0, 6), (6, 6)] {
+        assert_eq!(
+            at(corner.0, corner.1),
+            Some(JunctionKind::Corner),
+            "{corner:?}"
+        );
+    }
+    for t in [(3, 0), (0, 3), (6, 3)] {
+        assert_eq!(at(t.0, t.1), Some(JunctionKind::T), "{t:?}");
+    }
+    assert_eq!(at(3, 3), Some(JunctionKind::Cross));
+    // Straight runs and the dangling end below the cross
+    assert_eq!(at(1, 0), None);
+    assert_eq!(at(3, 4), None);
+    assert_eq!(junctions.len(), 8);
+}
+
+#[test]
+fn junctions_ignore_the_mesh() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    let junctions = |mesh| -> Vec<Junction> {
+        scan_grid(
+            &grid,
+            ScanOptions {
+                mesh,
+                ..ScanOptions::default()
+            },
+        )
+        .junctions()
+    };
+
+    let lines = junctions(Mesh::Lines);
+    for mesh in [Mesh::Rect, Mesh::Polyline, Mesh::Contour] {
+        assert_eq!(junctions(mesh), lines);
+    }
+}
@@ -0,0 +1,108 @@
+use std::{fs, path::Path, process::Command};
+
+use maze_lvl_maker::{parse_ascii, Grid, SizeMismatch};
+
+const BASE: &str = "\
+#######
+#S....#
+#.....#
+#....E#
+#######
+";
+
+/// Closes the middle column, except where the second layer opens it up.
+const WALLS: &str = "\
+.......
+...#...
+...#...
+...#...
+.......
+";
+
+/// A checkpoint on one of the first layer's walls.
+const CHECKPOINT: &str = "\
+.......
+.......
+...C...
+.......
+.......
+";
+
+const LAYERED: &str = "\
+#######
+#S.#..#
+#..C..#
+#..#.E#
+#######
+";
+
+fn grid(text: &str) -> Grid {
+    parse_ascii(text).unwrap()
+}
+
+#[test]
+fn later_layers_win_and_empty_squares_keep_what_is_below() {
+    let mut layered = grid(BASE);
+    layered.overlay(&grid(WALLS)).unwrap();
+    layered.overlay(&grid(CHECKPOINT)).unwrap();
+    assert_eq!(layered.to_ascii(), LAYERED);
+
+    let mut reversed = grid(BASE);
+    reversed.overlay(&grid(CHECKPOINT)).unwrap();
+    reversed.overlay(&grid(WALLS)).unwrap();
+    assert_eq!(reversed.to_ascii(), LAYERED.replace('C', "#"));
+}
+
+#[test]
+fn layers_must_match_the_size() {
+    let mut base = grid(BASE);
+    let error = base.overlay(&grid("...\n...\n")).unwrap_err();
+    assert_eq!(
+        error,
+        SizeMismatch {
+            expected: (7, 5),
+            found: (3, 2),
+        }
+    );
+    assert_eq!(error.to_string(), "expected a 7x5 level but got 3x2");
+    // Nothing is drawn
+    assert_eq!(base.to_ascii(), BASE);
+}
+
+fn save(dir: &Path, name: &str, text: &str) {
+    grid(text).to_image(1).save(dir.join(name)).unwrap();
+}
+
+fn layer(dir: &Path, layers: &[&str]) -> std::process::Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"));
+    command
+        .current_dir(dir)
+        .args(["--image", "base.png", "--emit", "ascii"]);
+    for layer in layers {
+        command.args(["--layer", layer]);
+    }
+    command.output().unwrap()
+}
+
+#[test]
+fn layer_flag() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-layer");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    save(&dir, "base.png", BASE);
+    save(&dir, "walls.png", WALLS);
+    save(&dir, "checkpoint.png", CHECKPOINT);
+    save(&dir, "small.png", "...\n...\n");
+
+    let output = layer(&dir, &["walls.png", "checkpoint.png"]);
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json["ascii"],
+        serde_json::json!(LAYERED.lines().collect::<Vec<_>>())
+    );
+
+    let output = layer(&dir, &["small.png"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected: (7, 5), found: (3, 2)"));
+}
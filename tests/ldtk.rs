@@ -0,0 +1,152 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{parse_ascii, scan_grid, Lvl, LvlBuilder, Point, ScanOptions};
+use serde_json::{json, Value};
+
+const LEVEL: &str = "\
+#######
+#S..#C#
+#.#...#
+#...#E#
+#######
+";
+
+fn project(lvl: &Lvl, grid_size: u32) -> Value {
+    serde_json::to_value(lvl.to_ldtk(grid_size)).unwrap()
+}
+
+fn layer<'a>(project: &'a Value, identifier: &str) -> &'a Value {
+    project["levels"][0]["layerInstances"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|layer| layer["__identifier"] == identifier)
+        .unwrap()
+}
+
+#[test]
+fn walls_are_an_int_grid() {
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    let project = project(&lvl, 16);
+    let level = &project["levels"][0];
+    assert_eq!(level["pxWid"], 7 * 16);
+    assert_eq!(level["pxHei"], 5 * 16);
+
+    let walls = layer(&project, "Walls");
+    assert_eq!(walls["__type"], "IntGrid");
+    assert_eq!([&walls["__cWid"], &walls["__cHei"]], [7, 5]);
+    let expected: Vec<u8> = LEVEL
+        .lines()
+        .flat_map(|line| line.chars().map(|square| u8::from(square == '#')))
+        .collect();
+    assert_eq!(walls["intGridCsv"], json!(expected));
+}
+
+#[test]
+fn points_are_entities() {
+    let lvl = LvlBuilder::new(4, 3)
+        .start(Point::new(0, 0))
+        .end(Point::new(3, 2))
+        .checkpoint(Point::new(1, 1))
+        .optional_checkpoint(Point::new(2, 1))
+        .build()
+        .unwrap();
+    let project = project(&lvl, 8);
+    let entities: Vec<_> = layer(&project, "Entities")["entityInstances"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entity| {
+            (
+                entity["__identifier"].as_str().unwrap(),
+                entity["__grid"].clone(),
+                entity["px"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        entities,
+        [
+            ("Start", json!([0, 0]), json!([0, 0])),
+            ("End", json!([3, 2]), json!([24, 16])),
+            ("Checkpoint", json!([1, 1]), json!([8, 8])),
+            ("OptionalCheckpoint", json!([2, 1]), json!([16, 8])),
+        ]
+    );
+
+    // Every instance points at its definition
+    let defs = project["defs"]["entities"].as_array().unwrap();
+    for entity in layer(&project, "Entities")["entityInstances"]
+        .as_array()
+        .unwrap()
+    {
+        let def = defs
+            .iter()
+            .find(|def| def["uid"] == entity["defUid"])
+            .unwrap();
+        assert_eq!(def["identifier"], entity["__identifier"]);
+    }
+}
+
+#[test]
+fn project_is_complete_and_stable() {
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    let project = project(&lvl, 16);
+    assert_eq!(project["__header__"]["fileType"], "LDtk Project JSON");
+    assert_eq!(project["jsonVersion"], "1.5.3");
+    for field in [
+        "iid",
+        "nextUid",
+        "defaultGridSize",
+        "worldLayout",
+        "defs",
+        "levels",
+        "worlds",
+        "dummyWorldIid",
+    ] {
+        assert!(project.get(field).is_some(), "{field}");
+    }
+
+    // Layer definitions and instances are in the same order
+    let defs: Vec<_> = project["defs"]["layers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|def| def["uid"].clone())
+        .collect();
+    let instances: Vec<_> = project["levels"][0]["layerInstances"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|layer| layer["layerDefUid"].clone())
+        .collect();
+    assert_eq!(defs, instances);
+
+    assert_eq!(project, self::project(&lvl, 16));
+}
+
+#[test]
+fn format_follows_the_extension() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-ldtk");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args([
+            "--ascii",
+            "level.txt",
+            "--cell-size",
+            "16",
+            "-o",
+            "level.ldtk",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let written: Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("level.ldtk")).unwrap()).unwrap();
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    assert_eq!(written, project(&lvl, 16));
+}
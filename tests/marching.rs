@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use maze_lvl_maker::{marching_squares, parse_ascii, Grid, Polygon, Vertex};
+
+const LEVEL: &str = "\
+##########
+#S.......#
+#.######.#
+#.#....#.#
+#.#.##.#.#
+#...#..#E#
+##########";
+
+/// Whether the point is inside the outer ring and outside every hole.
+fn inside(polygon: &Polygon, x: f64, y: f64) -> bool {
+    std::iter::once(&polygon.outer)
+        .chain(&polygon.holes)
+        .map(|ring| {
+            ring.iter()
+                .zip(ring.iter().cycle().skip(1))
+                .filter(|(from, to)| {
+                    (from.y > y) != (to.y > y)
+                        && x < from.x + (y - from.y) * (to.x - from.x) / (to.y - from.y)
+                })
+                .count()
+        })
+        .sum::<usize>()
+        % 2
+        == 1
+}
+
+fn vertex_count(polygons: &[Polygon]) -> usize {
+    polygons
+        .iter()
+        .flat_map(|polygon| std::iter::once(&polygon.outer).chain(&polygon.holes))
+        .map(Vec::len)
+        .sum()
+}
+
+#[test]
+fn single_square_is_a_diamond() {
+    let polygons = marching_squares(&parse_ascii("...\n.#.\n...").unwrap(), 0.0);
+
+    assert_eq!(polygons.len(), 1);
+    let mut corners: Vec<_> = polygons[0].outer.iter().map(|v| (v.x, v.y)).collect();
+    corners.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(corners, [(1.0, 1.5), (1.5, 1.0), (1.5, 2.0), (2.0, 1.5)]);
+}
+
+#[test]
+fn zero_tolerance_keeps_the_traced_outline() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    let traced = marching_squares(&grid, 0.0);
+
+    // Every vertex is a corner, so even the smallest tolerance drops none
+    assert_eq!(marching_squares(&grid, 1e-9), traced);
+    assert_eq!(marching_squares(&grid, -1.0), traced);
+    for ring in traced
+        .iter()
+        .flat_map(|polygon| std::iter::once(&polygon.outer).chain(&polygon.holes))
+    {
+        for (index, vertex) in ring.iter().enumerate() {
+            let previous = ring[(index + ring.len() - 1) % ring.len()];
+            let next = ring[(index + 1) % ring.len()];
+            let cross = (vertex.x - previous.x) * (next.y - vertex.y)
+                - (vertex.y - previous.y) * (next.x - vertex.x);
+            assert_ne!(cross, 0.0, "{vertex:?} is on a straight line");
+        }
+    }
+}
+
+#[test]
+fn simplifying_never_cuts_into_a_corridor() {
+    let maze =
+        image::open(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/maze.png")).unwrap();
+    for grid in [parse_ascii(LEVEL).unwrap(), Grid::from_image(&maze)] {
+        let traced = marching_squares(&grid, 0.0);
+        for tolerance in [0.5, 1.0, 3.0] {
+            let simplified = marching_squares(&grid, tolerance);
+            assert_eq!(simplified.len(), traced.len());
+            assert!(vertex_count(&simplified) < vertex_count(&traced));
+
+            // Sample a quarter square grid, anything outside the traced
+            // outline stays outside
+            for y in 0..grid.height() * 4 {
+                for x in 0..grid.width() * 4 {
+                    let (x, y) = ((f64::from(x) + 0.5) / 4.0, (f64::from(y) + 0.5) / 4.0);
+                    for (traced, simplified) in traced.iter().zip(&simplified) {
+                        assert!(
+                            inside(traced, x, y) || !inside(simplified, x, y),
+                            "{:?} at tolerance {tolerance}",
+                            Vertex { x, y }
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
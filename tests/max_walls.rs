@@ -0,0 +1,57 @@
+use std::{fs, path::PathBuf, process::Command};
+
+const LEVEL: &str = "\
+#########
+#S..#...#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+fn level(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("maze-lvl-maker-max-walls-{name}.txt"));
+    fs::write(&path, LEVEL).unwrap();
+    path
+}
+
+fn run(path: &PathBuf, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .arg("--ascii")
+        .arg(path)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn over_the_limit_fails_with_the_count() {
+    let path = level("limit");
+    let output = run(&path, &[]);
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let count = json["walls"].as_array().unwrap().len();
+
+    let output = run(&path, &["--max-walls", &count.to_string()]);
+    assert!(output.status.success(), "{output:?}");
+
+    let output = run(&path, &["--max-walls", &(count - 1).to_string()]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(&format!(
+            "the level has {count} walls, more than the --max-walls limit of {}",
+            count - 1
+        )),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn limit_and_overlaps_exit_differently() {
+    let path = level("overlaps");
+    let overlaps = run(&path, &["--report-overlaps"]);
+    let limit = run(&path, &["--max-walls", "1"]);
+    assert_eq!(overlaps.status.code(), Some(3));
+    assert_eq!(limit.status.code(), Some(4));
+}
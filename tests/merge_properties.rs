@@ -0,0 +1,73 @@
+use maze_lvl_maker::{scan_grid, Grid, Lvl, ScanOptions, SquareType};
+use proptest::prelude::*;
+
+fn square() -> impl Strategy<Value = SquareType> {
+    prop_oneof![
+        6 => Just(SquareType::Wall),
+        6 => Just(SquareType::Empty),
+        1 => (1..5_u8).prop_map(SquareType::DynamicWall),
+        1 => Just(SquareType::Checkpoint),
+        1 => Just(SquareType::OptionalCheckpoint),
+        1 => Just(SquareType::Start),
+        1 => Just(SquareType::End),
+    ]
+}
+
+fn grid() -> impl Strategy<Value = Grid> {
+    (1..20_u32, 1..20_u32).prop_flat_map(|(width, height)| {
+        prop::collection::vec(square(), (width * height) as usize).prop_map(move |squares| {
+            let mut grid = Grid::new(width, height);
+            for (index, square) in (0_u32..).zip(squares) {
+                grid.set(index % width, index / width, square);
+            }
+            grid
+        })
+    })
+}
+
+fn options() -> impl Strategy<Value = ScanOptions> {
+    (any::<bool>(), any::<bool>(), any::<bool>()).prop_map(
+        |(no_overlap, diagonals, optimize_walls)| ScanOptions {
+            no_overlap,
+            diagonals,
+            optimize_walls,
+            ..ScanOptions::default()
+        },
+    )
+}
+
+/// Number of walls covering each square, row-major.
+fn coverage(lvl: &Lvl) -> Vec<u32> {
+    let mut counts = vec![0; (lvl.width * lvl.height) as usize];
+    for square in lvl.walls.iter().flat_map(|wall| wall.squares()) {
+        counts[(square.y * lvl.width + square.x) as usize] += 1;
+    }
+    counts
+}
+
+proptest! {
+    #[test]
+    fn walls_cover_exactly_the_wall_squares(grid in grid(), options in options()) {
+        let lvl = scan_grid(&grid, options);
+        let counts = coverage(&lvl);
+
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let count = counts[(y * grid.width() + x) as usize];
+                if grid.get(x, y) == SquareType::Wall {
+                    prop_assert!(count > 0, "wall square {},{} isn't covered", x, y);
+                } else {
+                    prop_assert_eq!(count, 0, "square {},{} isn't a wall", x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn non_overlapping_modes_cover_squares_once(grid in grid(), options in options()) {
+        prop_assume!(options.no_overlap || options.optimize_walls);
+        let lvl = scan_grid(&grid, options);
+
+        prop_assert!(coverage(&lvl).iter().all(|count| *count <= 1), "{:?}", lvl.walls);
+    }
+}
@@ -0,0 +1,86 @@
+use maze_lvl_maker::{parse_ascii, scan_grid, Grid, Lvl, MergeStrategy, Point, ScanOptions, Wall};
+
+/// A 3x3 L of walls in the top left corner.
+const L_SHAPE: &str = "\
+#....
+#..S.
+###E.
+";
+
+/// Two columns joined by a row, the row crosses both of them.
+const H_SHAPE: &str = "\
+#.#..
+###S.
+#.#E.
+";
+
+fn scan(text: &str, merge_strategy: MergeStrategy, no_overlap: bool) -> Lvl {
+    scan_grid(
+        &parse_ascii(text).unwrap(),
+        ScanOptions {
+            merge_strategy,
+            no_overlap,
+            ..ScanOptions::default()
+        },
+    )
+}
+
+fn wall(start: (u32, u32), end: (u32, u32)) -> Wall {
+    Wall {
+        start: Point::new(start.0, start.1),
+        end: Some(Point::new(end.0, end.1)),
+    }
+}
+
+#[test]
+fn l_shape_corner_goes_to_the_first_orientation() {
+    let horizontal = scan(L_SHAPE, MergeStrategy::HorizontalFirst, true);
+    let vertical = scan(L_SHAPE, MergeStrategy::VerticalFirst, true);
+    assert_eq!(
+        horizontal.walls,
+        [wall((0, 2), (2, 2)), wall((0, 0), (0, 1))]
+    );
+    assert_eq!(vertical.walls, [wall((0, 0), (0, 2)), wall((1, 2), (2, 2))]);
+
+    // Overlapping walls are the same whichever orientation goes first
+    assert_eq!(
+        scan(L_SHAPE, MergeStrategy::HorizontalFirst, false).walls,
+        scan(L_SHAPE, MergeStrategy::VerticalFirst, false).walls
+    );
+}
+
+#[test]
+fn h_shape_wall_count_depends_on_the_strategy() {
+    let horizontal = scan(H_SHAPE, MergeStrategy::HorizontalFirst, true);
+    let vertical = scan(H_SHAPE, MergeStrategy::VerticalFirst, true);
+    // The row splits both columns, or both columns split the row
+    assert_eq!(horizontal.walls.len(), 5);
+    assert_eq!(vertical.walls.len(), 3);
+}
+
+#[test]
+fn every_strategy_covers_the_same_squares() {
+    for text in [L_SHAPE, H_SHAPE] {
+        for strategy in [MergeStrategy::HorizontalFirst, MergeStrategy::VerticalFirst] {
+            for no_overlap in [false, true] {
+                let lvl = scan(text, strategy, no_overlap);
+                assert_eq!(
+                    Grid::from_lvl(&lvl).to_ascii(),
+                    text,
+                    "{strategy:?} {no_overlap}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn strategy_deserializes_in_snake_case() {
+    let options: ScanOptions =
+        serde_json::from_str(r#"{"merge_strategy": "vertical_first"}"#).unwrap();
+    assert_eq!(options.merge_strategy, MergeStrategy::VerticalFirst);
+    assert_eq!(
+        ScanOptions::default().merge_strategy,
+        MergeStrategy::HorizontalFirst
+    );
+}
@@ -0,0 +1,206 @@
+use std::{collections::HashMap, path::Path};
+
+use maze_lvl_maker::{greedy_rects, parse_ascii, polylines, Grid, Point, Rect, SquareType};
+
+/// One chain from the top left corner winding inwards.
+const SPIRAL: &str = "\
+#######
+......#
+.####.#
+.#..#.#
+.#.##.#
+.#....#
+.######";
+
+/// Junctions, a loop without any and a lone square.
+const MIXED: &str = "\
+#####.....
+..#...###.
+..#...#.#.
+#####.###.
+..#.......
+..#...#...";
+
+fn maze() -> Grid {
+    let image =
+        image::open(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/maze.png")).unwrap();
+    Grid::from_image(&image)
+}
+
+fn walls(grid: &Grid) -> Vec<Point> {
+    (0..grid.height())
+        .flat_map(|y| (0..grid.width()).map(move |x| Point::new(x, y)))
+        .filter(|point| grid.get(point.x, point.y) == SquareType::Wall)
+        .collect()
+}
+
+/// Edges between neighboring wall squares, top or left square first.
+fn wall_edges(grid: &Grid) -> Vec<(Point, Point)> {
+    let wall = |x, y| grid.get(x, y) == SquareType::Wall;
+    let mut edges = Vec::new();
+    for point in walls(grid) {
+        if point.x + 1 < grid.width() && wall(point.x + 1, point.y) {
+            edges.push((point, Point::new(point.x + 1, point.y)));
+        }
+        if point.y + 1 < grid.height() && wall(point.x, point.y + 1) {
+            edges.push((point, Point::new(point.x, point.y + 1)));
+        }
+    }
+    edges
+}
+
+/// Every square a polyline runs through, in order.
+fn squares(path: &[Point]) -> Vec<Point> {
+    let mut squares = vec![path[0]];
+    for pair in path.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        assert!(from.x == to.x || from.y == to.y, "{from:?} to {to:?}");
+        let mut current = from;
+        while current != to {
+            current = Point::new(
+                current.x.wrapping_add_signed(to.x.cmp(&current.x) as i32),
+                current.y.wrapping_add_signed(to.y.cmp(&current.y) as i32),
+            );
+            squares.push(current);
+        }
+    }
+    squares
+}
+
+fn assert_covers(grid: &Grid) {
+    let paths = polylines(grid);
+    let mut squares_covered = Vec::new();
+    let mut edges_covered: HashMap<(Point, Point), usize> = HashMap::new();
+    for path in &paths {
+        let path = squares(path);
+        for pair in path.windows(2) {
+            let key = if (pair[0].y, pair[0].x) < (pair[1].y, pair[1].x) {
+                (pair[0], pair[1])
+            } else {
+                (pair[1], pair[0])
+            };
+            *edges_covered.entry(key).or_default() += 1;
+        }
+        squares_covered.extend(path);
+    }
+
+    squares_covered.sort_by_key(|point| (point.y, point.x));
+    squares_covered.dedup();
+    assert_eq!(squares_covered, walls(grid));
+    let edges = wall_edges(grid);
+    assert_eq!(edges_covered.len(), edges.len());
+    for edge in edges {
+        assert_eq!(edges_covered.get(&edge), Some(&1), "{edge:?}");
+    }
+}
+
+#[test]
+fn spiral_is_one_polyline_through_its_corners() {
+    let paths = polylines(&parse_ascii(SPIRAL).unwrap());
+    let corners = [
+        (0, 0),
+        (6, 0),
+        (6, 6),
+        (1, 6),
+        (1, 2),
+        (4, 2),
+        (4, 4),
+        (3, 4),
+    ];
+    assert_eq!(paths, [corners.map(|(x, y)| Point::new(x, y)).to_vec()]);
+}
+
+#[test]
+fn loops_repeat_their_first_point_and_lone_squares_are_points() {
+    let paths = polylines(&parse_ascii(MIXED).unwrap());
+    let lone = paths.iter().filter(|path| path.len() == 1).count();
+    assert_eq!(lone, 1);
+    let closed: Vec<_> = paths
+        .iter()
+        .filter(|path| path.len() > 1 && path.first() == path.last())
+        .collect();
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].len(), 5);
+}
+
+#[test]
+fn polylines_cover_every_wall_square_and_edge_once() {
+    for grid in [
+        parse_ascii(SPIRAL).unwrap(),
+        parse_ascii(MIXED).unwrap(),
+        maze(),
+    ] {
+        assert_covers(&grid);
+    }
+}
+
+const LATTICE: &str = "\
+#####
+#.#.#
+#####
+#.#.#
+#####";
+
+const CROSS: &str = "\
+..#..
+..#..
+#####
+..#..
+..#..";
+
+#[test]
+fn rects_cover_every_wall_square_once() {
+    for grid in [
+        parse_ascii(SPIRAL).unwrap(),
+        parse_ascii(MIXED).unwrap(),
+        parse_ascii(LATTICE).unwrap(),
+        maze(),
+    ] {
+        let mut covered: Vec<_> = greedy_rects(&grid)
+            .into_iter()
+            .flat_map(|rect| {
+                assert!(rect.width > 0 && rect.height > 0, "{rect:?}");
+                (rect.y..rect.y + rect.height)
+                    .flat_map(move |y| (rect.x..rect.x + rect.width).map(move |x| Point::new(x, y)))
+            })
+            .collect();
+        let count = covered.len();
+        covered.sort_by_key(|point| (point.y, point.x));
+        covered.dedup();
+        assert_eq!(covered.len(), count, "rects overlap");
+        assert_eq!(covered, walls(&grid));
+    }
+}
+
+#[test]
+fn rects_grow_right_then_down() {
+    let rect = |x, y, width, height| Rect {
+        x,
+        y,
+        width,
+        height,
+    };
+    assert_eq!(
+        greedy_rects(&parse_ascii("###.\n###.\n####").unwrap()),
+        [rect(0, 0, 3, 3), rect(3, 2, 1, 1)]
+    );
+    assert_eq!(
+        greedy_rects(&parse_ascii(CROSS).unwrap()),
+        [rect(2, 0, 1, 5), rect(0, 2, 2, 1), rect(3, 2, 2, 1)]
+    );
+    // Columns grow down into the rows below before those are reached
+    assert_eq!(
+        greedy_rects(&parse_ascii(LATTICE).unwrap()),
+        [
+            rect(0, 0, 5, 1),
+            rect(0, 1, 1, 4),
+            rect(2, 1, 1, 4),
+            rect(4, 1, 1, 4),
+            rect(1, 2, 1, 1),
+            rect(3, 2, 1, 1),
+            rect(1, 4, 1, 1),
+            rect(3, 4, 1, 1),
+        ]
+    );
+    assert!(greedy_rects(&parse_ascii("S..\n..E").unwrap()).is_empty());
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "msgpack")]
+
+use maze_lvl_maker::{parse_ascii, scan_grid, Mesh, ScanOptions};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+#[test]
+fn msgpack_matches_json() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    for mesh in [Mesh::Lines, Mesh::Rect, Mesh::Polyline, Mesh::Marching] {
+        let lvl = scan_grid(
+            &grid,
+            ScanOptions {
+                mesh,
+                ..ScanOptions::default()
+            },
+        );
+        let json = serde_json::to_value(&lvl).unwrap();
+        let from_json: serde_json::Value =
+            serde_json::from_slice(&serde_json::to_vec(&json).unwrap()).unwrap();
+
+        let bytes = rmp_serde::to_vec_named(&json).unwrap();
+        let from_msgpack: serde_json::Value = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(from_msgpack, from_json, "{mesh:?}");
+    }
+}
@@ -0,0 +1,42 @@
+use maze_lvl_maker::{LvlBuilder, Point, Wall};
+
+fn wall(start: (u32, u32), end: (u32, u32)) -> Wall {
+    Wall {
+        start: Point::new(start.0, start.1),
+        end: Some(Point::new(end.0, end.1)),
+    }
+}
+
+#[test]
+fn reversed_walls_are_put_in_reading_order() {
+    assert_eq!(wall((5, 2), (1, 2)).normalize(), wall((1, 2), (5, 2)));
+    assert_eq!(wall((3, 6), (3, 0)).normalize(), wall((3, 0), (3, 6)));
+    assert_eq!(wall((4, 4), (1, 1)).normalize(), wall((1, 1), (4, 4)));
+    assert_eq!(wall((1, 4), (4, 1)).normalize(), wall((4, 1), (1, 4)));
+
+    // Already in order, including a diagonal down to the left
+    for ordered in [wall((1, 2), (5, 2)), wall((4, 1), (1, 4))] {
+        assert_eq!(ordered.normalize(), ordered);
+    }
+    let single = Wall {
+        start: Point::new(2, 2),
+        end: None,
+    };
+    assert_eq!(single.normalize(), single);
+}
+
+#[test]
+fn builder_walls_cover_only_the_squares_between_their_ends() {
+    let lvl = LvlBuilder::new(7, 3)
+        .start(Point::new(0, 0))
+        .end(Point::new(6, 0))
+        .wall(Point::new(5, 1), Point::new(1, 1))
+        .dynamic_wall(Point::new(3, 2), Point::new(2, 2), 4)
+        .build()
+        .unwrap();
+
+    assert_eq!(lvl.walls, [wall((1, 1), (5, 1))]);
+    assert_eq!(lvl.dynamic_walls[0].wall, wall((2, 2), (3, 2)));
+    assert!(!lvl.walls[0].wraps());
+    assert!(!lvl.walls[0].contains(0, 1) && !lvl.walls[0].contains(6, 1));
+}
@@ -0,0 +1,49 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{parse_ascii, scan_grid, LvlBuilder, Point, ScanOptions};
+
+// Two rooms, the right one is bigger and holds the checkpoint
+const LEVEL: &str = "\
+#########
+#S.#....#
+#..#..C.#
+#########
+";
+
+#[test]
+fn largest_region_counts_every_passable_square() {
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    assert_eq!(lvl.largest_open_area(), 8);
+
+    let open = LvlBuilder::new(6, 4)
+        .start(Point::new(0, 0))
+        .end(Point::new(5, 3))
+        .checkpoint(Point::new(2, 2))
+        .build()
+        .unwrap();
+    assert_eq!(open.largest_open_area(), 6 * 4);
+}
+
+#[test]
+fn walled_in_points_are_regions_of_one() {
+    // Every other square is a wall
+    let lvl = scan_grid(&parse_ascii("S#\n#E\n").unwrap(), ScanOptions::default());
+    assert_eq!(lvl.largest_open_area(), 1);
+}
+
+#[test]
+fn flag_adds_the_area_to_the_output() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-open-area");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args(["--ascii", "level.txt", "--largest-open-area"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["largest_open_area"], 8);
+}
@@ -0,0 +1,85 @@
+use maze_lvl_maker::{parse_ascii, scan_grid, ScanOptions, SquareType};
+
+const LEVEL: &str = "\
+#########
+#...#...#
+#.#####.#
+#...#...#
+###.#.###
+#...#...#
+#########
+";
+
+#[test]
+fn optimized_walls_cover_every_wall_square_once() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    let optimized = scan_grid(
+        &grid,
+        ScanOptions {
+            optimize_walls: true,
+            ..ScanOptions::default()
+        },
+    );
+
+    let mut covered = vec![0; (grid.width() * grid.height()) as usize];
+    for point in optimized.walls.iter().flat_map(|wall| wall.squares()) {
+        covered[(point.y * grid.width() + point.x) as usize] += 1;
+    }
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let expected = u32::from(grid.get(x, y) == SquareType::Wall);
+            assert_eq!(
+                covered[(y * grid.width() + x) as usize],
+                expected,
+                "square {x},{y}"
+            );
+        }
+    }
+
+    let no_overlap = scan_grid(
+        &grid,
+        ScanOptions {
+            no_overlap: true,
+            ..ScanOptions::default()
+        },
+    );
+    assert!(optimized.walls.len() <= no_overlap.walls.len());
+}
+
+#[test]
+fn walls_covered_by_other_walls_are_pruned() {
+    let grid = parse_ascii(
+        "\
+#######
+#######
+###..##
+#S...E#
+#######
+",
+    )
+    .unwrap();
+    let lvl = scan_grid(&grid, ScanOptions::default());
+
+    let mut covered = vec![0; (grid.width() * grid.height()) as usize];
+    for point in lvl.walls.iter().flat_map(|wall| wall.squares()) {
+        covered[(point.y * grid.width() + point.x) as usize] += 1;
+    }
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let wall = grid.get(x, y) == SquareType::Wall;
+            assert_eq!(
+                covered[(y * grid.width() + x) as usize] > 0,
+                wall,
+                "square {x},{y}"
+            );
+        }
+    }
+    // Every wall has a square no other wall covers
+    for wall in &lvl.walls {
+        assert!(
+            wall.squares()
+                .any(|point| covered[(point.y * grid.width() + point.x) as usize] == 1),
+            "{wall:?} is covered by other walls"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+use maze_lvl_maker::{Mesh, ScanOptions};
+
+#[test]
+fn missing_fields_keep_their_defaults() {
+    let options: ScanOptions =
+        serde_json::from_str(r#"{"mesh": "rect", "diagonals": true}"#).unwrap();
+    assert_eq!(options.mesh, Mesh::Rect);
+    assert!(options.diagonals);
+    assert!(!options.optimize_walls);
+    assert!(options.simplify.abs() < f64::EPSILON);
+}
+
+#[test]
+fn unknown_mesh_is_rejected() {
+    assert!(serde_json::from_str::<ScanOptions>(r#"{"mesh": "triangles"}"#).is_err());
+}
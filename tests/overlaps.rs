@@ -0,0 +1,217 @@
+use maze_lvl_maker::{
+    parse_ascii, scan_grid, Lvl, LvlBuilder, MergeStrategy, Point, ScanOptions, SquareType, Wall,
+    WallOverlap, Wrap,
+};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+#[test]
+fn pairs_sharing_squares() {
+    let lvl = LvlBuilder::new(6, 6)
+        .start(Point::new(2, 2))
+        .end(Point::new(3, 3))
+        .wall(Point::new(0, 0), Point::new(5, 0))
+        .wall(Point::new(1, 0), Point::new(1, 4))
+        .wall(Point::new(5, 5), Point::new(5, 5))
+        .wall(Point::new(2, 0), Point::new(4, 0))
+        .build()
+        .unwrap();
+    let [top, left, _, inside] = lvl.walls[..] else {
+        unreachable!()
+    };
+
+    assert_eq!(
+        lvl.find_overlapping_walls(),
+        [
+            WallOverlap {
+                wall_a: top,
+                wall_b: left,
+                overlap_point: Point::new(1, 0),
+            },
+            // Reported once even though they share three squares
+            WallOverlap {
+                wall_a: top,
+                wall_b: inside,
+                overlap_point: Point::new(2, 0),
+            },
+        ]
+    );
+}
+
+#[test]
+fn merged_runs_meet_at_corners() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    let overlaps = scan_grid(&grid, ScanOptions::default()).find_overlapping_walls();
+    assert!(overlaps
+        .iter()
+        .any(|overlap| overlap.overlap_point == Point::new(0, 0)));
+
+    for options in [
+        ScanOptions {
+            no_overlap: true,
+            ..ScanOptions::default()
+        },
+        ScanOptions {
+            optimize_walls: true,
+            ..ScanOptions::default()
+        },
+    ] {
+        assert_eq!(scan_grid(&grid, options).find_overlapping_walls(), []);
+    }
+}
+
+#[test]
+fn wrapping_walls_overlap_across_the_seam() {
+    let mut lvl = LvlBuilder::new(6, 3)
+        .start(Point::new(2, 1))
+        .end(Point::new(3, 1))
+        .wall(Point::new(0, 0), Point::new(0, 2))
+        .build()
+        .unwrap();
+    lvl.set_wrap(Wrap::X);
+    let seam = Wall {
+        start: Point::new(4, 2),
+        end: Some(Point::new(0, 2)),
+    };
+    lvl.walls.push(seam);
+
+    let overlaps = lvl.find_overlapping_walls();
+    assert_eq!(overlaps.len(), 1);
+    assert_eq!(overlaps[0].wall_b, seam);
+    assert_eq!(overlaps[0].overlap_point, Point::new(0, 2));
+}
+
+const CROSS: &str = "\
+..#..
+..#..
+#####
+..#..
+..#..
+";
+
+const T: &str = "\
+#####
+..#..
+..#..
+";
+
+const LATTICE: &str = "\
+#####
+#.#.#
+#####
+#.#.#
+#####
+";
+
+fn no_overlap(text: &str, merge_strategy: MergeStrategy) -> Lvl {
+    let lvl = scan_grid(
+        &parse_ascii(text).unwrap(),
+        ScanOptions {
+            no_overlap: true,
+            merge_strategy,
+            ..ScanOptions::default()
+        },
+    );
+    assert_eq!(lvl.find_overlapping_walls(), [], "{text}");
+    lvl
+}
+
+/// Both ends of every wall, sorted.
+fn spans(lvl: &Lvl) -> Vec<((u32, u32), (u32, u32))> {
+    let mut spans: Vec<_> = lvl
+        .walls
+        .iter()
+        .map(|wall| {
+            let end = wall.end.unwrap_or(wall.start);
+            ((wall.start.x, wall.start.y), (end.x, end.y))
+        })
+        .collect();
+    spans.sort_unstable();
+    spans
+}
+
+#[test]
+fn no_overlap_covers_every_wall_square_once() {
+    for text in [CROSS, T, LATTICE, LEVEL] {
+        let grid = parse_ascii(text).unwrap();
+        for merge_strategy in [MergeStrategy::HorizontalFirst, MergeStrategy::VerticalFirst] {
+            let lvl = no_overlap(text, merge_strategy);
+            let mut counts = vec![0; (grid.width() * grid.height()) as usize];
+            for square in lvl.walls.iter().flat_map(|wall| wall.squares()) {
+                counts[(square.y * grid.width() + square.x) as usize] += 1;
+            }
+            for y in 0..grid.height() {
+                for x in 0..grid.width() {
+                    let expected = u32::from(grid.get(x, y) == SquareType::Wall);
+                    assert_eq!(
+                        counts[(y * grid.width() + x) as usize],
+                        expected,
+                        "{x},{y} of\n{text}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn no_overlap_splits_the_second_orientation() {
+    assert_eq!(
+        spans(&no_overlap(CROSS, MergeStrategy::HorizontalFirst)),
+        [((0, 2), (4, 2)), ((2, 0), (2, 1)), ((2, 3), (2, 4))]
+    );
+    assert_eq!(
+        spans(&no_overlap(CROSS, MergeStrategy::VerticalFirst)),
+        [((0, 2), (1, 2)), ((2, 0), (2, 4)), ((3, 2), (4, 2))]
+    );
+    assert_eq!(
+        spans(&no_overlap(T, MergeStrategy::HorizontalFirst)),
+        [((0, 0), (4, 0)), ((2, 1), (2, 2))]
+    );
+    assert_eq!(
+        spans(&no_overlap(T, MergeStrategy::VerticalFirst)),
+        [((0, 0), (1, 0)), ((2, 0), (2, 2)), ((3, 0), (4, 0))]
+    );
+
+    // Three full rows and the single squares between them
+    let lattice = no_overlap(LATTICE, MergeStrategy::HorizontalFirst);
+    assert_eq!(lattice.walls.len(), 9);
+    assert_eq!(
+        lattice
+            .walls
+            .iter()
+            .filter(|wall| wall.length() == 5)
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn huge_levels_only_look_at_the_wall_squares() {
+    let far = u32::MAX - 10;
+    let lvl = LvlBuilder::new(u32::MAX, u32::MAX)
+        .start(Point::new(0, 0))
+        .end(Point::new(far, far))
+        .wall(Point::new(far - 4, far), Point::new(far - 1, far))
+        .wall(Point::new(far - 2, far - 2), Point::new(far - 2, far + 2))
+        .build()
+        .unwrap();
+    let [across, down] = lvl.walls[..] else {
+        unreachable!()
+    };
+
+    assert_eq!(
+        lvl.find_overlapping_walls(),
+        [WallOverlap {
+            wall_a: across,
+            wall_b: down,
+            overlap_point: Point::new(far - 2, far),
+        }]
+    );
+}
@@ -0,0 +1,67 @@
+use std::{fs, path::PathBuf, process::Command};
+
+const LEVEL: &str = "\
+#######
+#S..#C#
+#.#...#
+#...#E#
+#######
+";
+
+fn level(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("maze-lvl-maker-preview-{name}.txt"));
+    fs::write(&path, LEVEL).unwrap();
+    path
+}
+
+fn preview(name: &str, args: &[&str], no_color: bool) -> String {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"));
+    command
+        .arg("--ascii")
+        .arg(level(name))
+        .arg("--preview")
+        .args(args);
+    if no_color {
+        command.env("NO_COLOR", "1");
+    } else {
+        command.env_remove("NO_COLOR");
+    }
+    let output = command.output().unwrap();
+    assert!(output.status.success());
+    // The level is still written
+    assert!(output.stdout.starts_with(b"{"));
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn plain_text_matches_the_ascii_level() {
+    assert_eq!(preview("plain", &[], false), LEVEL.replace(' ', "."));
+}
+
+#[test]
+fn colors_unless_no_color_is_set() {
+    let colored = preview("color", &["--color-output"], false);
+    assert!(colored.contains("\x1b["));
+    assert!(colored.contains("\x1b[92mS\x1b[0m"));
+    assert_eq!(colored.lines().count(), 5);
+
+    assert_eq!(preview("no-color", &["--color-output"], true), LEVEL);
+}
+
+#[test]
+fn scale_repeats_every_square() {
+    let scaled = preview("scale", &["--scale-preview", "2"], false);
+    assert_eq!(scaled.lines().next(), Some("##############"));
+    assert_eq!(scaled.lines().nth(1), Some("##SS....##CC##"));
+}
+
+#[test]
+fn scale_requires_the_preview() {
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .arg("--ascii")
+        .arg(level("requires"))
+        .args(["--scale-preview", "2"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
@@ -0,0 +1,36 @@
+use maze_lvl_maker::{
+    scan_grid, scan_grid_with_progress, Grid, ScanOptions, ScanPhase, SquareType,
+};
+
+#[test]
+fn reports_every_hundred_rows_and_at_the_end() {
+    let mut grid = Grid::new(120, 250);
+    for y in (0..250).step_by(2) {
+        for x in 0..120 {
+            grid.set(x, y, SquareType::Wall);
+        }
+    }
+
+    let mut reports = Vec::new();
+    let lvl = scan_grid_with_progress(&grid, ScanOptions::default(), |progress| {
+        reports.push(progress);
+    });
+    assert_eq!(lvl, scan_grid(&grid, ScanOptions::default()));
+
+    let done = |phase| {
+        reports
+            .iter()
+            .filter(|progress| progress.phase == phase)
+            .map(|progress| (progress.done, progress.total))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        done(ScanPhase::Horizontal),
+        [(100, 250), (200, 250), (250, 250)]
+    );
+    assert_eq!(done(ScanPhase::Vertical), [(100, 120), (120, 120)]);
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[0].walls <= pair[1].walls));
+    assert_eq!(reports[2].walls, 125);
+}
@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+use maze_lvl_maker::{parse_ascii, Grid, Point, QuadNode, QuadTree, SquareType};
+
+const LEVEL: &str = "\
+###########
+#S..#....C#
+#.#.#.###.#
+#.#...#E..#
+###########
+";
+
+fn walls(grid: &Grid) -> HashSet<Point> {
+    (0..grid.height())
+        .flat_map(|y| (0..grid.width()).map(move |x| Point::new(x, y)))
+        .filter(|point| grid.get(point.x, point.y) == SquareType::Wall)
+        .collect()
+}
+
+#[test]
+fn quadtree_expands_to_the_walls() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    let tree = QuadTree::from_grid(&grid);
+
+    assert_eq!(tree.size, 16);
+    assert_eq!(tree.walls(), walls(&grid));
+}
+
+#[test]
+fn uniform_levels_are_a_single_node() {
+    let full = parse_ascii("####\n####\n####\n####\n").unwrap();
+    let tree = QuadTree::from_grid(&full);
+    assert_eq!(
+        (tree.root.clone(), tree.depth, tree.nodes),
+        (QuadNode::Full, 0, 1)
+    );
+
+    let empty = QuadTree::from_grid(&parse_ascii("...\n...\n").unwrap());
+    assert_eq!((empty.root, empty.size), (QuadNode::Empty, 4));
+}
+
+#[test]
+fn padding_is_empty() {
+    // 3x3 of walls padded to 4x4 can't be a single full node
+    let tree = QuadTree::from_grid(&parse_ascii("###\n###\n###\n").unwrap());
+    assert_eq!(tree.depth, 2);
+    assert_eq!(tree.walls().len(), 9);
+}
@@ -0,0 +1,117 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use maze_lvl_maker::{
+    parse_ascii, parse_svg, scan_grid, Grid, LvlBuilder, Mesh, Point, ScanOptions,
+};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+#[test]
+fn svg_reads_back_into_the_same_squares() {
+    let grid = parse_ascii(LEVEL).unwrap();
+    for options in [
+        ScanOptions::default(),
+        ScanOptions {
+            diagonals: true,
+            ..ScanOptions::default()
+        },
+        ScanOptions {
+            mesh: Mesh::Rect,
+            ..ScanOptions::default()
+        },
+        ScanOptions {
+            mesh: Mesh::Polyline,
+            ..ScanOptions::default()
+        },
+    ] {
+        let lvl = scan_grid(&grid, options);
+        assert_eq!(
+            parse_svg(&lvl.to_svg(1)).unwrap().to_ascii(),
+            LEVEL,
+            "{options:?}"
+        );
+    }
+
+    let lvl = LvlBuilder::new(4, 3)
+        .start(Point::new(0, 0))
+        .end(Point::new(3, 2))
+        .optional_checkpoint(Point::new(1, 1))
+        .wall(Point::new(2, 0), Point::new(2, 1))
+        .build()
+        .unwrap();
+    assert_eq!(parse_svg(&lvl.to_svg(1)).unwrap(), Grid::from_lvl(&lvl));
+}
+
+#[test]
+fn scale_multiplies_every_coordinate() {
+    let lvl = LvlBuilder::new(3, 2)
+        .start(Point::new(0, 0))
+        .end(Point::new(2, 1))
+        .checkpoint(Point::new(0, 1))
+        .wall(Point::new(1, 0), Point::new(1, 1))
+        .dynamic_wall(Point::new(2, 0), Point::new(2, 0), 7)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        lvl.to_svg(10),
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="20" viewBox="0 0 30 20">
+  <defs>
+    <pattern id="grid" width="10" height="10" patternUnits="userSpaceOnUse">
+      <path d="M10 0H0V10" fill="none" stroke="#dddddd" stroke-width="1"/>
+    </pattern>
+  </defs>
+  <rect width="30" height="20" fill="white"/>
+  <rect width="30" height="20" fill="url(#grid)"/>
+  <rect x="10" y="0" width="10" height="20" fill="black"/>
+  <rect x="20" y="0" width="10" height="10" fill="black" fill-opacity="0.5" data-period="7"/>
+  <circle cx="5" cy="15" r="4" fill="blue"/>
+  <circle cx="25" cy="15" r="4" fill="red"/>
+  <circle cx="5" cy="5" r="4" fill="lime"/>
+</svg>
+"##
+    );
+}
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-render-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> std::process::Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    output
+}
+
+#[test]
+fn render_subcommand_draws_written_levels() {
+    let dir = dir("lvl");
+    run(&dir, &["--ascii", "level.txt", "-o", "level.json.gz"]);
+
+    let svg = run(&dir, &["render", "--lvl", "level.json.gz", "--scale", "4"]).stdout;
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    assert_eq!(String::from_utf8(svg).unwrap(), lvl.to_svg(4));
+
+    run(
+        &dir,
+        &["render", "--lvl", "level.json.gz", "-o", "level.svg"],
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("level.svg")).unwrap(),
+        lvl.to_svg(10)
+    );
+}
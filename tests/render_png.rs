@@ -0,0 +1,126 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use maze_lvl_maker::{Grid, Lvl, LvlBuilder, Point, SquareType, ValidationError};
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-render-png-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn golden(name: &str) -> String {
+    format!("{}/tests/golden/{name}.png", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn golden_images_render_back_pixel_for_pixel() {
+    let dir = dir("golden");
+    for name in ["tiny", "maze", "rooms"] {
+        let original = golden(name);
+        assert!(run(&dir, &["--image", &original, "-o", "level.json"])
+            .status
+            .success());
+        let output = run(
+            &dir,
+            &["render", "--lvl", "level.json", "--png", "level.png"],
+        );
+        assert!(output.status.success(), "{output:?}");
+
+        assert_eq!(
+            image::open(dir.join("level.png")).unwrap().to_rgba8(),
+            image::open(&original).unwrap().to_rgba8(),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn cell_size_scales_the_squares() {
+    let dir = dir("cell-size");
+    run(&dir, &["--image", &golden("maze"), "-o", "level.json"]);
+    let output = run(
+        &dir,
+        &[
+            "render",
+            "--lvl",
+            "level.json",
+            "--png",
+            "big.png",
+            "--cell-size",
+            "4",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    // Read back at the same scale it's the same level
+    run(
+        &dir,
+        &["--image", "big.png", "--scale", "4", "-o", "again.json"],
+    );
+    assert_eq!(
+        fs::read(dir.join("again.json")).unwrap(),
+        fs::read(dir.join("level.json")).unwrap()
+    );
+}
+
+#[test]
+fn overlapping_walls_paint_the_same_pixels() {
+    let lvl = LvlBuilder::new(5, 3)
+        .start(Point::new(0, 1))
+        .end(Point::new(4, 1))
+        .wall(Point::new(0, 0), Point::new(4, 0))
+        .wall(Point::new(1, 0), Point::new(3, 0))
+        .wall(Point::new(2, 0), Point::new(2, 2))
+        .build()
+        .unwrap();
+    let image = Grid::from_lvl(&lvl).to_image(1);
+
+    assert_eq!(image.get_pixel(2, 0).0, SquareType::Wall.to_rgba());
+    assert_eq!(image.get_pixel(2, 1).0, [0, 0, 0, 255]);
+    assert_eq!(image.get_pixel(0, 1).0, [0, 255, 0, 255]);
+    assert_eq!(image.get_pixel(4, 1).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(1, 2).0, [255, 255, 255, 255]);
+}
+
+#[test]
+fn points_outside_the_level_name_their_field() {
+    let mut lvl: Lvl = LvlBuilder::new(4, 4)
+        .start(Point::new(0, 0))
+        .end(Point::new(3, 3))
+        .checkpoint(Point::new(1, 1))
+        .wall(Point::new(0, 2), Point::new(3, 2))
+        .build()
+        .unwrap();
+    assert_eq!(lvl.check_bounds(), Ok(()));
+
+    lvl.walls[0].end = Some(Point::new(4, 2));
+    assert_eq!(
+        lvl.check_bounds(),
+        Err(ValidationError::FieldOutOfBounds {
+            field: "walls[0]".to_owned(),
+            point: Point::new(4, 2),
+        })
+    );
+
+    lvl.checkpoints.push(Point::new(2, 7));
+    let dir = dir("outside");
+    fs::write(dir.join("level.json"), serde_json::to_string(&lvl).unwrap()).unwrap();
+    let output = run(
+        &dir,
+        &["render", "--lvl", "level.json", "--png", "level.png"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("checkpoints[1] at (2, 7) is outside of the level"));
+    assert!(!dir.join("level.png").exists());
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "ron")]
+
+use maze_lvl_maker::{LvlBuilder, Orientation, Point, Wall};
+use ron::ser::PrettyConfig;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Walls {
+    walls: Vec<Wall>,
+}
+
+fn level() -> maze_lvl_maker::Lvl {
+    LvlBuilder::new(6, 6)
+        .start(Point::new(1, 1))
+        .end(Point::new(4, 4))
+        .wall(Point::new(0, 0), Point::new(5, 0))
+        .wall(Point::new(0, 1), Point::new(0, 5))
+        .wall(Point::new(2, 2), Point::new(4, 4))
+        .wall(Point::new(3, 5), Point::new(1, 3))
+        .wall(Point::new(5, 3), Point::new(5, 3))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn walls_read_back_into_the_level() {
+    let lvl = level();
+    for ron in [
+        ron::to_string(&lvl).unwrap(),
+        ron::ser::to_string_pretty(&lvl, PrettyConfig::default()).unwrap(),
+    ] {
+        assert_eq!(
+            ron::from_str::<Walls>(&ron).unwrap().walls,
+            lvl.walls,
+            "{ron}"
+        );
+    }
+}
+
+#[test]
+fn ends_are_options() {
+    let ron = ron::to_string(&level()).unwrap();
+    assert!(
+        ron.contains("(start:(x:5,y:3),end:None,orientation:point)"),
+        "{ron}"
+    );
+    assert!(
+        ron.contains("(start:(x:0,y:0),end:Some((x:5,y:0)),orientation:horizontal)"),
+        "{ron}"
+    );
+}
+
+#[test]
+fn orientations_are_variants() {
+    for orientation in [
+        Orientation::Horizontal,
+        Orientation::Vertical,
+        Orientation::Diagonal,
+        Orientation::Point,
+    ] {
+        let ron = ron::to_string(&orientation).unwrap();
+        assert!(!ron.contains('"'), "{ron}");
+        assert_eq!(ron::from_str::<Orientation>(&ron).unwrap(), orientation);
+    }
+}
+
+#[test]
+fn hand_written_walls_without_orientation() {
+    let ron = "(walls: [
+        (start: (x: 2, y: 1), end: None),
+        (start: (x: 0, y: 0), end: Some((x: 3, y: 0))),
+    ])";
+    assert_eq!(
+        ron::from_str::<Walls>(ron).unwrap().walls,
+        [
+            Wall {
+                start: Point::new(2, 1),
+                end: None,
+            },
+            Wall {
+                start: Point::new(0, 0),
+                end: Some(Point::new(3, 0)),
+            },
+        ]
+    );
+}
@@ -0,0 +1,152 @@
+use std::{fs, path::Path, process::Command};
+
+use maze_lvl_maker::{parse_ascii, scan_grid, Grid, Lvl, Point, Room, ScanOptions};
+
+/// A 10x5 room with a 3x5 room below its left side.
+const L_SHAPE: &str = "\
+S.........
+..........
+..........
+..........
+.........E
+...#######
+...#######
+...#######
+...#######
+...#######
+";
+
+fn scan(grid: &Grid) -> Lvl {
+    scan_grid(grid, ScanOptions::default())
+}
+
+fn golden(name: &str) -> Grid {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/golden/{name}.png"));
+    Grid::from_image(&image::open(path).unwrap())
+}
+
+/// Area of the largest rectangle of open squares, from scratch.
+fn largest_area(open: &[Vec<bool>]) -> u32 {
+    let width = open.first().map_or(0, Vec::len);
+    let mut heights = vec![0; width];
+    let mut best = 0;
+    for row in open {
+        for (height, open) in heights.iter_mut().zip(row) {
+            *height = if *open { *height + 1 } else { 0 };
+        }
+        for first in 0..width {
+            let mut lowest = u32::MAX;
+            for (last, height) in heights.iter().enumerate().skip(first) {
+                lowest = lowest.min(*height);
+                best = best.max(lowest * (last - first + 1) as u32);
+            }
+        }
+    }
+    best
+}
+
+fn assert_rooms(grid: &Grid, min_area: u32) {
+    let rooms = scan(grid).rooms(min_area);
+    let mut open: Vec<Vec<bool>> = (0..grid.height())
+        .map(|y| {
+            (0..grid.width())
+                .map(|x| grid.is_passable(Point::new(x, y)))
+                .collect()
+        })
+        .collect();
+
+    for room in &rooms {
+        assert!(room.area >= min_area, "{room:?}");
+        // Every room is the largest rectangle of what the ones before left
+        assert_eq!(room.area, largest_area(&open), "{room:?}");
+        for row in &mut open[room.top_left.y as usize..=room.bottom_right.y as usize] {
+            for open in &mut row[room.top_left.x as usize..=room.bottom_right.x as usize] {
+                assert!(*open, "{room:?} isn't open or overlaps another room");
+                *open = false;
+            }
+        }
+    }
+    assert!(largest_area(&open) < min_area);
+}
+
+#[test]
+fn rooms_below_a_taken_room_are_still_found() {
+    let rooms = scan(&parse_ascii(L_SHAPE).unwrap()).rooms(4);
+    assert_eq!(
+        rooms,
+        [
+            Room {
+                top_left: Point::new(0, 0),
+                bottom_right: Point::new(9, 4),
+                area: 50,
+            },
+            Room {
+                top_left: Point::new(0, 5),
+                bottom_right: Point::new(2, 9),
+                area: 15,
+            },
+        ]
+    );
+    assert_eq!(scan(&parse_ascii(L_SHAPE).unwrap()).rooms(16).len(), 1);
+}
+
+#[test]
+fn every_room_is_the_largest_that_is_left() {
+    for grid in [
+        parse_ascii(L_SHAPE).unwrap(),
+        golden("rooms"),
+        golden("maze"),
+    ] {
+        for min_area in [1, 4, 12] {
+            assert_rooms(&grid, min_area);
+        }
+    }
+}
+
+#[test]
+fn many_tall_rooms() {
+    // Corridors from top to bottom, every one a room of its own
+    let row = "..#".repeat(100);
+    let text = format!(
+        "S{}\n{}\nE{}\n",
+        &row[1..],
+        vec![row.as_str(); 300].join("\n"),
+        &row[1..]
+    );
+    let rooms = scan(&parse_ascii(&text).unwrap()).rooms(4);
+    assert_eq!(rooms.len(), 100);
+    assert!(rooms.iter().all(|room| room.area == 2 * 302));
+}
+
+#[test]
+fn min_room_size_flag_drops_smaller_rooms() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-rooms");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), L_SHAPE).unwrap();
+    let rooms = |min_room_size: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args([
+                "--ascii",
+                "level.txt",
+                "--rooms",
+                "--min-room-size",
+                min_room_size,
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["rooms"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|room| room["area"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(rooms("4"), [50, 15]);
+    assert_eq!(rooms("16"), [50]);
+    assert_eq!(rooms("51"), Vec::<u64>::new());
+}
@@ -0,0 +1,142 @@
+use maze_lvl_maker::{parse_ascii, Grid, Lvl, LvlBuilder, Point, ValidationError};
+use proptest::prelude::*;
+
+/// Walls, dynamic walls and points sorted, so levels that only differ in
+/// the order of their lists compare equal.
+fn sorted(mut lvl: Lvl) -> Lvl {
+    lvl.walls.sort();
+    lvl.dynamic_walls
+        .sort_by_key(|dynamic_wall| (dynamic_wall.wall, dynamic_wall.period));
+    lvl.checkpoints.sort_by_key(|point| (point.y, point.x));
+    lvl.optional_checkpoints
+        .sort_by_key(|point| (point.y, point.x));
+    lvl
+}
+
+fn round_trip(lvl: &Lvl) -> Lvl {
+    Grid::from_lvl(lvl).to_lvl().unwrap()
+}
+
+/// Horizontal or vertical run of up to 5 squares inside the level.
+fn run(width: u32, height: u32) -> impl Strategy<Value = (Point, Point)> {
+    (0..width, 0..height, any::<bool>(), 0..5_u32).prop_map(move |(x, y, vertical, length)| {
+        let end = if vertical {
+            Point::new(x, (y + length).min(height - 1))
+        } else {
+            Point::new((x + length).min(width - 1), y)
+        };
+        (Point::new(x, y), end)
+    })
+}
+
+fn level() -> impl Strategy<Value = Lvl> {
+    (2..16_u32, 2..16_u32)
+        .prop_flat_map(|(width, height)| {
+            let squares = (0..width * height).collect::<Vec<_>>();
+            (
+                Just((width, height)),
+                prop::collection::vec(run(width, height), 0..12),
+                prop::collection::vec((run(width, height), 1..5_u8), 0..3),
+                // Start, end and then the checkpoints
+                Just(squares).prop_shuffle(),
+                0..3_usize,
+                0..3_usize,
+            )
+        })
+        .prop_map(
+            |((width, height), walls, dynamic_walls, squares, checkpoints, optional)| {
+                let points: Vec<Point> = squares
+                    .into_iter()
+                    .map(|square| Point::new(square % width, square / width))
+                    .take(2 + checkpoints + optional)
+                    .collect();
+
+                let mut builder = LvlBuilder::new(width, height)
+                    .start(points[0])
+                    .end(points[1]);
+                for (start, end) in walls {
+                    builder = builder.wall(start, end);
+                }
+                for ((start, end), period) in dynamic_walls {
+                    builder = builder.dynamic_wall(start, end, period);
+                }
+                for point in &points[2..2 + checkpoints] {
+                    builder = builder.checkpoint(*point);
+                }
+                for point in &points[2 + checkpoints..] {
+                    builder = builder.optional_checkpoint(*point);
+                }
+                builder.build().unwrap()
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn grid_round_trip(lvl in level()) {
+        let grid = Grid::from_lvl(&lvl);
+        let scanned = grid.to_lvl().unwrap();
+
+        // Built walls can overlap or be split anywhere, the scanned ones
+        // cover the same squares
+        prop_assert_eq!(Grid::from_lvl(&scanned), grid);
+        prop_assert_eq!(scanned.start, lvl.start);
+        prop_assert_eq!(scanned.end, lvl.end);
+        let lvl = sorted(lvl);
+        let scanned = sorted(scanned);
+        prop_assert_eq!(&scanned.checkpoints, &lvl.checkpoints);
+        prop_assert_eq!(&scanned.optional_checkpoints, &lvl.optional_checkpoints);
+
+        // Scanned levels come back exactly
+        prop_assert_eq!(sorted(round_trip(&scanned)), scanned);
+    }
+}
+
+#[test]
+fn scanned_walls_come_back_unchanged() {
+    let lvl = LvlBuilder::new(5, 4)
+        .start(Point::new(1, 1))
+        .end(Point::new(3, 2))
+        .checkpoint(Point::new(3, 1))
+        .wall(Point::new(0, 0), Point::new(4, 0))
+        .wall(Point::new(0, 3), Point::new(4, 3))
+        .wall(Point::new(0, 0), Point::new(0, 3))
+        .wall(Point::new(4, 0), Point::new(4, 3))
+        .wall(Point::new(2, 0), Point::new(2, 3))
+        .build()
+        .unwrap();
+
+    assert_eq!(sorted(round_trip(&lvl)), sorted(lvl));
+}
+
+#[test]
+fn start_and_end_are_required() {
+    let no_start = parse_ascii("#..E#\n").unwrap();
+    assert_eq!(no_start.to_lvl(), Err(ValidationError::MissingStart));
+
+    let two_ends = parse_ascii("#S.E#\n#..E#\n").unwrap();
+    assert_eq!(
+        two_ends.to_lvl(),
+        Err(ValidationError::MultipleEnds(vec![
+            Point::new(3, 0),
+            Point::new(3, 1)
+        ]))
+    );
+}
+
+#[test]
+fn builder_rejects_invalid_levels() {
+    let builder = LvlBuilder::new(3, 3).start(Point::new(0, 0));
+    assert_eq!(builder.clone().build(), Err(ValidationError::MissingEnd));
+    assert_eq!(
+        builder.clone().end(Point::new(0, 0)).build(),
+        Err(ValidationError::SharedSquare(Point::new(0, 0)))
+    );
+    assert_eq!(
+        builder
+            .end(Point::new(2, 2))
+            .wall(Point::new(1, 0), Point::new(1, 3))
+            .build(),
+        Err(ValidationError::OutOfBounds(Point::new(1, 3)))
+    );
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "schema")]
+
+use std::{fs, path::Path, process::Command};
+
+use maze_lvl_maker::Lvl;
+use serde_json::Value;
+
+#[test]
+fn every_property_has_a_description() {
+    let schema = serde_json::to_value(schemars::schema_for!(Lvl)).unwrap();
+    let definitions = schema["$defs"].as_object().unwrap().values();
+    for definition in std::iter::once(&schema).chain(definitions) {
+        let Some(properties) = definition.get("properties").and_then(Value::as_object) else {
+            continue;
+        };
+        for (name, property) in properties {
+            assert!(property.get("description").is_some(), "{name}");
+        }
+    }
+}
+
+#[test]
+fn wall_schema_matches_the_serialized_fields() {
+    let schema = serde_json::to_value(schemars::schema_for!(Lvl)).unwrap();
+    let properties = schema["$defs"]["Wall"]["properties"].as_object().unwrap();
+    let names: Vec<&str> = properties.keys().map(String::as_str).collect();
+    assert_eq!(names, ["start", "end", "orientation"]);
+}
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+fn run(dir: &Path, args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    output.stdout
+}
+
+fn json(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes).unwrap()
+}
+
+#[test]
+fn written_levels_validate_against_their_schema_variant() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-schema");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+
+    for flags in [
+        &[][..],
+        &["--diagonals"],
+        &["--mesh", "rect", "--wrap", "x"],
+        &["--compact-walls"],
+        &["--no-orientation"],
+        &["--compact-walls", "--no-orientation"],
+    ] {
+        let level = json(&run(&dir, &[&["--ascii", "level.txt"], flags].concat()));
+        let variant: Vec<&str> = flags
+            .iter()
+            .copied()
+            .filter(|flag| ["--compact-walls", "--no-orientation"].contains(flag))
+            .collect();
+        let schema = json(&run(&dir, &[&["schema"], &variant[..]].concat()));
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let errors: Vec<String> = validator
+            .iter_errors(&level)
+            .map(|error| error.to_string())
+            .collect();
+        assert!(errors.is_empty(), "{flags:?}: {errors:?}");
+
+        if !variant.is_empty() {
+            let default = json(&run(&dir, &["schema"]));
+            assert!(
+                !jsonschema::validator_for(&default)
+                    .unwrap()
+                    .is_valid(&level),
+                "{flags:?}"
+            );
+        }
+    }
+
+    assert_eq!(
+        json(&run(&dir, &["--dump-schema"])),
+        json(&run(&dir, &["schema"]))
+    );
+}
@@ -0,0 +1,99 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{parse_ascii, scan_grid, scan_image, Lvl, Point, ScanOptions, Wrap};
+
+/// The checkpoint is off to the side, the end is right of the start.
+const DETOUR: &str = "\
+#######
+#S...E#
+#.###.#
+#C#...#
+#######
+";
+
+fn scan(text: &str) -> Lvl {
+    scan_grid(&parse_ascii(text).unwrap(), ScanOptions::default())
+}
+
+fn assert_walks(lvl: &Lvl, path: &[Point]) {
+    assert_eq!(path.first(), Some(&lvl.start));
+    assert_eq!(path.last(), Some(&lvl.end));
+    for step in path.windows(2) {
+        let (dx, dy) = (step[0].x.abs_diff(step[1].x), step[0].y.abs_diff(step[1].y));
+        let across_x = lvl.wrap.wraps_x() && dy == 0 && dx == lvl.width - 1;
+        let across_y = lvl.wrap.wraps_y() && dx == 0 && dy == lvl.height - 1;
+        assert!(dx + dy == 1 || across_x || across_y, "{step:?}");
+    }
+}
+
+#[test]
+fn solutions_of_the_golden_levels_are_walkable() {
+    for name in ["tiny", "maze", "rooms"] {
+        let path = format!("{}/tests/golden/{name}.png", env!("CARGO_MANIFEST_DIR"));
+        let lvl = scan_image(&image::open(path).unwrap(), ScanOptions::default());
+        let solution = lvl.solution().unwrap();
+        assert_walks(&lvl, &solution);
+
+        let mut visited = solution.iter();
+        for checkpoint in &lvl.checkpoints {
+            assert!(visited.any(|point| point == checkpoint), "{name}");
+        }
+    }
+}
+
+#[test]
+fn checkpoints_are_visited_in_order() {
+    let lvl = scan(DETOUR);
+    let solution = lvl.solution().unwrap();
+    assert_walks(&lvl, &solution);
+    // Down to the checkpoint and back up, then along the top
+    assert_eq!(solution.len(), 4 + 1 + 4);
+    assert_eq!(solution[2], Point::new(1, 3));
+
+    let mut direct = lvl.clone();
+    direct.checkpoints.clear();
+    let solution = direct.solution().unwrap();
+    assert_eq!(
+        solution.len() as u32,
+        direct.shortest_path_length().unwrap() + 1
+    );
+}
+
+#[test]
+fn solutions_cross_the_seam() {
+    let mut lvl = scan("##.####\n..S#E..\n##.####\n");
+    assert_eq!(lvl.solution(), None);
+
+    lvl.set_wrap(Wrap::X);
+    let solution = lvl.solution().unwrap();
+    assert_walks(&lvl, &solution);
+    assert_eq!(solution.len(), 6);
+}
+
+#[test]
+fn flag_embeds_the_solution_or_null() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-solution");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("detour.txt"), DETOUR).unwrap();
+    fs::write(dir.join("blocked.txt"), "#####\n#S#E#\n#####\n").unwrap();
+    let run = |file: &str, args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(["--ascii", file])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    assert!(run("detour.txt", &[]).get("solution").is_none());
+    let json = run("detour.txt", &["--embed-solution"]);
+    let solution: Vec<Point> = serde_json::from_value(json["solution"].clone()).unwrap();
+    assert_eq!(solution, scan(DETOUR).solution().unwrap());
+
+    let json = run("blocked.txt", &["--embed-solution"]);
+    assert!(json["solution"].is_null());
+    assert!(json.get("solution").is_some());
+}
@@ -0,0 +1,181 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{
+    parse_ascii, scan_grid, Lvl, LvlBuilder, Orientation, OrientationFirst, Point, ScanOptions,
+    Wall, WallSortKey,
+};
+
+const LEVEL: &str = "\
+#####
+#S..#
+#.#E#
+#####
+";
+
+#[test]
+fn sort_walls_by_position_and_orientation() {
+    let mut lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+
+    lvl.sort_walls(WallSortKey::Position);
+    let starts: Vec<_> = lvl
+        .walls
+        .iter()
+        .map(|wall| (wall.start.y, wall.start.x))
+        .collect();
+    assert!(starts.is_sorted());
+
+    lvl.sort_walls(WallSortKey::Orientation(OrientationFirst::Vertical));
+    let orientations: Vec<_> = lvl.walls.iter().map(|wall| wall.orientation()).collect();
+    assert_eq!(
+        orientations,
+        [
+            Orientation::Vertical,
+            Orientation::Vertical,
+            Orientation::Vertical,
+            Orientation::Horizontal,
+            Orientation::Horizontal,
+        ]
+    );
+    // Stable inside the groups, so still in reading order
+    assert_eq!(lvl.walls[0].start.x, 0);
+
+    lvl.sort_walls(WallSortKey::Length);
+    let lengths: Vec<_> = lvl.walls.iter().map(|wall| wall.length()).collect();
+    assert_eq!(lengths, [5, 5, 4, 4, 2]);
+}
+
+#[test]
+fn ties_are_broken_by_orientation() {
+    let wall = |x, y| Wall {
+        start: Point::new(1, 1),
+        end: Some(Point::new(x, y)),
+    };
+    let (horizontal, vertical, diagonal) = (wall(2, 1), wall(1, 2), wall(0, 2));
+
+    assert!(horizontal < vertical);
+    assert!(vertical < diagonal);
+    assert_eq!(diagonal.orientation(), Orientation::Diagonal);
+}
+
+/// Start in the top left, end in the top right. Every checkpoint but
+/// (1, 1) is as far from the start.
+fn checkpoint_level() -> Lvl {
+    LvlBuilder::new(6, 6)
+        .start(Point::new(0, 0))
+        .end(Point::new(5, 0))
+        .checkpoint(Point::new(4, 0))
+        .checkpoint(Point::new(3, 1))
+        .checkpoint(Point::new(0, 4))
+        .checkpoint(Point::new(1, 3))
+        .checkpoint(Point::new(1, 1))
+        .build()
+        .unwrap()
+}
+
+fn points(points: &[(u32, u32)]) -> Vec<Point> {
+    points.iter().map(|&(x, y)| Point::new(x, y)).collect()
+}
+
+#[test]
+fn checkpoints_sort_stably_by_distance() {
+    let mut lvl = checkpoint_level();
+    lvl.sort_checkpoints_by_proximity_to_start();
+    assert_eq!(
+        lvl.checkpoints,
+        points(&[(1, 1), (4, 0), (3, 1), (0, 4), (1, 3)])
+    );
+
+    let mut lvl = checkpoint_level();
+    lvl.sort_checkpoints_by_proximity_to_end();
+    assert_eq!(
+        lvl.checkpoints,
+        points(&[(4, 0), (3, 1), (1, 1), (1, 3), (0, 4)])
+    );
+
+    lvl.sort_checkpoints_lexicographic();
+    assert_eq!(
+        lvl.checkpoints,
+        points(&[(4, 0), (1, 1), (3, 1), (1, 3), (0, 4)])
+    );
+}
+
+#[test]
+fn checkpoint_sort_flag_reorders_the_output() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-checkpoint-sort");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), "S...C\n.....\nC...E\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(&dir)
+        .args([
+            "--ascii",
+            "level.txt",
+            "--checkpoint-sort",
+            "proximity-start",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let lvl: Lvl = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(lvl.checkpoints, points(&[(0, 2), (4, 0)]));
+}
+
+/// Rows of single squares and pairs, so most walls tie on length.
+const EQUAL_WALLS: &str = "\
+S..........
+#.#.#.#.#.#
+...........
+.#.#.#.#.#.
+...........
+##.##.##.##
+...........
+.##.##.##..
+..........E
+";
+
+#[test]
+fn wall_order_agrees_with_eq() {
+    let lvl = scan_grid(&parse_ascii(EQUAL_WALLS).unwrap(), ScanOptions::default());
+    for a in &lvl.walls {
+        for b in &lvl.walls {
+            assert_eq!(a.cmp(b).is_eq(), a == b, "{a:?} {b:?}");
+            assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{a:?} {b:?}");
+        }
+    }
+
+    let mut sorted = lvl.walls.clone();
+    sorted.sort_unstable();
+    for shift in 0..lvl.walls.len() {
+        let mut shuffled = lvl.walls.clone();
+        shuffled.rotate_left(shift);
+        shuffled.reverse();
+        shuffled.sort_unstable();
+        assert_eq!(shuffled, sorted);
+    }
+}
+
+#[test]
+fn equal_length_walls_come_out_the_same_every_run() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-equal-walls");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), EQUAL_WALLS).unwrap();
+
+    let run = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(["--ascii", "level.txt"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        output.stdout
+    };
+    let first = run();
+    let lvl: Lvl = serde_json::from_slice(&first).unwrap();
+    let lengths: Vec<_> = lvl.walls.iter().map(|wall| wall.length()).collect();
+    assert!(lengths.windows(2).filter(|pair| pair[0] == pair[1]).count() > 10);
+    for _ in 0..5 {
+        assert_eq!(run(), first);
+    }
+}
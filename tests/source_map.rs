@@ -0,0 +1,169 @@
+use std::{collections::HashMap, fs, process::Command};
+
+use maze_lvl_maker::{scan_image, Grid, LvlBuilder, Point, ScanOptions, SquareType, Wall, Wrap};
+
+#[test]
+fn every_wall_square_maps_to_a_wall_covering_it() {
+    for name in ["tiny", "maze", "rooms"] {
+        let path = format!("{}/tests/golden/{name}.png", env!("CARGO_MANIFEST_DIR"));
+        let lvl = scan_image(&image::open(path).unwrap(), ScanOptions::default());
+        let map = lvl.source_map();
+        let grid = Grid::from_lvl(&lvl);
+
+        let walls = (0..grid.height())
+            .flat_map(|y| (0..grid.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| grid.get(x, y) == SquareType::Wall)
+            .count();
+        assert_eq!(map.len(), walls, "{name}");
+        for (&(x, y), &index) in &map {
+            assert!(
+                lvl.walls[index]
+                    .squares()
+                    .any(|square| square == Point::new(x, y)),
+                "{name} ({x}, {y})"
+            );
+        }
+    }
+}
+
+#[test]
+fn shared_squares_belong_to_the_first_wall() {
+    let mut lvl = LvlBuilder::new(6, 4)
+        .start(Point::new(0, 3))
+        .end(Point::new(5, 3))
+        .wall(Point::new(0, 1), Point::new(3, 1))
+        .wall(Point::new(2, 0), Point::new(2, 2))
+        .build()
+        .unwrap();
+    lvl.wrap = Wrap::X;
+    lvl.walls.push(Wall {
+        start: Point::new(4, 2),
+        end: Some(Point::new(1, 2)),
+    });
+    let map = lvl.source_map();
+
+    assert_eq!(map[&(2, 1)], 0);
+    assert_eq!(map[&(2, 0)], 1);
+    assert_eq!(map[&(2, 2)], 1);
+    // Across the seam from (4, 2) to (1, 2)
+    let wrapped: Vec<_> = [(4, 2), (5, 2), (0, 2), (1, 2)]
+        .iter()
+        .map(|square| map[square])
+        .collect();
+    assert_eq!(wrapped, [2; 4]);
+    assert!(!map.contains_key(&(3, 2)));
+}
+
+#[test]
+fn flag_writes_the_sidecar_unless_it_is_too_big() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-source-map");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let image = format!("{}/tests/golden/tiny.png", env!("CARGO_MANIFEST_DIR"));
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(["--image", &image, "--source-map", "map.json"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["-o", "level.json"]);
+    assert!(output.status.success(), "{output:?}");
+    let map: HashMap<String, usize> =
+        serde_json::from_slice(&fs::read(dir.join("map.json")).unwrap()).unwrap();
+    let lvl = scan_image(&image::open(&image).unwrap(), ScanOptions::default());
+    let expected: HashMap<String, usize> = lvl
+        .source_map()
+        .into_iter()
+        .map(|((x, y), index)| (format!("{x},{y}"), index))
+        .collect();
+    assert_eq!(map, expected);
+
+    fs::remove_file(dir.join("map.json")).unwrap();
+    fs::remove_file(dir.join("level.json")).unwrap();
+    let output = run(&["-o", "level.json", "--max-source-map-size", "3"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--max-source-map-size 3"));
+    assert!(!dir.join("map.json").exists());
+    assert!(!dir.join("level.json").exists());
+
+    assert!(!run(&["--mesh", "rect"]).status.success());
+    for flags in [&["--chunk-size", "4"][..], &["--diff", "level.json"]] {
+        let output = run(flags);
+        assert_eq!(output.status.code(), Some(2), "{flags:?}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("cannot be used with"), "{stderr}");
+    }
+}
+
+#[test]
+fn inverse_lists_every_square_of_every_wall() {
+    let path = format!("{}/tests/golden/maze.png", env!("CARGO_MANIFEST_DIR"));
+    let lvl = scan_image(&image::open(path).unwrap(), ScanOptions::default());
+    let inverse = lvl.inverse_source_map();
+
+    assert_eq!(inverse.len(), lvl.walls.len());
+    for (wall, squares) in lvl.walls.iter().zip(&inverse) {
+        assert_eq!(squares.len() as u32, wall.length(), "{wall:?}");
+    }
+    for ((x, y), index) in lvl.source_map() {
+        assert!(inverse[index].contains(&(x, y)), "({x}, {y})");
+    }
+}
+
+#[test]
+fn inverse_lists_shared_squares_for_each_wall() {
+    let mut lvl = LvlBuilder::new(6, 4)
+        .start(Point::new(0, 3))
+        .end(Point::new(5, 3))
+        .wall(Point::new(0, 1), Point::new(3, 1))
+        .wall(Point::new(2, 0), Point::new(2, 2))
+        .build()
+        .unwrap();
+    lvl.wrap = Wrap::X;
+    lvl.walls.push(Wall {
+        start: Point::new(4, 2),
+        end: Some(Point::new(1, 2)),
+    });
+
+    assert_eq!(
+        lvl.inverse_source_map(),
+        [
+            vec![(0, 1), (1, 1), (2, 1), (3, 1)],
+            vec![(2, 0), (2, 1), (2, 2)],
+            vec![(4, 2), (5, 2), (0, 2), (1, 2)],
+        ]
+    );
+}
+
+#[test]
+fn inverse_flag_writes_the_squares_of_the_walls() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-inverse-source-map");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let image = format!("{}/tests/golden/tiny.png", env!("CARGO_MANIFEST_DIR"));
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(["--image", &image, "--inverse-source-map", "walls.json"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&[]);
+    assert!(output.status.success(), "{output:?}");
+    let walls: Vec<Vec<(u32, u32)>> =
+        serde_json::from_slice(&fs::read(dir.join("walls.json")).unwrap()).unwrap();
+    let lvl = scan_image(&image::open(&image).unwrap(), ScanOptions::default());
+    assert_eq!(walls, lvl.inverse_source_map());
+
+    fs::remove_file(dir.join("walls.json")).unwrap();
+    let output = run(&["--max-source-map-size", "3"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("the inverse source map has"));
+    assert!(output.stdout.is_empty());
+    assert!(!dir.join("walls.json").exists());
+}
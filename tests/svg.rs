@@ -0,0 +1,104 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{parse_svg, Lvl, Point, SquareType, SvgError};
+
+const LEVEL: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="5" height="3">
+  <rect width="5" height="1" fill="black"/>
+  <rect x="1.5" y="2" width="2" height="1" style="stroke: none; fill: #000"/>
+  <rect x="4" y="1" width="1" height="1" fill="orange"/>
+  <circle cx="0.5" cy="1.5" r="0.4" fill="lime"/>
+  <ellipse cx="4.5" cy="2.5" rx="0.4" ry="0.3" fill="rgb(255, 0, 0)"/>
+</svg>
+"##;
+
+#[test]
+fn shapes_paint_the_squares_under_them() {
+    let grid = parse_svg(LEVEL).unwrap();
+    assert_eq!((grid.width(), grid.height()), (5, 3));
+    for x in 0..5 {
+        assert_eq!(grid.get(x, 0), SquareType::Wall);
+    }
+    // Every square the rect touches
+    for x in 1..4 {
+        assert_eq!(grid.get(x, 2), SquareType::Wall);
+    }
+    assert_eq!(grid.get(0, 2), SquareType::Empty);
+    // Fills outside of the palette are skipped
+    assert_eq!(grid.get(4, 1), SquareType::Empty);
+    assert_eq!(grid.get(0, 1), SquareType::Start);
+    assert_eq!(grid.get(4, 2), SquareType::End);
+}
+
+#[test]
+fn size_falls_back_to_the_view_box() {
+    let grid =
+        parse_svg(r#"<svg viewBox="0 0 4.5 2"><rect width="1" height="1" fill="black"/></svg>"#)
+            .unwrap();
+    assert_eq!((grid.width(), grid.height()), (5, 2));
+    assert_eq!(grid.get(0, 0), SquareType::Wall);
+
+    assert!(matches!(
+        parse_svg("<svg><rect/></svg>"),
+        Err(SvgError::MissingSize)
+    ));
+}
+
+#[test]
+fn rects_need_a_width_and_height() {
+    for (rect, attribute) in [
+        (r#"<rect x="1" height="1" fill="black"/>"#, "width"),
+        (r#"<rect x="1" width="1" fill="black"/>"#, "height"),
+        (
+            r#"<rect x="1" width="wide" height="1" fill="black"/>"#,
+            "width",
+        ),
+    ] {
+        let svg = format!(r#"<svg width="3" height="3">{rect}</svg>"#);
+        let error = parse_svg(&svg).unwrap_err();
+        assert!(
+            matches!(&error, SvgError::InvalidAttribute { element, attribute: found }
+                if element == "rect" && found == attribute),
+            "{rect}: {error:?}"
+        );
+    }
+
+    // Positions default to 0 like in SVG
+    let grid =
+        parse_svg(r#"<svg width="3" height="3"><rect width="1" height="1" fill="black"/></svg>"#)
+            .unwrap();
+    assert_eq!(grid.get(0, 0), SquareType::Wall);
+}
+
+#[test]
+fn svg_flag_reads_the_level() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-svg");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.svg"), LEVEL).unwrap();
+    fs::write(
+        dir.join("broken.svg"),
+        r#"<svg width="3" height="3"><rect x="1" height="1" fill="black"/></svg>"#,
+    )
+    .unwrap();
+    let run = |file| {
+        Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(["--svg", file])
+            .output()
+            .unwrap()
+    };
+
+    let output = run("level.svg");
+    assert!(output.status.success(), "{output:?}");
+    let lvl: Lvl = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!((lvl.start, lvl.end), (Point::new(0, 1), Point::new(4, 2)));
+    assert_eq!(lvl.walls.len(), 2);
+
+    let output = run("broken.svg");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("InvalidAttribute") && stderr.contains("width"),
+        "{stderr}"
+    );
+}
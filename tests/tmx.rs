@@ -0,0 +1,115 @@
+use std::{fs, process::Command};
+
+use maze_lvl_maker::{parse_ascii, scan_grid, Grid, LvlBuilder, Point, ScanOptions, Wrap};
+use roxmltree::Document;
+
+const LEVEL: &str = "\
+#######
+#S..#C#
+#.#...#
+#...#E#
+#######
+";
+
+/// Written by `maze-lvl-maker --ascii` of `LEVEL` with `--wrap x
+/// --cell-size 16 -o level.tmx`, in the layout Tiled 1.10 saves maps in.
+/// Regenerate it when the output format changes on purpose.
+const GOLDEN: &str = include_str!("fixtures/level.tmx");
+
+#[test]
+fn tmx_matches_golden_fixture() {
+    let mut lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    lvl.set_wrap(Wrap::X);
+    assert_eq!(lvl.to_tmx(16), GOLDEN);
+}
+
+#[test]
+fn tile_layer_holds_the_square_codes() {
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    let tmx = lvl.to_tmx(1);
+    let document = Document::parse(&tmx).unwrap();
+    let map = document.root_element();
+    assert_eq!(map.attribute("tilewidth"), Some("1"));
+    assert!(!map.children().any(|child| child.has_tag_name("properties")));
+
+    let data = map
+        .descendants()
+        .find(|node| node.has_tag_name("data"))
+        .unwrap();
+    assert_eq!(data.attribute("encoding"), Some("csv"));
+    let gids: Vec<Vec<u8>> = data
+        .text()
+        .unwrap()
+        .trim()
+        .lines()
+        .map(|row| {
+            row.trim_end_matches(',')
+                .split(',')
+                .map(|gid| gid.parse().unwrap())
+                .collect()
+        })
+        .collect();
+    assert_eq!(gids, Grid::from_lvl(&lvl).codes());
+}
+
+#[test]
+fn points_are_named_objects() {
+    let lvl = LvlBuilder::new(4, 3)
+        .start(Point::new(0, 0))
+        .end(Point::new(3, 2))
+        .checkpoint(Point::new(1, 1))
+        .optional_checkpoint(Point::new(2, 1))
+        .build()
+        .unwrap();
+    let tmx = lvl.to_tmx(8);
+    let document = Document::parse(&tmx).unwrap();
+    let objects: Vec<_> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("object"))
+        .map(|object| {
+            assert!(object.children().any(|child| child.has_tag_name("point")));
+            (
+                object.attribute("name").unwrap(),
+                object.attribute("x").unwrap(),
+                object.attribute("y").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        objects,
+        [
+            ("start", "4", "4"),
+            ("end", "28", "20"),
+            ("checkpoint", "12", "12"),
+            ("optional checkpoint", "20", "12"),
+        ]
+    );
+    assert_eq!(document.root_element().attribute("nextobjectid"), Some("5"));
+}
+
+#[test]
+fn format_follows_the_extension() {
+    let dir = std::env::temp_dir().join("maze-lvl-maker-tmx");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.txt"), LEVEL).unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+            .current_dir(&dir)
+            .args(["--ascii", "level.txt"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["-o", "level.tmx"]);
+    assert!(output.status.success());
+    let lvl = scan_grid(&parse_ascii(LEVEL).unwrap(), ScanOptions::default());
+    assert_eq!(
+        fs::read_to_string(dir.join("level.tmx")).unwrap(),
+        lvl.to_tmx(1)
+    );
+
+    let output = run(&["--format", "tmx", "--emit", "grid"]);
+    assert!(!output.status.success());
+}
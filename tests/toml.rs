@@ -0,0 +1,66 @@
+#![cfg(feature = "toml")]
+
+use maze_lvl_maker::{LvlBuilder, Point, Wall};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Walls {
+    walls: Vec<Wall>,
+}
+
+#[test]
+fn single_square_end_equal_to_start_reads_as_no_end() {
+    let toml = "\
+[[walls]]
+orientation = \"point\"
+start = { x = 2, y = 1 }
+end = { x = 2, y = 1 }
+
+[[walls]]
+start = { x = 4, y = 4 }
+
+[[walls]]
+start = { x = 0, y = 0 }
+end = { x = 3, y = 0 }
+";
+    let walls = toml::from_str::<Walls>(toml).unwrap().walls;
+    assert_eq!(
+        walls,
+        [
+            Wall {
+                start: Point::new(2, 1),
+                end: None,
+            },
+            Wall {
+                start: Point::new(4, 4),
+                end: None,
+            },
+            Wall {
+                start: Point::new(0, 0),
+                end: Some(Point::new(3, 0)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn level_walls_survive_toml() {
+    let lvl = LvlBuilder::new(5, 4)
+        .start(Point::new(0, 3))
+        .end(Point::new(4, 3))
+        .wall(Point::new(0, 0), Point::new(4, 0))
+        .wall(Point::new(2, 2), Point::new(2, 2))
+        .build()
+        .unwrap();
+
+    // What `--format toml` writes, TOML has no null so `end` repeats `start`
+    let mut json = serde_json::to_value(&lvl).unwrap();
+    for wall in json["walls"].as_array_mut().unwrap() {
+        if wall["end"].is_null() {
+            wall["end"] = wall["start"].clone();
+        }
+    }
+    let text = toml::to_string(&json).unwrap();
+
+    assert_eq!(toml::from_str::<Walls>(&text).unwrap().walls, lvl.walls);
+}
@@ -0,0 +1,238 @@
+use maze_lvl_maker::{parse_ascii, scan_grid, Lvl, Mesh, Point, Rect, ScanOptions, SquareType};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+fn scan(mesh: Mesh) -> Lvl {
+    let grid = parse_ascii(LEVEL).unwrap();
+    scan_grid(
+        &grid,
+        ScanOptions {
+            mesh,
+            ..ScanOptions::default()
+        },
+    )
+}
+
+#[test]
+fn mirroring_twice_is_the_original() {
+    for mesh in [
+        Mesh::Lines,
+        Mesh::Rect,
+        Mesh::Polyline,
+        Mesh::Contour,
+        Mesh::Marching,
+    ] {
+        let lvl = scan(mesh);
+        assert_eq!(lvl.mirror_horizontal().mirror_horizontal(), lvl);
+        assert_eq!(lvl.mirror_vertical().mirror_vertical(), lvl);
+    }
+}
+
+#[test]
+fn mirrored_squares_match() {
+    let lvl = scan(Mesh::Lines);
+    let horizontal = lvl.mirror_horizontal();
+    let vertical = lvl.mirror_vertical();
+    for y in 0..lvl.height {
+        for x in 0..lvl.width {
+            let square = lvl.cell_at(x, y);
+            assert_eq!(horizontal.cell_at(lvl.width - 1 - x, y), square);
+            assert_eq!(vertical.cell_at(x, lvl.height - 1 - y), square);
+        }
+    }
+}
+
+#[test]
+fn mirrored_walls_start_top_left() {
+    for wall in scan(Mesh::Lines)
+        .mirror_horizontal()
+        .mirror_vertical()
+        .walls
+    {
+        if let Some(end) = wall.end {
+            assert!(wall.start.x <= end.x && wall.start.y <= end.y);
+        }
+    }
+}
+
+#[test]
+fn four_quarter_turns_are_the_original() {
+    for mesh in [
+        Mesh::Lines,
+        Mesh::Rect,
+        Mesh::Polyline,
+        Mesh::Contour,
+        Mesh::Marching,
+    ] {
+        let lvl = scan(mesh);
+        assert_eq!(lvl.rotate_90().rotate_270(), lvl);
+        assert_eq!(lvl.rotate_180().rotate_180(), lvl);
+    }
+}
+
+#[test]
+fn rotated_squares_match() {
+    let lvl = scan(Mesh::Lines);
+    let rotated = lvl.rotate_90();
+    assert_eq!((rotated.width, rotated.height), (lvl.height, lvl.width));
+    for y in 0..lvl.height {
+        for x in 0..lvl.width {
+            assert_eq!(rotated.cell_at(lvl.height - 1 - y, x), lvl.cell_at(x, y));
+        }
+    }
+
+    for wall in rotated.walls {
+        if let Some(end) = wall.end {
+            assert!(wall.start.x <= end.x && wall.start.y <= end.y);
+        }
+    }
+}
+
+#[test]
+fn subregion_undoes_merge() {
+    let lvl = scan(Mesh::Lines);
+    let offset = Point::new(lvl.width, 2);
+    let merged = lvl.merge(&lvl, offset);
+
+    let tile = merged.subregion(Rect {
+        x: offset.x,
+        y: offset.y,
+        width: lvl.width,
+        height: lvl.height,
+    });
+    assert_eq!((tile.width, tile.height), (lvl.width, lvl.height));
+    assert_eq!(tile.walls, lvl.walls);
+    assert_eq!(tile.checkpoints, lvl.checkpoints);
+}
+
+#[test]
+fn walls_are_clipped_to_the_subregion() {
+    let lvl = scan(Mesh::Lines);
+    let rect = Rect {
+        x: 1,
+        y: 1,
+        width: 4,
+        height: 3,
+    };
+    let tile = lvl.subregion(rect);
+    for y in 0..rect.height {
+        for x in 0..rect.width {
+            assert_eq!(
+                tile.cell_at(x, y) == SquareType::Wall,
+                lvl.cell_at(x + rect.x, y + rect.y) == SquareType::Wall,
+                "square {x},{y}"
+            );
+        }
+    }
+}
+
+#[test]
+fn empty_subregion_is_an_empty_level() {
+    let lvl = scan(Mesh::Lines);
+    for (width, height) in [(0, 3), (4, 0), (0, 0)] {
+        let tile = lvl.subregion(Rect {
+            x: 2,
+            y: 1,
+            width,
+            height,
+        });
+        assert_eq!((tile.width, tile.height), (width, height));
+        assert!(tile.walls.is_empty());
+        assert!(tile.checkpoints.is_empty());
+        assert_eq!((tile.start, tile.end), (Point::new(0, 0), Point::new(0, 0)));
+    }
+}
+
+#[test]
+fn subregion_past_the_largest_coordinate() {
+    let lvl = scan(Mesh::Rect);
+    let tile = lvl.subregion(Rect {
+        x: 3,
+        y: 2,
+        width: u32::MAX,
+        height: u32::MAX,
+    });
+    assert_eq!(tile.start, Point::new(0, 0));
+    assert_eq!(tile.end, Point::new(4, 1));
+    assert!(!tile.rects.is_empty());
+}
+
+#[test]
+fn rects_reaching_past_the_largest_coordinate() {
+    let edge = Rect {
+        x: u32::MAX - 1,
+        y: 5,
+        width: 10,
+        height: 2,
+    };
+    assert!(edge.contains(u32::MAX, 6));
+    assert!(!edge.contains(u32::MAX, 7));
+    assert!(!edge.contains(0, 5));
+
+    let whole = Rect {
+        x: 0,
+        y: 0,
+        width: u32::MAX,
+        height: u32::MAX,
+    };
+    assert_eq!(
+        edge.intersection(whole),
+        Some(Rect {
+            x: u32::MAX - 1,
+            y: 5,
+            width: 1,
+            height: 2,
+        })
+    );
+    let empty = Rect { width: 0, ..edge };
+    assert!(!empty.contains(u32::MAX - 1, 5));
+    assert_eq!(empty.intersection(whole), None);
+}
+
+#[test]
+fn tiling_places_copies_next_to_each_other() {
+    let lvl = scan(Mesh::Lines);
+    let tiled = lvl.tile(2, 3);
+    assert_eq!((tiled.width, tiled.height), (18, 15));
+    assert_eq!((tiled.start, tiled.end), (lvl.start, lvl.end));
+    assert_eq!(tiled.walls.len(), lvl.walls.len() * 6);
+    assert_eq!(tiled.checkpoints.len(), lvl.checkpoints.len() * 6);
+    for (x, y) in [(0, 0), (9, 0), (0, 10), (9, 10)] {
+        for checkpoint in &lvl.checkpoints {
+            assert!(tiled
+                .checkpoints
+                .contains(&Point::new(checkpoint.x + x, checkpoint.y + y)));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "the tiled level is too wide")]
+fn tiling_past_the_largest_size_panics() {
+    scan(Mesh::Lines).tile(u32::MAX / 9 + 1, 1);
+}
+
+#[test]
+fn tile_flag_rejects_sizes_that_dont_fit() {
+    let path = std::env::temp_dir().join("maze-lvl-maker-tile-overflow.txt");
+    std::fs::write(&path, LEVEL).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .arg("--ascii")
+        .arg(&path)
+        .args(["--tile", "1000000000x1"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("--tile 1000000000x1 of a 9x5 level is more than 4294967295 squares"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use maze_lvl_maker::{Lvl, FORMAT_VERSION};
+
+/// Written by the first builds, before walls had an `orientation`.
+const V1: &str = include_str!("fixtures/v1.json");
+/// `V1` upgraded with `--pretty`.
+const V2: &str = include_str!("fixtures/v2.json");
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("maze-lvl-maker-upgrade-{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn upgrade(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_maze-lvl-maker"))
+        .current_dir(dir)
+        .arg("upgrade")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn every_fixture_reads_at_its_version() {
+    let v1: Lvl = serde_json::from_str(V1).unwrap();
+    let v2: Lvl = serde_json::from_str(V2).unwrap();
+    assert_eq!(v1.format_version, 1);
+    assert_eq!(v2.format_version, FORMAT_VERSION);
+
+    let mut upgraded = v1;
+    upgraded.upgrade();
+    assert_eq!(upgraded, v2);
+}
+
+#[test]
+fn upgrade_rewrites_old_files_at_the_current_version() {
+    let dir = dir("rewrite");
+    fs::write(dir.join("level.json"), V1).unwrap();
+    let output = upgrade(&dir, &["--pretty", "level.json"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(fs::read_to_string(dir.join("level.json")).unwrap(), V2);
+
+    // Already current, so left as it is even without --pretty
+    let output = upgrade(&dir, &["level.json"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(fs::read_to_string(dir.join("level.json")).unwrap(), V2);
+}
+
+#[test]
+fn compressed_files_stay_compressed() {
+    let dir = dir("gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(V1.as_bytes()).unwrap();
+    fs::write(dir.join("level.json.gz"), encoder.finish().unwrap()).unwrap();
+
+    let output = upgrade(&dir, &["--pretty", "level.json.gz"]);
+    assert!(output.status.success(), "{output:?}");
+    let mut json = String::new();
+    GzDecoder::new(fs::read(dir.join("level.json.gz")).unwrap().as_slice())
+        .read_to_string(&mut json)
+        .unwrap();
+    assert_eq!(json, V2);
+}
+
+#[test]
+fn single_squares_lose_their_repeated_end() {
+    let repeated = V1.replace(r#""end":null"#, r#""end":{"x":7,"y":7}"#);
+    let lvl: Lvl = serde_json::from_str(&repeated).unwrap();
+    assert_eq!(lvl, serde_json::from_str::<Lvl>(V1).unwrap());
+    assert_eq!(lvl.walls[4].end, None);
+}
+
+#[test]
+fn future_versions_are_refused() {
+    let future = V2.replacen(
+        &format!(r#""format_version": {FORMAT_VERSION}"#),
+        &format!(r#""format_version": {}"#, FORMAT_VERSION + 1),
+        1,
+    );
+    let error = serde_json::from_str::<Lvl>(&future).unwrap_err();
+    assert!(error.to_string().contains("isn't supported"), "{error}");
+
+    // Nothing is rewritten when one of the files can't be read
+    let dir = dir("future");
+    fs::write(dir.join("old.json"), V1).unwrap();
+    fs::write(dir.join("future.json"), &future).unwrap();
+    let output = upgrade(&dir, &["old.json", "future.json"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("future.json"), "{stderr}");
+    assert_eq!(fs::read_to_string(dir.join("old.json")).unwrap(), V1);
+    assert_eq!(fs::read_to_string(dir.join("future.json")).unwrap(), future);
+}
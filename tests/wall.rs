@@ -0,0 +1,29 @@
+use maze_lvl_maker::{Point, Wall};
+
+fn wall(start: (u32, u32), end: Option<(u32, u32)>) -> Wall {
+    Wall {
+        start: Point::new(start.0, start.1),
+        end: end.map(|(x, y)| Point::new(x, y)),
+    }
+}
+
+#[test]
+fn single_square_wall_has_length_one() {
+    assert_eq!(wall((3, 4), None).length(), 1);
+    assert_eq!(wall((3, 4), Some((3, 4))).length(), 1);
+}
+
+#[test]
+fn length_counts_both_ends() {
+    assert_eq!(wall((2, 5), Some((7, 5))).length(), 6);
+    assert_eq!(wall((1, 0), Some((1, 9))).length(), 10);
+}
+
+#[test]
+fn length_is_the_same_from_either_end() {
+    let forward = wall((0, 3), Some((4, 3)));
+    let backward = wall((4, 3), Some((0, 3)));
+    assert_eq!(forward.length(), 5);
+    assert_eq!(backward.length(), forward.length());
+    assert_eq!(forward.length() as usize, forward.squares().count());
+}
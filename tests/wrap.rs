@@ -0,0 +1,92 @@
+use maze_lvl_maker::{parse_ascii, Grid, Point, Wall, Wrap};
+
+/// The end is only reachable through the tunnel across the left and right
+/// edge.
+const TUNNEL: &str = "\
+##.####
+..S#E..
+##.####
+";
+
+#[test]
+fn corridor_only_reachable_across_the_seam() {
+    let grid = parse_ascii(TUNNEL).unwrap();
+    let mut lvl = grid.to_lvl().unwrap();
+    assert!(!lvl.is_solvable());
+    assert!(lvl.border_gaps().len() > 2);
+
+    lvl.set_wrap(Wrap::X);
+    assert!(lvl.is_solvable());
+    assert_eq!(lvl.shortest_path_length(), Some(5));
+    // Only the dead ends at the top and bottom are still open
+    assert_eq!(lvl.border_gaps().len(), 2);
+}
+
+#[test]
+fn walls_are_joined_across_the_seam() {
+    let mut grid = parse_ascii(TUNNEL).unwrap();
+    grid.set_wrap(Wrap::X);
+    let lvl = grid.to_lvl().unwrap();
+
+    let wrapping: Vec<Wall> = lvl
+        .walls
+        .iter()
+        .copied()
+        .filter(|wall| wall.wraps())
+        .collect();
+    assert_eq!(
+        wrapping,
+        [
+            Wall {
+                start: Point::new(3, 2),
+                end: Some(Point::new(1, 2)),
+            },
+            Wall {
+                start: Point::new(3, 0),
+                end: Some(Point::new(1, 0)),
+            },
+        ]
+    );
+    assert!(wrapping[0].contains(0, 2) && wrapping[0].contains(6, 2));
+    assert!(!wrapping[0].contains(2, 2));
+
+    // Painting the joined walls gives back the same squares
+    assert_eq!(Grid::from_lvl(&lvl), grid);
+}
+
+#[test]
+fn vertical_walls_wrap_top_to_bottom() {
+    let mut grid = parse_ascii(
+        "\
+.#.
+.#.
+S.E
+...
+.#.
+",
+    )
+    .unwrap();
+    grid.set_wrap(Wrap::Y);
+    let lvl = grid.to_lvl().unwrap();
+
+    assert_eq!(
+        lvl.walls,
+        [Wall {
+            start: Point::new(1, 4),
+            end: Some(Point::new(1, 1)),
+        }]
+    );
+    assert_eq!(Grid::from_lvl(&lvl), grid);
+}
+
+#[test]
+fn manhattan_path_length_takes_the_short_way_around() {
+    let mut lvl = parse_ascii(TUNNEL).unwrap().to_lvl().unwrap();
+    lvl.end = Point::new(6, 1);
+    assert_eq!(lvl.manhattan_path_length(), Some(4));
+
+    lvl.set_wrap(Wrap::X);
+    assert_eq!(lvl.manhattan_path_length(), Some(3));
+    // The tunnel is a straight line
+    assert_eq!(lvl.shortest_path_length(), Some(3));
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "yaml")]
+
+use maze_lvl_maker::{parse_ascii, scan_grid, Lvl, Mesh, ScanOptions, Wall};
+
+const LEVEL: &str = "\
+#########
+#S..#..C#
+#.#.#.#.#
+#.#...#E#
+#########
+";
+
+fn level(options: ScanOptions) -> Lvl {
+    scan_grid(&parse_ascii(LEVEL).unwrap(), options)
+}
+
+#[test]
+fn yaml_matches_json() {
+    for mesh in [Mesh::Lines, Mesh::Rect, Mesh::Polyline, Mesh::Marching] {
+        let json = serde_json::to_value(level(ScanOptions {
+            mesh,
+            ..ScanOptions::default()
+        }))
+        .unwrap();
+        let yaml = serde_yaml::to_string(&json).unwrap();
+        let read: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(read, json, "{mesh:?}");
+    }
+}
+
+#[test]
+fn yaml_walls_read_back_into_the_level() {
+    let lvl = level(ScanOptions::default());
+    let yaml = serde_yaml::to_string(&serde_json::to_value(&lvl).unwrap()).unwrap();
+    let read: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+    let walls: Vec<Wall> = serde_yaml::from_value(read["walls"].clone()).unwrap();
+    assert_eq!(walls, lvl.walls);
+}